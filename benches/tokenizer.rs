@@ -0,0 +1,69 @@
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+
+use nibi::app::ingot::{
+	parser::IngotParser, str_tokenizer::StrTokenizer, token::RawToken, tokenizer::IngotTokenizer,
+};
+
+fn sample_ingot(paragraphs: usize) -> String {
+	let mut src = String::from(
+		"id: 1\nstatus: publish\ntags: [rust, \"static site\", 日本語]\ncategories: diary // comment\npublished: 2024-01-01T00:00:00Z\n\nタイトル Title\n\n",
+	);
+	for i in 0..paragraphs {
+		src.push_str(&format!(
+			"段落{i}: 吾輩は猫である。名前はまだ無い。 The quick brown fox, jumps over (the) lazy dog. /* x */\n\n"
+		));
+	}
+	src.push_str("\n\n\nupdated: 2024-02-01T00:00:00Z\nexcerpt: 'bench'\n");
+	src
+}
+
+fn tokenize_by_chars(src: &str) -> usize {
+	let mut tokenizer = IngotTokenizer::new(src.chars().collect());
+	let mut count = 0;
+	loop {
+		match tokenizer.next_raw_token() {
+			(_, RawToken::Eos) => break,
+			token => {
+				black_box(token);
+				count += 1;
+			}
+		}
+	}
+	count
+}
+
+fn tokenize_by_str(src: &str) -> usize {
+	StrTokenizer::new(src).map(black_box).count()
+}
+
+fn bench_tokenizers(c: &mut Criterion) {
+	let mut group = c.benchmark_group("tokenize");
+	for paragraphs in [10, 1000] {
+		let src = sample_ingot(paragraphs);
+		group.throughput(Throughput::Bytes(src.len() as u64));
+		group.bench_with_input(BenchmarkId::new("chars", paragraphs), &src, |b, src| {
+			b.iter(|| tokenize_by_chars(src))
+		});
+		group.bench_with_input(BenchmarkId::new("str", paragraphs), &src, |b, src| {
+			b.iter(|| tokenize_by_str(src))
+		});
+	}
+	group.finish();
+}
+
+fn bench_parse(c: &mut Criterion) {
+	let mut group = c.benchmark_group("parse");
+	for paragraphs in [10, 1000] {
+		let src = sample_ingot(paragraphs);
+		group.throughput(Throughput::Bytes(src.len() as u64));
+		group.bench_with_input(BenchmarkId::new("str", paragraphs), &src, |b, src| {
+			b.iter(|| IngotParser::parse_str(src).unwrap())
+		});
+	}
+	group.finish();
+}
+
+criterion_group!(benches, bench_tokenizers, bench_parse);
+criterion_main!(benches);
@@ -1,18 +1,37 @@
+/// author module
+pub mod author;
 /// build module
 pub mod build;
 /// category module
 pub mod category;
 /// config module
 pub mod config;
+/// export module
+pub mod export;
 /// fs module
 pub mod fs;
+/// gears module
+pub mod gears;
+/// i18n module
+pub mod i18n;
 /// igata module
 pub mod igata;
+/// import module
+pub mod import;
 /// ingot module
 pub mod ingot;
+/// markdown module
+pub mod markdown;
 /// recipe module
 pub mod recipe;
 /// serde module
 pub mod serde;
+/// suggest module
+pub mod suggest;
 /// tag module
 pub mod tag;
+#[cfg(test)]
+/// testing module
+pub mod testing;
+/// value module
+pub mod value;
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+};
+
+use super::serde::{DeResult, FileType, read_deserialized_file, read_deserialized_value};
+
+#[derive(Debug, Deserialize, Serialize)]
+/// Represents an author of ingots. `Ingot::author` refers to the `id` of the author.
+pub struct Author {
+	/// The unique identifier of the author.
+	pub id: usize,
+	/// The login name of the author.
+	pub login: String,
+	/// The display name of the author.
+	pub name: String,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	/// The email address of the author.
+	pub email: String,
+	#[serde(skip_serializing_if = "Option::is_none", default)]
+	/// Optional attributes of the author.
+	pub opt_attr: Option<BTreeMap<String, String>>,
+}
+
+impl Author {
+	/// Creates a new `Author` instance with the given `id`, `login`, `name`, and `email`.
+	pub fn new(id: usize, login: String, name: String, email: String) -> Self {
+		Self {
+			id,
+			login,
+			name,
+			email,
+			opt_attr: None,
+		}
+	}
+}
+
+/// Returns the base path (without extension) of the authors list file in the given directory path.
+pub fn authors_file_base_path(dir_path: &Path) -> PathBuf {
+	dir_path.join("authors")
+}
+
+/// Reads the authors from the given reader.
+pub fn read_authors<R: std::io::Read>(reader: R, file_type: FileType) -> DeResult<Vec<Author>> {
+	read_deserialized_value(reader, file_type)
+}
+
+/// Reads the authors from the authors list file in the given directory path.
+/// The authors list file can be written in any supported file type.
+pub fn get_authors_from_dir_path(dir_path: &Path) -> Option<Vec<Author>> {
+	read_deserialized_file(&authors_file_base_path(dir_path)).ok()
+}
@@ -1,67 +1,934 @@
 use std::{
-	collections::BTreeMap,
+	collections::{BTreeMap, BTreeSet},
+	fmt::Display,
+	fs,
+	io::Error as IOError,
 	path::{Path, PathBuf},
 };
 
+use minijinja::Value;
+use rayon::{ThreadPoolBuilder, prelude::*};
 use walkdir::WalkDir;
 
 use crate::app::{
-	category::{get_categories_from_dir_path, get_index_map_from_categories},
-	fs::io::open_file_with_read_mode,
-	igata::pack::get_packs_from_names,
-	ingot::Ingot,
-	recipe::read_recipe,
+	category::{Category, get_categories_from_dir_path, get_index_map_from_categories},
+	config::{ExcerptConf, I18nConf},
+	fs::io::{open_file_with_overwrite_mode, open_file_with_read_mode, write_str},
+	gears::{GearError, Gears},
+	i18n::{is_lang_tag, translation_index},
+	igata::{
+		igata::{IGATA_EXT, Igata},
+		messages::load_messages,
+		pack::{PackError, PackProperties, dependency_ordered_names, get_packs_from_names},
+		schema::{SchemaViolation, validate_values},
+	},
+	ingot::{
+		Ingot,
+		error::ParseError,
+		ingot::{Status, UnresolvedKey},
+	},
+	recipe::{Recipe, read_named_recipe},
+	serde::DeError,
+	suggest::{did_you_mean, near_miss_names},
 	tag::get_index_map_from_tags,
+	value::{ValueMap, merge_values},
 };
+use crate::msg;
 
 use super::{
-	config::Config,
+	config::{Config, Profile},
 	tag::{Tag, get_tags_from_dir_path},
 };
+
+/// api module
+pub mod api;
+/// images module
+pub mod images;
+/// menu module
+pub mod menu;
+/// render module
+pub mod render;
+/// search module
+pub mod search;
+/// shortcode module
+pub mod shortcode;
+
+use api::{ApiContext, render_api};
+use images::{ImageError, Images};
+use menu::{MenuError, resolve_menus};
+use render::{RenderContext, RenderedIngot, RenderedPage, render_ingot};
+use search::{build_search_index, render_search_index};
+use shortcode::{ShortcodeError, Shortcodes};
+
+#[derive(Debug, Default, Clone)]
+/// Options for building the site.
+pub struct BuildOptions {
+	/// The number of threads. 0 means the number of available CPUs.
+	pub jobs: usize,
+	/// The recipe name to use instead of the one in the config or the profile.
+	pub recipe: Option<String>,
+	/// The build profile name in the config.
+	pub profile: Option<String>,
+}
+
+impl BuildOptions {
+	/// Creates a new `BuildOptions` with the given number of threads.
+	pub fn new(jobs: usize) -> Self {
+		Self {
+			jobs,
+			..Default::default()
+		}
+	}
+	/// Sets the recipe name.
+	pub fn recipe(mut self, recipe: Option<String>) -> Self {
+		self.recipe = recipe;
+		self
+	}
+	/// Sets the build profile name.
+	pub fn profile(mut self, profile: Option<String>) -> Self {
+		self.profile = profile;
+		self
+	}
+}
+
+#[derive(Debug)]
+/// Represents an error that can occur during building the site.
+pub enum BuildError {
+	/// Failed to read or parse an ingot file.
+	Ingot(PathBuf, ParseError),
+	/// The ID of an ingot is the same as the one of another ingot, given as the second path.
+	DuplicateId(PathBuf, usize, PathBuf),
+	/// The language of an ingot can not be used in the permalinks.
+	InvalidLang(PathBuf, String),
+	/// A category or tag of an ingot could not be collated.
+	Unresolved(PathBuf, UnresolvedKey),
+	/// The build profile was not found in the config. Near-miss profile names are suggested.
+	ProfileNotFound(String, Vec<String>),
+	/// Failed to read the recipe.
+	Recipe(DeError),
+	/// A pack specified in the recipe could not be resolved.
+	Pack(PackError),
+	/// A value does not satisfy the schema of a pack.
+	Value(String, SchemaViolation),
+	/// A menu entry of the recipe could not be resolved.
+	Menu(MenuError),
+	/// The igata of an igata table entry was not found in the packs. Near-miss igata names are suggested.
+	IgataNotFound(String, String, Vec<String>),
+	/// A gear failed.
+	Gear(GearError),
+	/// Failed to compile a template.
+	Compile(String, minijinja::Error),
+	/// Failed to expand the shortcodes of an ingot.
+	Shortcode(PathBuf, ShortcodeError),
+	/// Failed to render an ingot.
+	Render(PathBuf, minijinja::Error),
+	/// The output path of an ingot goes out of the site directory.
+	InvalidOutputPath(PathBuf, PathBuf),
+	/// The output path of an ingot is the same as the one of another ingot, given as the second path.
+	DuplicateOutputPath(PathBuf, PathBuf, PathBuf),
+	/// The output path of an ingot is the same as the one of another ingot in another language.
+	/// The languages need permalink prefixes.
+	LangOutputConflict(PathBuf, PathBuf, PathBuf),
+	/// The output path of an API endpoint for the tag or category path name goes out of the site directory.
+	InvalidTermPath(String, PathBuf),
+	/// Failed to serialize a JSON output, such as an API endpoint or the search index.
+	Api(PathBuf, serde_json::Error),
+	/// Failed to read a message file of a pack.
+	Messages(PathBuf, Box<DeError>),
+	/// Failed to process an image referenced from an ingot.
+	Image(ImageError),
+	/// Failed to write an output file.
+	IO(PathBuf, IOError),
+	/// Failed to prepare the thread pool.
+	ThreadPool(rayon::ThreadPoolBuildError),
+}
+
+impl Display for BuildError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			BuildError::Ingot(path, e) => write!(f, "{}: {}", path.display(), e),
+			BuildError::DuplicateId(path, id, other) => write!(
+				f,
+				"{}",
+				msg!(IngotIdDuplicate, path.display(), id, other.display())
+			),
+			BuildError::InvalidLang(path, lang) => {
+				write!(f, "{}", msg!(IngotLangInvalid, path.display(), lang))
+			}
+			BuildError::Unresolved(path, key) => write!(f, "{}: {}", path.display(), key),
+			BuildError::ProfileNotFound(name, suggestions) => {
+				write!(
+					f,
+					"{}",
+					msg!(ProfileNotFound, name, did_you_mean(suggestions))
+				)
+			}
+			BuildError::Recipe(e) => write!(f, "{}", msg!(RecipeReadFailed, e)),
+			BuildError::Pack(e) => write!(f, "{e}"),
+			BuildError::Value(pack, v) => write!(f, "{v} (pack: {pack})"),
+			BuildError::Menu(e) => write!(f, "{e}"),
+			BuildError::IgataNotFound(key, name, suggestions) => write!(
+				f,
+				"{}",
+				msg!(IgataNotFound, name, key, did_you_mean(suggestions))
+			),
+			BuildError::Gear(e) => write!(f, "{e}"),
+			BuildError::Compile(name, e) => write!(f, "{name}: {e:#}"),
+			BuildError::Shortcode(path, e) => write!(f, "{}: {e}", path.display()),
+			BuildError::Render(path, e) => write!(f, "{}: {:#}", path.display(), e),
+			BuildError::InvalidOutputPath(path, out) => write!(
+				f,
+				"{}",
+				msg!(OutputPathInvalid, path.display(), out.display())
+			),
+			BuildError::LangOutputConflict(path, other, out) => write!(
+				f,
+				"{}",
+				msg!(
+					OutputPathLangConflict,
+					path.display(),
+					other.display(),
+					out.display()
+				)
+			),
+			BuildError::DuplicateOutputPath(path, other, out) => write!(
+				f,
+				"{}",
+				msg!(
+					OutputPathDuplicate,
+					path.display(),
+					other.display(),
+					out.display()
+				)
+			),
+			BuildError::InvalidTermPath(path_name, out) => {
+				write!(f, "{}", msg!(TermPathInvalid, path_name, out.display()))
+			}
+			BuildError::Api(path, e) => write!(f, "{}: {e}", path.display()),
+			BuildError::Messages(path, e) => {
+				write!(f, "{}", msg!(MessagesReadFailed, path.display(), e))
+			}
+			BuildError::Image(e) => write!(f, "{e}"),
+			BuildError::IO(path, e) => write!(f, "{}: {}", path.display(), e),
+			BuildError::ThreadPool(e) => write!(f, "{}", msg!(ThreadsFailed, e)),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The mode of the build pipeline.
+pub enum BuildMode {
+	/// Builds the site and writes the output files.
+	Write,
+	/// Runs the whole pipeline and compiles every template without writing the output files.
+	/// The gears running external commands are skipped.
+	Check,
+}
+
 /// Builds the website of the project.
-pub fn build(config: Config, proj_path: &Path) {
+pub fn build(config: Config, proj_path: &Path, options: BuildOptions) {
+	let errors = run(&config, proj_path, &options, BuildMode::Write);
+	for e in errors.iter() {
+		println!("{e}");
+	}
+}
+
+/// Checks the project by running the build pipeline without writing the site directory.
+/// The gears running external commands are not called, so the values or pages they would provide are not checked.
+/// Returns the problems found.
+pub fn check(config: &Config, proj_path: &Path, options: BuildOptions) -> Vec<BuildError> {
+	run(config, proj_path, &options, BuildMode::Check)
+}
+
+fn run(
+	config: &Config,
+	proj_path: &Path,
+	options: &BuildOptions,
+	mode: BuildMode,
+) -> Vec<BuildError> {
+	let result = ThreadPoolBuilder::new()
+		.num_threads(options.jobs)
+		.build()
+		.map_err(BuildError::ThreadPool)
+		.map(|pool| pool.install(|| build_site(config, proj_path, options, mode)));
+	match result {
+		Ok(errors) => errors,
+		Err(e) => vec![e],
+	}
+}
+
+fn build_site(
+	config: &Config,
+	proj_path: &Path,
+	options: &BuildOptions,
+	mode: BuildMode,
+) -> Vec<BuildError> {
+	let mut errors = Vec::new();
+
+	let profile = match select_profile(config, options) {
+		Ok(profile) => profile,
+		Err(e) => {
+			errors.push(e);
+			return errors;
+		}
+	};
+
 	let zairyo_dir = config.get_dir_conf().get_zairyo_path(proj_path);
 
 	let categories = get_categories_from_dir_path(&zairyo_dir).unwrap_or_default();
 
 	let tags: Vec<Tag> = get_tags_from_dir_path(&zairyo_dir).unwrap_or_default();
 
-	let mut ingots: BTreeMap<usize, (PathBuf, Ingot)> = BTreeMap::new();
-
 	let index_categories_map = get_index_map_from_categories(&categories);
 	let index_tags_map = get_index_map_from_tags(&tags);
 
-	for entry in WalkDir::new(zairyo_dir)
-		.into_iter()
-		.filter_map(|e| e.ok())
-		.filter(|e| e.file_type().is_file() && e.file_name().to_string_lossy().ends_with(".ingot"))
-	{
-		let reader = open_file_with_read_mode(entry.path()).unwrap();
-		match Ingot::read(reader) {
-			Ok(mut ingot) => {
-				// ingotのカテゴリとタグを照合
-				ingot.collate_ids(&index_categories_map, &index_tags_map);
+	let (mut ingots, mut ingot_errors) = load_ingots(
+		&collect_ingot_paths(&zairyo_dir),
+		config.get_excerpt_conf(),
+		config.get_i18n_conf(),
+		&index_categories_map,
+		&index_tags_map,
+	);
+	errors.append(&mut ingot_errors);
 
-				ingots.insert(ingot.id, (entry.path().to_path_buf(), ingot));
-			}
-			Err(e) => {
-				println!("{}: {}", entry.path().display(), e);
-			}
-		}
+	// gearは宣言ファイル名の順に呼ばれる。gearが出力したファイルはページと一緒に書き出す
+	let (mut gears, gear_errors) = Gears::load(&config.get_dir_conf().get_gears_path(proj_path));
+	// 検査では外部コマンドを実行しない
+	if mode == BuildMode::Check {
+		gears.skip_processes();
 	}
+	errors.extend(gear_errors.into_iter().map(BuildError::Gear));
+	let mut gear_files = Vec::new();
+	errors.extend(
+		gears
+			.after_ingot_load(&mut ingots, &mut gear_files)
+			.into_iter()
+			.map(BuildError::Gear),
+	);
 
-	// レシピを読む
-	let recipe = match read_recipe(&config, proj_path) {
+	let recipe = match read_effective_recipe(config, proj_path, options, profile) {
 		Ok(recipe) => recipe,
 		Err(e) => {
-			println!("Failed to read recipe: {}", e);
-			return;
+			errors.push(e);
+			return errors;
 		}
 	};
 
 	// 必要なpackのデータを読み込んでおく
-	let _packs = get_packs_from_names(
-		recipe.get_pack_names(),
-		&config.get_dir_conf().get_igata_path(proj_path),
+	let (ordered_packs, pack_errors) =
+		resolve_packs(&recipe, &config.get_dir_conf().get_igata_path(proj_path));
+	let mut recipe_errors = pack_errors
+		.into_iter()
+		.map(BuildError::Pack)
+		.collect::<Vec<_>>();
+	let mut igata = Igata::new(
+		ordered_packs
+			.iter()
+			.map(|pack| pack.get_directory().to_path_buf())
+			.collect(),
+	);
+
+	gears.register_filters(&mut igata);
+	let (messages, message_errors) = load_messages(igata.get_pack_dirs());
+	errors.extend(
+		message_errors
+			.into_iter()
+			.map(|(path, e)| BuildError::Messages(path, Box::new(e))),
+	);
+
+	let template_names = igata.template_names();
+	let igata_ext = format!(".{IGATA_EXT}");
+	for (key, name) in recipe.igata_table.iter() {
+		if !igata.exists(name) {
+			let igata_names = template_names
+				.iter()
+				.map(|t| t.strip_suffix(&igata_ext).unwrap_or(t));
+			recipe_errors.push(BuildError::IgataNotFound(
+				key.clone(),
+				name.clone(),
+				near_miss_names(name, igata_names),
+			));
+		}
+	}
+
+	if mode == BuildMode::Check {
+		for name in template_names {
+			if let Err(e) = igata.compile(&name) {
+				errors.push(BuildError::Compile(name, e));
+			}
+		}
+	}
+
+	// メニューは言語ごとに解決し、同じ言語の生地にリンクする。同じエラーは一度だけ報告する
+	let default_lang = config.get_i18n_conf().get_default_lang();
+	let langs = ingots
+		.values()
+		.map(|(_, ingot)| ingot.lang.as_str())
+		.chain([default_lang])
+		.collect::<BTreeSet<_>>();
+	let mut menus = BTreeMap::new();
+	let mut menu_messages = BTreeSet::new();
+	for lang in langs {
+		let (resolved, menu_errors) = resolve_menus(
+			&recipe.menus,
+			&ingots,
+			&index_categories_map,
+			&index_tags_map,
+			lang,
+		);
+		menus.insert(lang.to_string(), resolved);
+		recipe_errors.extend(
+			menu_errors
+				.into_iter()
+				.filter(|e| menu_messages.insert(e.to_string()))
+				.map(BuildError::Menu),
+		);
+	}
+
+	// packの値の上にレシピの値を重ねる。先に指定されたpackの値が優先される
+	let mut values = ValueMap::new();
+	for pack in ordered_packs.iter().rev() {
+		merge_values(&mut values, pack.get_config().get_default_values());
+	}
+	merge_values(&mut values, recipe.values.clone());
+	// gearが注入した値もスキーマで検証する
+	errors.extend(
+		gears
+			.before_render(&mut values, &ingots, &mut gear_files)
+			.into_iter()
+			.map(BuildError::Gear),
+	);
+	// 同じ名前の値のスキーマは先に指定されたpackのものを使う
+	let mut checked_keys = BTreeSet::new();
+	for pack in ordered_packs.iter() {
+		let schema = pack
+			.get_config()
+			.schema
+			.iter()
+			.filter(|(key, _)| checked_keys.insert(key.as_str()))
+			.map(|(key, s)| (key.clone(), s.clone()))
+			.collect();
+		recipe_errors.extend(
+			validate_values(&schema, &values)
+				.into_iter()
+				.map(|v| BuildError::Value(pack.get_pack_name().to_string(), v)),
+		);
+	}
+
+	// レシピの内容に問題がある場合はレンダリングしない
+	if !recipe_errors.is_empty() {
+		errors.append(&mut recipe_errors);
+		return errors;
+	}
+	let value_map = values;
+	let values = Value::from_serialize(&value_map);
+	// 変換した画像はキャッシュに置き、内容が変わらなければ次のビルドでは作り直さない
+	let images = Images::new(
+		&recipe.images,
+		config.get_dir_conf().get_resources_path(proj_path),
+		config
+			.get_dir_conf()
+			.get_cache_path(proj_path)
+			.join("images"),
+		mode == BuildMode::Write,
 	);
+
+	let rc = RenderContext {
+		igata: &igata,
+		recipe: &recipe,
+		values: &values,
+		menus: &menus,
+		shortcodes: Shortcodes::new(&igata, &values, &ingots),
+		categories_index_map: &index_categories_map,
+		tags_index_map: &index_tags_map,
+		translations: translation_index(&ingots),
+		messages: &messages,
+		default_lang,
+		images: &images,
+	};
+	let pages = render_ingots(&rc, &ingots);
+	errors.extend(images.take_errors().into_iter().map(BuildError::Image));
+
+	let mut rendered = Vec::new();
+	let mut contents = BTreeMap::new();
+	// 同じ出力先のページは上書きせず、先にレンダリングしたものを残す
+	let mut outputs: BTreeMap<PathBuf, usize> = BTreeMap::new();
+	for page in pages {
+		match page {
+			Ok(page) => {
+				if let Some(other) = outputs.get(&page.page.path) {
+					let (path, other_path) = (ingot_path(&ingots, page.id), ingot_path(&ingots, *other));
+					// 言語ごとの接頭辞がないと、別の言語の同じpnameのページが重なる
+					errors.push(
+						if ingot_lang(&ingots, page.id) == ingot_lang(&ingots, *other) {
+							BuildError::DuplicateOutputPath(path, other_path, page.page.path)
+						} else {
+							BuildError::LangOutputConflict(path, other_path, page.page.path)
+						},
+					);
+					continue;
+				}
+				outputs.insert(page.page.path.clone(), page.id);
+				contents.insert(page.id, page.content_html);
+				rendered.push(page.page);
+			}
+			Err(e) => errors.push(e),
+		}
+	}
+	// JSONのAPIはgearの後処理の対象にしない
+	let mut api_pages = Vec::new();
+	let ac = ApiContext {
+		conf: &recipe.api,
+		values: &value_map,
+		menus: &menus[default_lang],
+		ingots: &ingots,
+		contents: &contents,
+		categories: &categories,
+		tags: &tags,
+	};
+	for page in render_api(&ac) {
+		match page {
+			Ok(page) => api_pages.push(page),
+			Err(e) => errors.push(e),
+		}
+	}
+	if recipe.search.enabled {
+		let index = build_search_index(
+			&recipe.search,
+			&ingots,
+			&contents,
+			&index_categories_map,
+			&index_tags_map,
+		);
+		match render_search_index(&index) {
+			Ok(page) => api_pages.push(page),
+			Err(e) => errors.push(e),
+		}
+	}
+	errors.extend(
+		gears
+			.after_render(&mut rendered, &mut gear_files)
+			.into_iter()
+			.map(BuildError::Gear),
+	);
+
+	if mode == BuildMode::Check {
+		return errors;
+	}
+	let site_dir = profile
+		.and_then(|p| p.get_site_path(proj_path))
+		.unwrap_or_else(|| config.get_dir_conf().get_site_path(proj_path));
+	// リソースは元のまま先に置き、同じパスのページや変換した画像で上書きする
+	errors.extend(copy_resources(
+		&config.get_dir_conf().get_resources_path(proj_path),
+		&site_dir,
+	));
+	for page in rendered
+		.iter()
+		.chain(api_pages.iter())
+		.chain(gear_files.iter())
+	{
+		if let Err(e) = write_page(&site_dir, page) {
+			errors.push(e);
+		}
+	}
+	for variant in images.variants() {
+		if let Err(e) = copy_file(&variant.cache_path, &site_dir.join(&variant.site_path)) {
+			errors.push(e);
+		}
+	}
+	let mut after_files = Vec::new();
+	errors.extend(
+		gears
+			.after_build(&site_dir, &rendered, &mut after_files)
+			.into_iter()
+			.map(BuildError::Gear),
+	);
+	for file in after_files.iter() {
+		if let Err(e) = write_page(&site_dir, file) {
+			errors.push(e);
+		}
+	}
+
+	errors
+}
+
+/// Returns the build profile selected by the options, if any.
+pub fn select_profile<'a>(
+	config: &'a Config,
+	options: &BuildOptions,
+) -> Result<Option<&'a Profile>, BuildError> {
+	let Some(name) = &options.profile else {
+		return Ok(None);
+	};
+	match config.get_profile(name) {
+		Some(profile) => Ok(Some(profile)),
+		None => {
+			let names = config.get_profiles().keys().map(|s| s.as_str());
+			Err(BuildError::ProfileNotFound(
+				name.clone(),
+				near_miss_names(name, names),
+			))
+		}
+	}
+}
+
+/// Reads the recipe used for the build, with the value overrides of the profile merged.
+pub fn read_effective_recipe(
+	config: &Config,
+	proj_path: &Path,
+	options: &BuildOptions,
+	profile: Option<&Profile>,
+) -> Result<Recipe, BuildError> {
+	// レシピを読む。指定の優先順位はオプション > プロファイル > config
+	let recipe_name = options
+		.recipe
+		.clone()
+		.or_else(|| profile.and_then(|p| p.get_recipe().cloned()))
+		.unwrap_or_else(|| config.get_recipe().clone());
+	let mut recipe =
+		read_named_recipe(config, proj_path, recipe_name).map_err(BuildError::Recipe)?;
+	if let Some(profile) = profile {
+		merge_values(&mut recipe.values, profile.get_values().clone());
+	}
+	Ok(recipe)
+}
+
+/// Resolves the packs of the recipe with the packs they depend on, in the order of priority.
+/// Returns the found packs and the problems for the packs which could not be resolved.
+pub fn resolve_packs(recipe: &Recipe, igata_dir: &Path) -> (Vec<PackProperties>, Vec<PackError>) {
+	let pack_names = recipe.get_pack_names_dedup_ordered();
+	let (mut packs, pack_errors) = get_packs_from_names(&pack_names, igata_dir);
+	// レシピでの指定順にpackを並べ、各packの後にその依存packを置く。先に並んだpackが優先される
+	let ordered_packs = dependency_ordered_names(&pack_names, &packs)
+		.iter()
+		.filter_map(|name| packs.remove(name))
+		.collect();
+	(ordered_packs, pack_errors)
+}
+
+/// Returns the paths of the ingot files in the given directory, sorted by file name.
+pub fn collect_ingot_paths(zairyo_dir: &Path) -> Vec<PathBuf> {
+	WalkDir::new(zairyo_dir)
+		.sort_by_file_name()
+		.into_iter()
+		.filter_map(|e| e.ok())
+		.filter(|e| e.file_type().is_file() && e.file_name().to_string_lossy().ends_with(".ingot"))
+		.map(|e| e.into_path())
+		.collect()
+}
+
+fn load_ingot(
+	path: &Path,
+	excerpt_conf: &ExcerptConf,
+	i18n_conf: &I18nConf,
+	categories_index_map: &BTreeMap<usize, &Category>,
+	tags_index_map: &BTreeMap<usize, &Tag>,
+) -> Result<(Ingot, Vec<UnresolvedKey>), ParseError> {
+	let reader = open_file_with_read_mode(path).map_err(ParseError::IO)?;
+	let mut ingot = Ingot::read(reader)?;
+	ingot.path = path.to_path_buf();
+	// ingotのカテゴリとタグを照合
+	let unresolved = ingot.collate_ids(categories_index_map, tags_index_map);
+	ingot.fill_excerpt(excerpt_conf);
+	ingot.fill_lang(i18n_conf);
+	Ok((ingot, unresolved))
+}
+
+/// Loads the ingots from the given paths in parallel.
+/// The results are merged in the order of the paths, so the output is deterministic.
+/// An ingot with the same ID as a former one, or with an invalid language, is reported and skipped.
+pub fn load_ingots(
+	paths: &[PathBuf],
+	excerpt_conf: &ExcerptConf,
+	i18n_conf: &I18nConf,
+	categories_index_map: &BTreeMap<usize, &Category>,
+	tags_index_map: &BTreeMap<usize, &Tag>,
+) -> (BTreeMap<usize, (PathBuf, Ingot)>, Vec<BuildError>) {
+	let results = paths
+		.par_iter()
+		.map(|path| {
+			load_ingot(
+				path,
+				excerpt_conf,
+				i18n_conf,
+				categories_index_map,
+				tags_index_map,
+			)
+			.map_err(|e| BuildError::Ingot(path.clone(), e))
+		})
+		.collect::<Vec<_>>();
+
+	let mut ingots: BTreeMap<usize, (PathBuf, Ingot)> = BTreeMap::new();
+	let mut errors = Vec::new();
+	for (path, result) in paths.iter().zip(results) {
+		match result {
+			Ok((ingot, unresolved)) => {
+				// 同じIDのingotは読み込んだ順に先のものを残す
+				if let Some((other, _)) = ingots.get(&ingot.id) {
+					errors.push(BuildError::DuplicateId(
+						path.clone(),
+						ingot.id,
+						other.clone(),
+					));
+					continue;
+				}
+				// 言語はパーマリンクの接頭辞になるため、パスとして安全なものに限る
+				if !is_lang_tag(&ingot.lang) {
+					errors.push(BuildError::InvalidLang(path.clone(), ingot.lang));
+					continue;
+				}
+				errors.extend(
+					unresolved
+						.into_iter()
+						.map(|key| BuildError::Unresolved(path.clone(), key)),
+				);
+				ingots.insert(ingot.id, (path.clone(), ingot));
+			}
+			Err(e) => errors.push(e),
+		}
+	}
+	(ingots, errors)
+}
+
+/// Renders the published ingots in parallel. The results are in the order of the ingot IDs.
+pub fn render_ingots(
+	rc: &RenderContext,
+	ingots: &BTreeMap<usize, (PathBuf, Ingot)>,
+) -> Vec<Result<RenderedIngot, BuildError>> {
+	ingots
+		.values()
+		.filter(|(_, ingot)| matches!(ingot.status, Status::Publish))
+		.collect::<Vec<_>>()
+		.par_iter()
+		.map(|(_, ingot)| render_ingot(rc, ingot))
+		.collect()
+}
+
+fn ingot_path(ingots: &BTreeMap<usize, (PathBuf, Ingot)>, id: usize) -> PathBuf {
+	ingots
+		.get(&id)
+		.map(|(path, _)| path.clone())
+		.unwrap_or_default()
+}
+
+fn ingot_lang(ingots: &BTreeMap<usize, (PathBuf, Ingot)>, id: usize) -> &str {
+	ingots
+		.get(&id)
+		.map(|(_, ingot)| ingot.lang.as_str())
+		.unwrap_or_default()
+}
+
+fn copy_file(from: &Path, to: &Path) -> Result<(), BuildError> {
+	if let Some(parent) = to.parent() {
+		fs::create_dir_all(parent).map_err(|e| BuildError::IO(parent.to_path_buf(), e))?;
+	}
+	fs::copy(from, to)
+		.map(|_| ())
+		.map_err(|e| BuildError::IO(to.to_path_buf(), e))
+}
+
+/// Copies the files in the resources directory into the site directory as they are,
+/// keeping the paths from the resources directory. Symbolic links are not followed.
+fn copy_resources(resources_dir: &Path, site_dir: &Path) -> Vec<BuildError> {
+	WalkDir::new(resources_dir)
+		.into_iter()
+		.filter_map(|e| e.ok())
+		.filter(|e| e.file_type().is_file())
+		.filter_map(|e| {
+			let rel = e.path().strip_prefix(resources_dir).ok()?;
+			copy_file(e.path(), &site_dir.join(rel)).err()
+		})
+		.collect()
+}
+
+fn write_page(site_dir: &Path, page: &RenderedPage) -> Result<(), BuildError> {
+	let out_path = site_dir.join(&page.path);
+	if let Some(parent) = out_path.parent() {
+		fs::create_dir_all(parent).map_err(|e| BuildError::IO(parent.to_path_buf(), e))?;
+	}
+	open_file_with_overwrite_mode(&out_path)
+		.and_then(|file| write_str(file, &page.body))
+		.map(|_| ())
+		.map_err(|e| BuildError::IO(out_path, e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::{
+		serde::FileType,
+		testing::{TempDir, write_ingot, write_project},
+		value::RecipeValue,
+	};
+
+	fn site_files(site_dir: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+		WalkDir::new(site_dir)
+			.into_iter()
+			.filter_map(|e| e.ok())
+			.filter(|e| e.file_type().is_file())
+			.map(|e| {
+				let rel = e.path().strip_prefix(site_dir).unwrap().to_path_buf();
+				(rel, fs::read(e.path()).unwrap())
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_parallel_build() {
+		let dir = TempDir::new("build");
+		let config = write_project(&dir);
+		// 問題のあるingotも混ぜ、エラーの順番も比べる
+		fs::write(dir.join("zairyo/p00.ingot"), [0xff, 0xfe, 0xfd]).unwrap();
+		write_ingot(&dir, "p41.ingot", 3, "other", "Same ID");
+		write_ingot(&dir, "p42.ingot", 42, "post1", "Same pname");
+		write_ingot(&dir, "p43.ingot", 43, "../../escaped", "Escaped");
+
+		let site_dir = config.get_dir_conf().get_site_path(&dir);
+		let mut results = Vec::new();
+		for jobs in [1, 4] {
+			let _ = fs::remove_dir_all(&site_dir);
+			let errors = run(&config, &dir, &BuildOptions::new(jobs), BuildMode::Write)
+				.iter()
+				.map(|e| e.to_string())
+				.collect::<Vec<_>>();
+			results.push((errors, site_files(&site_dir)));
+		}
+		assert_eq!(results[0], results[1]);
+
+		let (errors, files) = &results[0];
+		assert_eq!(errors.len(), 4, "{errors:?}");
+		assert!(errors[0].contains("p00.ingot"));
+		assert!(errors[1].contains("p41.ingot") && errors[1].contains("p03.ingot"));
+		assert!(errors[2].contains("p42.ingot") && errors[2].contains("p01.ingot"));
+		assert!(errors[3].contains("p43.ingot"));
+		assert_eq!(files.len(), 40);
+		assert!(
+			String::from_utf8_lossy(&files[Path::new("post1/index.html")]).starts_with("Post 1\n")
+		);
+		assert!(
+			String::from_utf8_lossy(&files[Path::new("post3/index.html")]).starts_with("Post 3\n")
+		);
+		assert!(!dir.parent().unwrap().join("escaped").exists());
+	}
+
+	#[test]
+	fn test_lang_output_paths() {
+		let dir = TempDir::new("build_lang");
+		let config = write_project(&dir);
+		let write = |file: &str, id: usize, lang: &str| {
+			let src = format!("id: {id}\nstatus: publish\npname: about\nlang: {lang}\n\nAbout\n\nx\n");
+			fs::write(dir.join("zairyo").join(file), src).unwrap();
+		};
+		write("p41.ingot", 41, "ja");
+		write("p42.ingot", 42, "en");
+		write("p43.ingot", 43, "../evil");
+
+		let errors = check(&config, &dir, BuildOptions::new(2));
+		assert_eq!(errors.len(), 2, "{errors:?}");
+		assert!(
+			matches!(&errors[0], BuildError::InvalidLang(path, lang) if path.ends_with("p43.ingot") && lang == "../evil")
+		);
+		assert!(
+			matches!(&errors[1], BuildError::LangOutputConflict(path, other, _) if path.ends_with("p42.ingot") && other.ends_with("p41.ingot"))
+		);
+	}
+
+	#[test]
+	fn test_resources() {
+		let dir = TempDir::new("build_resources");
+		let config = write_project(&dir);
+		let resources_dir = config.get_dir_conf().get_resources_path(&dir);
+		for (path, body) in [
+			("css/site.css", "body {}"),
+			("posts/trip/photo.gif", "GIF89a"),
+			("post1/index.html", "resource"),
+		] {
+			let path = resources_dir.join(path);
+			fs::create_dir_all(path.parent().unwrap()).unwrap();
+			fs::write(path, body).unwrap();
+		}
+
+		let site_dir = config.get_dir_conf().get_site_path(&dir);
+		assert!(check(&config, &dir, BuildOptions::new(1)).is_empty());
+		assert!(!site_dir.exists());
+		let errors = run(&config, &dir, &BuildOptions::new(1), BuildMode::Write);
+		assert!(errors.is_empty(), "{errors:?}");
+		let files = site_files(&site_dir);
+		assert_eq!(files.len(), 42);
+		assert_eq!(files[Path::new("css/site.css")], b"body {}");
+		assert_eq!(files[Path::new("posts/trip/photo.gif")], b"GIF89a");
+		// 同じパスのページが優先される
+		assert!(
+			String::from_utf8_lossy(&files[Path::new("post1/index.html")]).starts_with("Post 1\n")
+		);
+	}
+
+	#[test]
+	fn test_profile() {
+		let dir = TempDir::new("build_profile");
+		write_project(&dir);
+		let config = Config::read(
+			r#"{
+				"project_name": "test",
+				"site_name": "test",
+				"recipe": "base",
+				"profiles": {
+					"prod": {"recipe": "prod", "site": "public", "values": {"b": {"c": 3}}},
+					"plain": {}
+				}
+			}"#
+			.as_bytes(),
+			FileType::Json,
+		)
+		.unwrap();
+		let values = r#"{"a": 1, "b": {"c": 2, "d": 4}}"#;
+		for (name, pack) in [("base", "base"), ("prod", "theme"), ("flag", "flag")] {
+			let values = if name == "flag" { "{}" } else { values };
+			let src = format!(r#"(pack: "{pack}", overrides: (igata_table: {{}}, values: {values}))"#);
+			fs::write(dir.join(format!("{name}.ron")), src).unwrap();
+		}
+		let recipe = |options: BuildOptions| {
+			let profile = select_profile(&config, &options).unwrap();
+			read_effective_recipe(&config, &dir, &options, profile).unwrap()
+		};
+
+		// 指定の優先順位はオプション > プロファイル > config
+		assert_eq!(recipe(BuildOptions::new(1)).get_pack_names(), ["base"]);
+		let plain = BuildOptions::new(1).profile(Some("plain".to_string()));
+		assert_eq!(recipe(plain).get_pack_names(), ["base"]);
+		let prod = BuildOptions::new(1).profile(Some("prod".to_string()));
+		let prod_recipe = recipe(prod.clone());
+		assert_eq!(prod_recipe.get_pack_names(), ["theme"]);
+		let flag = prod.clone().recipe(Some("flag".to_string()));
+		let flag_recipe = recipe(flag);
+		assert_eq!(flag_recipe.get_pack_names(), ["flag"]);
+
+		// プロファイルの値はレシピの値に重ねられる
+		let map = |entries: &[(&str, RecipeValue)]| {
+			RecipeValue::Map(
+				entries
+					.iter()
+					.map(|(k, v)| (k.to_string(), v.clone()))
+					.collect(),
+			)
+		};
+		assert_eq!(prod_recipe.values["a"], RecipeValue::Int(1));
+		assert_eq!(
+			prod_recipe.values["b"],
+			map(&[("c", RecipeValue::Int(3)), ("d", RecipeValue::Int(4))])
+		);
+		assert_eq!(flag_recipe.values["b"], map(&[("c", RecipeValue::Int(3))]));
+
+		assert!(matches!(
+			select_profile(&config, &BuildOptions::new(1).profile(Some("prd".to_string()))),
+			Err(BuildError::ProfileNotFound(name, s)) if name == "prd" && s == ["prod"]
+		));
+
+		// プロファイルの出力先に書き出す
+		let errors = run(&config, &dir, &prod, BuildMode::Write);
+		assert!(errors.is_empty(), "{errors:?}");
+		assert!(dir.join("public/post1/index.html").is_file());
+		assert!(!config.get_dir_conf().get_site_path(&dir).exists());
+	}
 }
@@ -0,0 +1,484 @@
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+};
+
+use jiff::Timestamp;
+use serde::Serialize;
+
+use super::{
+	BuildError,
+	menu::Menus,
+	render::{RenderedPage, collated_ids, term_path_name, url_of},
+};
+use crate::app::{
+	category::Category,
+	fs::path::is_contained_path,
+	ingot::{
+		Ingot,
+		ingot::{RKeyList, Status},
+	},
+	recipe::ApiConf,
+	tag::Tag,
+	value::ValueMap,
+};
+
+/// The directory of the JSON API endpoints in the site directory.
+pub const API_DIR: &str = "api";
+
+/// Returns the URL of the endpoint from the site root.
+pub fn api_url(rel_path: &str) -> String {
+	format!("/{API_DIR}/{rel_path}")
+}
+
+#[derive(Debug, Serialize)]
+/// A tag or a category referred from an ingot.
+pub struct TermRef<'a> {
+	/// The ID of the term.
+	pub id: usize,
+	/// The name of the term.
+	pub name: &'a str,
+	/// The path name of the term.
+	pub path_name: &'a str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	/// The URL of the index endpoint of the term, if enabled.
+	pub api: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+/// An entry of a term index, with the number of the published ingots in the term.
+pub struct TermEntry<'a> {
+	#[serde(flatten)]
+	/// The reference to the term.
+	pub term: TermRef<'a>,
+	/// The description of the term.
+	pub description: &'a str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	/// The ID of the parent category.
+	pub parent_id: Option<usize>,
+	/// The number of the published ingots in the term.
+	pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+/// The summary of an ingot, used in the lists.
+pub struct IngotSummary<'a> {
+	/// The ingot ID.
+	pub id: usize,
+	/// The title.
+	pub title: &'a str,
+	/// The page name.
+	pub pname: &'a str,
+	/// The build type.
+	pub to: &'a str,
+	#[serde(skip_serializing_if = "str::is_empty")]
+	/// The language code.
+	pub lang: &'a str,
+	#[serde(skip_serializing_if = "str::is_empty")]
+	/// The key linking the variants in other languages.
+	pub translation_key: &'a str,
+	/// The URL of the page.
+	pub url: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	/// The URL of the endpoint of the ingot, if enabled.
+	pub api: Option<String>,
+	/// The author ID.
+	pub author: usize,
+	/// The published timestamp.
+	pub published: Timestamp,
+	/// The updated timestamp.
+	pub updated: Timestamp,
+	/// The excerpt.
+	pub excerpt: &'a str,
+	/// The tags.
+	pub tags: Vec<TermRef<'a>>,
+	/// The categories.
+	pub categories: Vec<TermRef<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+/// An ingot with its content.
+pub struct IngotDetail<'a> {
+	#[serde(flatten)]
+	/// The summary of the ingot.
+	pub summary: IngotSummary<'a>,
+	/// The content in HTML, the same as the rendered page.
+	pub content: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+/// A term with the summaries of its published ingots.
+pub struct TermIndex<'a> {
+	/// The term.
+	pub term: TermEntry<'a>,
+	/// The published ingots in the term, newest first.
+	pub ingots: Vec<&'a IngotSummary<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+/// The site metadata.
+pub struct SiteMeta<'a> {
+	/// The template values.
+	pub values: &'a ValueMap,
+	/// The resolved menus.
+	pub menus: &'a Menus,
+	/// The URLs of the enabled endpoints, keyed by the setting name.
+	pub endpoints: BTreeMap<&'static str, String>,
+	/// The number of the published ingots.
+	pub ingots: usize,
+	/// The number of the tags.
+	pub tags: usize,
+	/// The number of the categories.
+	pub categories: usize,
+}
+
+/// The shared inputs to write the JSON API endpoints.
+pub struct ApiContext<'a> {
+	/// The settings of the endpoints.
+	pub conf: &'a ApiConf,
+	/// The template values.
+	pub values: &'a ValueMap,
+	/// The resolved menus.
+	pub menus: &'a Menus,
+	/// The ingots by their IDs.
+	pub ingots: &'a BTreeMap<usize, (PathBuf, Ingot)>,
+	/// The content HTML of the rendered ingots by their IDs.
+	pub contents: &'a BTreeMap<usize, String>,
+	/// The categories.
+	pub categories: &'a [Category],
+	/// The tags.
+	pub tags: &'a [Tag],
+}
+
+fn json_page<T: Serialize>(rel_path: String, value: &T) -> Result<RenderedPage, BuildError> {
+	let path = Path::new(API_DIR).join(rel_path);
+	let body = serde_json::to_string(value).map_err(|e| BuildError::Api(path.clone(), e))?;
+	Ok(RenderedPage { path, body })
+}
+
+/// Returns the endpoint of the tag or category in the directory, such as `tags/rust.json`.
+fn term_page<T: Serialize>(
+	dir: &str,
+	path_name: &str,
+	value: &T,
+) -> Result<RenderedPage, BuildError> {
+	let rel_path = format!("{dir}/{}.json", term_path_name(path_name));
+	if !is_contained_path(Path::new(&rel_path)) {
+		return Err(BuildError::InvalidTermPath(
+			path_name.to_string(),
+			Path::new(API_DIR).join(rel_path),
+		));
+	}
+	json_page(rel_path, value)
+}
+
+impl<'a> ApiContext<'a> {
+	/// Returns the reference to the tag with the name to show, such as the one in the language of an ingot.
+	fn tag_ref(&self, tag: &'a Tag, name: &'a str) -> TermRef<'a> {
+		TermRef {
+			id: tag.id,
+			name,
+			path_name: &tag.path_name,
+			api: self
+				.conf
+				.tags
+				.then(|| api_url(&format!("tags/{}.json", term_path_name(&tag.path_name)))),
+		}
+	}
+	/// Returns the reference to the category with the name to show, such as the one in the language of an ingot.
+	fn category_ref(&self, category: &'a Category, name: &'a str) -> TermRef<'a> {
+		TermRef {
+			id: category.id,
+			name,
+			path_name: &category.path_name,
+			api: self.conf.categories.then(|| {
+				api_url(&format!(
+					"categories/{}.json",
+					term_path_name(&category.path_name)
+				))
+			}),
+		}
+	}
+	fn summary(
+		&self,
+		ingot: &'a Ingot,
+		tags_index_map: &BTreeMap<usize, &'a Tag>,
+		categories_index_map: &BTreeMap<usize, &'a Category>,
+	) -> IngotSummary<'a> {
+		IngotSummary {
+			id: ingot.id,
+			title: &ingot.title,
+			pname: &ingot.pname,
+			lang: &ingot.lang,
+			translation_key: &ingot.translation_key,
+			to: ingot.to.name(),
+			url: url_of(ingot),
+			api: self
+				.conf
+				.ingot
+				.then(|| api_url(&format!("ingots/{}.json", ingot.id))),
+			author: ingot.author,
+			published: ingot.published,
+			updated: ingot.updated,
+			excerpt: &ingot.excerpt,
+			tags: collated_ids(&ingot.tags)
+				.iter()
+				.filter_map(|id| tags_index_map.get(id))
+				.map(|tag| self.tag_ref(tag, tag.name_in(&ingot.lang)))
+				.collect(),
+			categories: collated_ids(&ingot.categories)
+				.iter()
+				.filter_map(|id| categories_index_map.get(id))
+				.map(|category| self.category_ref(category, category.name_in(&ingot.lang)))
+				.collect(),
+		}
+	}
+}
+
+fn has_term(list: &RKeyList, id: usize) -> bool {
+	collated_ids(list).contains(&id)
+}
+
+/// Returns the JSON API endpoints enabled in the settings.
+///
+/// Only the published ingots which were rendered are included, and the lists are sorted newest first.
+/// The JSON is written without indentation.
+pub fn render_api(ac: &ApiContext) -> Vec<Result<RenderedPage, BuildError>> {
+	let conf = ac.conf;
+	let mut pages = Vec::new();
+	if !conf.is_enabled() {
+		return pages;
+	}
+	let tags_index_map: BTreeMap<usize, &Tag> = ac.tags.iter().map(|t| (t.id, t)).collect();
+	let categories = ac
+		.categories
+		.iter()
+		.flat_map(|c| c.get_descendants())
+		.collect::<Vec<_>>();
+	let categories_index_map: BTreeMap<usize, &Category> =
+		categories.iter().map(|c| (c.id, *c)).collect();
+
+	let mut ingots = ac
+		.ingots
+		.values()
+		.map(|(_, ingot)| ingot)
+		.filter(|ingot| {
+			matches!(ingot.status, Status::Publish) && ac.contents.contains_key(&ingot.id)
+		})
+		.collect::<Vec<_>>();
+	// 新しい順に並べる
+	ingots.sort_by(|a, b| b.published.cmp(&a.published).then(b.id.cmp(&a.id)));
+	let summaries = ingots
+		.iter()
+		.map(|ingot| ac.summary(ingot, &tags_index_map, &categories_index_map))
+		.collect::<Vec<_>>();
+
+	if conf.ingots {
+		pages.push(json_page("ingots.json".to_string(), &summaries));
+	}
+	if conf.ingot {
+		for ingot in ingots.iter() {
+			let detail = IngotDetail {
+				summary: ac.summary(ingot, &tags_index_map, &categories_index_map),
+				content: ac
+					.contents
+					.get(&ingot.id)
+					.map(|s| s.as_str())
+					.unwrap_or_default(),
+			};
+			pages.push(json_page(format!("ingots/{}.json", ingot.id), &detail));
+		}
+	}
+	if conf.tags {
+		let mut entries = Vec::new();
+		for tag in ac.tags.iter() {
+			let tagged = ingots
+				.iter()
+				.zip(&summaries)
+				.filter(|(ingot, _)| has_term(&ingot.tags, tag.id))
+				.map(|(_, summary)| summary)
+				.collect::<Vec<_>>();
+			let entry = || TermEntry {
+				term: ac.tag_ref(tag, &tag.name),
+				description: &tag.description,
+				parent_id: None,
+				count: tagged.len(),
+			};
+			let index = TermIndex {
+				term: entry(),
+				ingots: tagged.clone(),
+			};
+			pages.push(term_page("tags", &tag.path_name, &index));
+			entries.push(entry());
+		}
+		pages.push(json_page("tags.json".to_string(), &entries));
+	}
+	if conf.categories {
+		let mut entries = Vec::new();
+		for category in categories.iter() {
+			let in_category = ingots
+				.iter()
+				.zip(&summaries)
+				.filter(|(ingot, _)| has_term(&ingot.categories, category.id))
+				.map(|(_, summary)| summary)
+				.collect::<Vec<_>>();
+			let entry = || TermEntry {
+				term: ac.category_ref(category, &category.name),
+				description: &category.description,
+				parent_id: category.parent_id,
+				count: in_category.len(),
+			};
+			let index = TermIndex {
+				term: entry(),
+				ingots: in_category.clone(),
+			};
+			pages.push(term_page("categories", &category.path_name, &index));
+			entries.push(entry());
+		}
+		pages.push(json_page("categories.json".to_string(), &entries));
+	}
+	if conf.site {
+		let endpoints = [
+			("ingots", conf.ingots, "ingots.json"),
+			("ingot", conf.ingot, "ingots/{id}.json"),
+			("tags", conf.tags, "tags.json"),
+			("categories", conf.categories, "categories.json"),
+			("site", conf.site, "site.json"),
+		]
+		.into_iter()
+		.filter(|(_, enabled, _)| *enabled)
+		.map(|(key, _, rel_path)| (key, api_url(rel_path)))
+		.collect();
+		let meta = SiteMeta {
+			values: ac.values,
+			menus: ac.menus,
+			endpoints,
+			ingots: ingots.len(),
+			tags: ac.tags.len(),
+			categories: categories.len(),
+		};
+		pages.push(json_page("site.json".to_string(), &meta));
+	}
+	pages
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_render_api() {
+		let tags = vec![Tag::new(1, "rust".into(), "Rust".into(), String::new())];
+		let categories = vec![Category::new(3, "dev".into(), "Dev".into(), String::new())];
+		let mut ingots = BTreeMap::new();
+		for (id, status, published) in [
+			(1, Status::Publish, "2020-01-01T00:00:00Z"),
+			(2, Status::Publish, "2021-01-01T00:00:00Z"),
+			(3, Status::Draft, "2022-01-01T00:00:00Z"),
+		] {
+			let mut ingot = Ingot::new(id);
+			ingot.status = status;
+			ingot.title = format!("t{id}");
+			ingot.published = published.parse().unwrap();
+			ingot.tags = RKeyList::CollatedId(vec![1]);
+			ingot.categories = RKeyList::CollatedId(if id == 1 { vec![3] } else { vec![] });
+			ingots.insert(id, (PathBuf::new(), ingot));
+		}
+		let contents = [1, 2, 3]
+			.into_iter()
+			.map(|id| (id, format!("<p>{id}</p>")))
+			.collect();
+		let conf = ApiConf {
+			ingots: true,
+			ingot: true,
+			tags: true,
+			categories: false,
+			site: true,
+		};
+		let values = ValueMap::new();
+		let menus = Menus::new();
+		let ac = ApiContext {
+			conf: &conf,
+			values: &values,
+			menus: &menus,
+			ingots: &ingots,
+			contents: &contents,
+			categories: &categories,
+			tags: &tags,
+		};
+		let pages = render_api(&ac)
+			.into_iter()
+			.map(|p| p.unwrap())
+			.map(|p| (p.path.to_string_lossy().replace('\\', "/"), p.body))
+			.collect::<BTreeMap<_, _>>();
+		assert_eq!(
+			pages.keys().collect::<Vec<_>>(),
+			[
+				"api/ingots.json",
+				"api/ingots/1.json",
+				"api/ingots/2.json",
+				"api/site.json",
+				"api/tags.json",
+				"api/tags/rust.json"
+			]
+		);
+		let list: serde_json::Value = serde_json::from_str(&pages["api/ingots.json"]).unwrap();
+		assert_eq!(list[0]["id"], 2);
+		assert_eq!(list[0]["api"], "/api/ingots/2.json");
+		assert_eq!(list[1]["tags"][0]["api"], "/api/tags/rust.json");
+		assert!(list[1]["categories"][0].get("api").is_none());
+		let detail: serde_json::Value = serde_json::from_str(&pages["api/ingots/1.json"]).unwrap();
+		assert_eq!(detail["content"], "<p>1</p>");
+		let tag: serde_json::Value = serde_json::from_str(&pages["api/tags/rust.json"]).unwrap();
+		assert_eq!(tag["term"]["count"], 2);
+		let site: serde_json::Value = serde_json::from_str(&pages["api/site.json"]).unwrap();
+		assert_eq!(site["ingots"], 2);
+		assert!(site["endpoints"].get("categories").is_none());
+
+		let off = ApiConf::default();
+		assert!(render_api(&ApiContext { conf: &off, ..ac }).is_empty());
+	}
+
+	#[test]
+	fn test_term_paths() {
+		let tags = vec![
+			Tag::new(1, " /rust/ ".into(), "Rust".into(), String::new()),
+			Tag::new(2, "../../x".into(), "X".into(), String::new()),
+		];
+		let mut ingot = Ingot::new(1);
+		ingot.status = Status::Publish;
+		ingot.tags = RKeyList::CollatedId(vec![1]);
+		let ingots = BTreeMap::from([(1, (PathBuf::new(), ingot))]);
+		let contents = BTreeMap::from([(1, String::new())]);
+		let conf = ApiConf {
+			ingots: true,
+			ingot: false,
+			tags: true,
+			categories: false,
+			site: false,
+		};
+		let values = ValueMap::new();
+		let menus = Menus::new();
+		let ac = ApiContext {
+			conf: &conf,
+			values: &values,
+			menus: &menus,
+			ingots: &ingots,
+			contents: &contents,
+			categories: &[],
+			tags: &tags,
+		};
+		let pages = render_api(&ac);
+		assert_eq!(pages.len(), 4);
+		assert_eq!(
+			pages[1].as_ref().unwrap().path,
+			Path::new("api/tags/rust.json")
+		);
+		assert!(matches!(
+			&pages[2],
+			Err(BuildError::InvalidTermPath(path_name, _)) if path_name == "../../x"
+		));
+		let list: serde_json::Value = serde_json::from_str(&pages[0].as_ref().unwrap().body).unwrap();
+		assert_eq!(list[0]["tags"][0]["api"], "/api/tags/rust.json");
+	}
+}
@@ -0,0 +1,770 @@
+use std::{
+	collections::BTreeMap,
+	fmt::{Display, Write},
+	fs,
+	io::{Cursor, Error as IOError},
+	path::{Component, Path, PathBuf},
+	sync::{
+		Arc, Mutex, OnceLock,
+		atomic::{AtomicUsize, Ordering},
+	},
+};
+
+use image::{
+	DynamicImage, ImageEncoder, ImageError as CodecError, ImageReader,
+	codecs::{avif::AvifEncoder, jpeg::JpegEncoder, png::PngEncoder, webp::WebPEncoder},
+	imageops::FilterType,
+};
+
+use crate::{
+	app::{
+		markdown::{decode_basic_entities, escape_html, percent_decode},
+		recipe::{ImageFormat, ImagesConf},
+	},
+	msg,
+};
+
+/// The speed of the AVIF encoder, from 1 (slowest) to 10 (fastest).
+const AVIF_SPEED: u8 = 8;
+
+#[derive(Debug)]
+/// Represents an error while processing an image.
+pub enum ImageError {
+	/// Failed to read the source image.
+	Read(PathBuf, IOError),
+	/// The source image could not be decoded.
+	Decode(PathBuf, CodecError),
+	/// Failed to encode a variant of the image.
+	Encode(PathBuf, CodecError),
+	/// Failed to write a variant into the cache directory.
+	Write(PathBuf, IOError),
+}
+
+impl Display for ImageError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ImageError::Read(path, e) => {
+				write!(f, "{}", msg!(ImageReadFailed, path.display(), e))
+			}
+			ImageError::Decode(path, e) => {
+				write!(f, "{}", msg!(ImageDecodeFailed, path.display(), e))
+			}
+			ImageError::Encode(path, e) => {
+				write!(f, "{}", msg!(ImageEncodeFailed, path.display(), e))
+			}
+			ImageError::Write(path, e) => write!(f, "{}: {}", path.display(), e),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The encodings of the variants.
+pub enum Encoding {
+	/// PNG.
+	Png,
+	/// JPEG.
+	Jpeg,
+	/// WebP.
+	Webp,
+	/// AVIF.
+	Avif,
+}
+
+impl Encoding {
+	/// Returns the encoding of the source image from its extension.
+	pub fn from_path(path: &Path) -> Option<Self> {
+		let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+		match ext.as_str() {
+			"png" => Some(Encoding::Png),
+			"jpg" | "jpeg" => Some(Encoding::Jpeg),
+			"webp" => Some(Encoding::Webp),
+			_ => None,
+		}
+	}
+	/// Returns the file extension.
+	pub fn ext(self) -> &'static str {
+		match self {
+			Encoding::Png => "png",
+			Encoding::Jpeg => "jpg",
+			Encoding::Webp => "webp",
+			Encoding::Avif => "avif",
+		}
+	}
+	/// Returns the MIME type.
+	pub fn mime(self) -> &'static str {
+		match self {
+			Encoding::Png => "image/png",
+			Encoding::Jpeg => "image/jpeg",
+			Encoding::Webp => "image/webp",
+			Encoding::Avif => "image/avif",
+		}
+	}
+	fn is_lossy(self) -> bool {
+		matches!(self, Encoding::Jpeg | Encoding::Avif)
+	}
+}
+
+impl From<ImageFormat> for Encoding {
+	fn from(format: ImageFormat) -> Self {
+		match format {
+			ImageFormat::Webp => Encoding::Webp,
+			ImageFormat::Avif => Encoding::Avif,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Represents a resized variant of an image.
+pub struct Variant {
+	/// The encoding.
+	pub encoding: Encoding,
+	/// The width in pixels.
+	pub width: u32,
+	/// The height in pixels.
+	pub height: u32,
+	/// The URL from the site root.
+	pub url: String,
+	/// The output path relative to the site directory.
+	pub site_path: PathBuf,
+	/// The path of the encoded file in the cache directory.
+	pub cache_path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Represents a processed image.
+pub struct ProcessedImage {
+	/// The width of the source image in pixels.
+	pub width: u32,
+	/// The height of the source image in pixels.
+	pub height: u32,
+	/// The encoding of the source image, used for the `<img>` element.
+	pub encoding: Encoding,
+	/// The variants, ordered by the encodings and then by the widths.
+	pub variants: Vec<Variant>,
+}
+
+impl ProcessedImage {
+	/// Returns the variants in the encoding, ordered by the widths.
+	pub fn variants_of(&self, encoding: Encoding) -> impl Iterator<Item = &Variant> {
+		self.variants.iter().filter(move |v| v.encoding == encoding)
+	}
+
+	/// Returns the `srcset` attribute value for the encoding.
+	pub fn srcset(&self, encoding: Encoding) -> String {
+		self
+			.variants_of(encoding)
+			.map(|v| format!("{} {}w", v.url, v.width))
+			.collect::<Vec<_>>()
+			.join(", ")
+	}
+
+	/// Returns the additional encodings in the order of the `<source>` elements.
+	pub fn source_encodings(&self) -> Vec<Encoding> {
+		let mut encodings: Vec<Encoding> = Vec::new();
+		for v in self.variants.iter() {
+			if v.encoding != self.encoding && !encodings.contains(&v.encoding) {
+				encodings.push(v.encoding);
+			}
+		}
+		encodings
+	}
+}
+
+/// Returns the hash of the content, used to name the variants.
+/// FNV-1a is enough to tell the changed images apart and stable across builds.
+pub fn content_hash(bytes: &[u8]) -> String {
+	let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+	for b in bytes {
+		hash ^= u64::from(*b);
+		hash = hash.wrapping_mul(0x0100_0000_01b3);
+	}
+	format!("{hash:016x}")
+}
+
+/// Returns the widths of the variants for the source width.
+/// Widths larger than the source are skipped and the source width is always included, so images are never upscaled.
+pub fn variant_widths(widths: &[u32], source_width: u32) -> Vec<u32> {
+	let mut widths = widths
+		.iter()
+		.copied()
+		.filter(|w| *w > 0 && *w < source_width)
+		.collect::<Vec<_>>();
+	widths.push(source_width);
+	widths.sort_unstable();
+	widths.dedup();
+	widths
+}
+
+/// Returns the height for the width, keeping the aspect ratio of the source.
+pub fn scaled_height(width: u32, height: u32, scaled_width: u32) -> u32 {
+	if width == 0 {
+		return height;
+	}
+	let scaled =
+		(u64::from(height) * u64::from(scaled_width) + u64::from(width) / 2) / u64::from(width);
+	scaled.max(1) as u32
+}
+
+fn file_stem(path: &Path) -> String {
+	// URLに使えるよう、ファイル名はASCIIの英数字と記号の一部だけにする
+	let stem = path
+		.file_stem()
+		.map(|s| s.to_string_lossy())
+		.unwrap_or_default()
+		.chars()
+		.filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+		.collect::<String>();
+	if stem.is_empty() {
+		"image".to_string()
+	} else {
+		stem
+	}
+}
+
+fn encode(img: &DynamicImage, encoding: Encoding, quality: u8) -> Result<Vec<u8>, CodecError> {
+	let mut buf = Vec::new();
+	match encoding {
+		Encoding::Png => {
+			let img = img.to_rgba8();
+			PngEncoder::new(&mut buf).write_image(
+				&img,
+				img.width(),
+				img.height(),
+				image::ExtendedColorType::Rgba8,
+			)?
+		}
+		Encoding::Jpeg => {
+			let img = img.to_rgb8();
+			JpegEncoder::new_with_quality(&mut buf, quality).write_image(
+				&img,
+				img.width(),
+				img.height(),
+				image::ExtendedColorType::Rgb8,
+			)?
+		}
+		Encoding::Webp => {
+			let img = img.to_rgba8();
+			WebPEncoder::new_lossless(&mut buf).write_image(
+				&img,
+				img.width(),
+				img.height(),
+				image::ExtendedColorType::Rgba8,
+			)?
+		}
+		Encoding::Avif => {
+			let img = img.to_rgba8();
+			AvifEncoder::new_with_speed_quality(&mut buf, AVIF_SPEED, quality).write_image(
+				&img,
+				img.width(),
+				img.height(),
+				image::ExtendedColorType::Rgba8,
+			)?
+		}
+	}
+	Ok(buf)
+}
+
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn write_cache(path: &Path, bytes: &[u8]) -> Result<(), ImageError> {
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent).map_err(|e| ImageError::Write(parent.to_path_buf(), e))?;
+	}
+	// 途中で止まっても壊れたファイルがキャッシュに残らないよう、一時ファイルから移す
+	// 同じ内容の画像は同じキャッシュを並列に書くので、一時ファイルの名前は書き込みごとに変える
+	let n = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+	let tmp = path.with_extension(format!("{}-{n}.tmp", std::process::id()));
+	fs::write(&tmp, bytes)
+		.and_then(|_| fs::rename(&tmp, path))
+		.map_err(|e| {
+			let _ = fs::remove_file(&tmp);
+			ImageError::Write(path.to_path_buf(), e)
+		})
+}
+
+type ProcessedCell = Arc<OnceLock<Option<Arc<ProcessedImage>>>>;
+
+/// The images referenced from the ingots.
+/// Images are processed on the first reference and shared by the pages rendered in parallel.
+pub struct Images {
+	conf: ImagesConf,
+	resources_dir: PathBuf,
+	cache_dir: PathBuf,
+	encode: bool,
+	processed: Mutex<BTreeMap<PathBuf, ProcessedCell>>,
+	errors: Mutex<Vec<ImageError>>,
+}
+
+impl Images {
+	/// Creates a new context with the settings, the resources directory to find the images in
+	/// and the cache directory for the encoded variants.
+	/// If `encode` is `false`, only the dimensions are read and no variants are encoded.
+	pub fn new(conf: &ImagesConf, resources_dir: PathBuf, cache_dir: PathBuf, encode: bool) -> Self {
+		Self {
+			conf: conf.clone(),
+			resources_dir,
+			cache_dir,
+			encode,
+			processed: Mutex::new(BTreeMap::new()),
+			errors: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Returns the path of the image in the resources directory for the `src` attribute value.
+	/// External URLs, paths out of the resources directory and unsupported formats are `None`.
+	pub fn resolve(&self, src: &str) -> Option<PathBuf> {
+		let src = decode_basic_entities(src.trim());
+		if src.starts_with("//") || src.starts_with("data:") || src.contains("://") {
+			return None;
+		}
+		let path = percent_decode(src.split(['?', '#']).next().unwrap_or_default());
+		let mut rel = path.trim_start_matches('/');
+		// resourcesディレクトリからのパスでも、ディレクトリ名を含むパスでもよい
+		if let Some(dir_name) = self.resources_dir.file_name().and_then(|n| n.to_str())
+			&& let Some(stripped) = rel.strip_prefix(dir_name).and_then(|r| r.strip_prefix('/'))
+		{
+			rel = stripped;
+		}
+		let rel = Path::new(rel);
+		if rel.as_os_str().is_empty() || rel.components().any(|c| !matches!(c, Component::Normal(_)))
+		{
+			return None;
+		}
+		Encoding::from_path(rel)?;
+		let path = self.resources_dir.join(rel);
+		path.is_file().then_some(path)
+	}
+
+	/// Returns the processed image of the path in the resources directory.
+	/// The image is processed once, and the problems are kept to be taken with [`Images::take_errors`].
+	pub fn get(&self, path: &Path) -> Option<Arc<ProcessedImage>> {
+		let cell = {
+			let mut processed = self.processed.lock().unwrap_or_else(|e| e.into_inner());
+			processed.entry(path.to_path_buf()).or_default().clone()
+		};
+		cell
+			.get_or_init(|| match self.process(path) {
+				Ok(image) => Some(Arc::new(image)),
+				Err(e) => {
+					self
+						.errors
+						.lock()
+						.unwrap_or_else(|e| e.into_inner())
+						.push(e);
+					None
+				}
+			})
+			.clone()
+	}
+
+	fn process(&self, path: &Path) -> Result<ProcessedImage, ImageError> {
+		let bytes = fs::read(path).map_err(|e| ImageError::Read(path.to_path_buf(), e))?;
+		let hash = content_hash(&bytes);
+		let (width, height) = ImageReader::new(Cursor::new(&bytes))
+			.with_guessed_format()
+			.map_err(|e| ImageError::Read(path.to_path_buf(), e))?
+			.into_dimensions()
+			.map_err(|e| ImageError::Decode(path.to_path_buf(), e))?;
+		let source = Encoding::from_path(path).unwrap_or(Encoding::Png);
+		let mut encodings = vec![source];
+		for format in self.conf.formats.iter() {
+			let encoding = Encoding::from(*format);
+			if !encodings.contains(&encoding) {
+				encodings.push(encoding);
+			}
+		}
+		let quality = self.conf.quality.clamp(1, 100);
+		let stem = file_stem(path);
+		let dir = self.conf.dir.trim_matches('/');
+
+		let mut decoded: Option<DynamicImage> = None;
+		let mut variants = Vec::new();
+		for w in variant_widths(&self.conf.widths, width) {
+			let h = scaled_height(width, height, w);
+			let mut resized: Option<DynamicImage> = None;
+			for &encoding in encodings.iter() {
+				let file_name = format!("{stem}-{}-{w}.{}", &hash[..8], encoding.ext());
+				let site_path = Path::new(dir).join(&file_name);
+				// 非可逆の変換は品質ごとにキャッシュする
+				let cache_name = if encoding.is_lossy() {
+					format!("{hash}-{w}-q{quality}.{}", encoding.ext())
+				} else {
+					format!("{hash}-{w}.{}", encoding.ext())
+				};
+				let cache_path = self.cache_dir.join(cache_name);
+				if self.encode && !cache_path.is_file() {
+					if decoded.is_none() {
+						decoded = Some(
+							image::load_from_memory(&bytes)
+								.map_err(|e| ImageError::Decode(path.to_path_buf(), e))?,
+						);
+					}
+					if resized.is_none()
+						&& let Some(img) = &decoded
+					{
+						resized = Some(if w == width {
+							img.clone()
+						} else {
+							img.resize_exact(w, h, FilterType::Lanczos3)
+						});
+					}
+					if let Some(img) = &resized {
+						let bytes = encode(img, encoding, quality)
+							.map_err(|e| ImageError::Encode(path.to_path_buf(), e))?;
+						write_cache(&cache_path, &bytes)?;
+					}
+				}
+				let url = if dir.is_empty() {
+					format!("/{file_name}")
+				} else {
+					format!("/{dir}/{file_name}")
+				};
+				variants.push(Variant {
+					encoding,
+					width: w,
+					height: h,
+					url,
+					site_path,
+					cache_path,
+				});
+			}
+		}
+		// エンコーディングごとにまとめ、幅の順に並べる
+		variants.sort_by_key(|v| (encodings.iter().position(|e| *e == v.encoding), v.width));
+		Ok(ProcessedImage {
+			width,
+			height,
+			encoding: source,
+			variants,
+		})
+	}
+
+	/// Rewrites the `<img>` elements of the images in the resources directory into `<picture>` elements
+	/// with the `srcset` of the variants, and `width` and `height` of the source image.
+	/// The other `<img>` elements, or all of them if the processing is disabled, are kept as they are,
+	/// except that the `src` with the resources directory name points to the source published into the site.
+	pub fn rewrite(&self, html: &str) -> String {
+		if !html.contains("<img") {
+			return html.to_string();
+		}
+		let mut out = String::with_capacity(html.len());
+		let mut rest = html;
+		while let Some(pos) = rest.find("<img") {
+			out.push_str(&rest[..pos]);
+			rest = &rest[pos..];
+			let Some(end) = rest.find('>') else {
+				break;
+			};
+			let tag = &rest[..=end];
+			rest = &rest[end + 1..];
+			let picture = if self.conf.enabled {
+				self.picture(tag)
+			} else {
+				None
+			};
+			match picture.or_else(|| self.published(tag)) {
+				Some(rewritten) => out.push_str(&rewritten),
+				None => out.push_str(tag),
+			}
+		}
+		out.push_str(rest);
+		out
+	}
+
+	fn picture(&self, tag: &str) -> Option<String> {
+		let attrs = parse_img_attrs(tag)?;
+		let attr = |name: &str| {
+			attrs
+				.iter()
+				.find(|(n, _)| n.eq_ignore_ascii_case(name))
+				.map(|(_, v)| v.as_deref().unwrap_or_default())
+		};
+		// 既にsrcsetがある画像は書き手の指定を優先する
+		if attr("srcset").is_some() {
+			return None;
+		}
+		let path = self.resolve(attr("src")?)?;
+		let image = self.get(&path)?;
+		let fallback = image.variants_of(image.encoding).last()?;
+
+		let sizes = match self.conf.sizes.trim() {
+			"" => String::new(),
+			sizes => format!(r#" sizes="{}""#, escape_html(sizes)),
+		};
+		let mut out = String::from("<picture>");
+		for encoding in image.source_encodings() {
+			let _ = write!(
+				out,
+				r#"<source type="{}" srcset="{}"{sizes}>"#,
+				encoding.mime(),
+				escape_html(&image.srcset(encoding))
+			);
+		}
+		let _ = write!(
+			out,
+			r#"<img src="{}" srcset="{}"{sizes}"#,
+			escape_html(&fallback.url),
+			escape_html(&image.srcset(image.encoding))
+		);
+		for (name, value) in attrs.iter() {
+			if name.eq_ignore_ascii_case("src")
+				|| (name.eq_ignore_ascii_case("sizes") && !sizes.is_empty())
+			{
+				continue;
+			}
+			push_attr(&mut out, name, value.as_deref());
+		}
+		if attr("width").is_none() && attr("height").is_none() {
+			let _ = write!(out, r#" width="{}" height="{}""#, image.width, image.height);
+		}
+		out.push_str("></picture>");
+		Some(out)
+	}
+
+	/// Returns the `<img>` tag with the `src` pointing to the source published into the site,
+	/// if the `src` has the resources directory name, such as `/resources/photos/cat.gif`.
+	/// The resources are published from the site root, so it becomes `/photos/cat.gif`.
+	fn published(&self, tag: &str) -> Option<String> {
+		let attrs = parse_img_attrs(tag)?;
+		let src = attrs
+			.iter()
+			.find(|(n, _)| n.eq_ignore_ascii_case("src"))
+			.and_then(|(_, v)| v.as_deref())?;
+		let dir_name = self.resources_dir.file_name()?.to_str()?;
+		let rest = src
+			.trim()
+			.trim_start_matches('/')
+			.strip_prefix(dir_name)?
+			.strip_prefix('/')?;
+		let url = format!("/{rest}");
+		let mut out = String::from("<img");
+		for (name, value) in attrs.iter() {
+			if name.eq_ignore_ascii_case("src") {
+				push_attr(&mut out, name, Some(&url));
+			} else {
+				push_attr(&mut out, name, value.as_deref());
+			}
+		}
+		out.push('>');
+		Some(out)
+	}
+
+	/// Returns the variants of the processed images, to be copied into the site directory.
+	pub fn variants(&self) -> Vec<Variant> {
+		let processed = self.processed.lock().unwrap_or_else(|e| e.into_inner());
+		processed
+			.values()
+			.filter_map(|cell| cell.get().cloned().flatten())
+			.flat_map(|image| image.variants.clone())
+			.collect()
+	}
+
+	/// Takes the problems found while processing the images.
+	pub fn take_errors(&self) -> Vec<ImageError> {
+		std::mem::take(&mut *self.errors.lock().unwrap_or_else(|e| e.into_inner()))
+	}
+}
+
+fn push_attr(out: &mut String, name: &str, value: Option<&str>) {
+	match value {
+		Some(value) => {
+			let _ = write!(out, r#" {name}="{}""#, value.replace('"', "&quot;"));
+		}
+		None => {
+			let _ = write!(out, " {name}");
+		}
+	}
+}
+
+/// Parses the attributes of the `<img>` tag. The values are kept escaped, and `None` for the attributes without values.
+/// Returns `None` if the tag is not an `<img>` tag.
+pub fn parse_img_attrs(tag: &str) -> Option<Vec<(String, Option<String>)>> {
+	let body = tag.strip_prefix("<img")?.strip_suffix('>')?;
+	if !body.is_empty() && !body.starts_with(|c: char| c.is_ascii_whitespace() || c == '/') {
+		return None;
+	}
+	let body = body.trim_end().trim_end_matches('/');
+	let mut attrs = Vec::new();
+	let mut chars = body.char_indices().peekable();
+	loop {
+		while chars.next_if(|(_, c)| c.is_ascii_whitespace()).is_some() {}
+		let Some(&(start, _)) = chars.peek() else {
+			break;
+		};
+		let mut end = start;
+		while let Some((i, c)) = chars.next_if(|(_, c)| !c.is_ascii_whitespace() && *c != '=') {
+			end = i + c.len_utf8();
+		}
+		let name = body[start..end].to_string();
+		if name.is_empty() {
+			// 名前のない `=` は読み飛ばす
+			chars.next();
+			continue;
+		}
+		let mut value = None;
+		if chars.next_if(|(_, c)| *c == '=').is_some() {
+			let value = value.insert(String::new());
+			match chars.peek().map(|(_, c)| *c) {
+				Some(quote @ ('"' | '\'')) => {
+					chars.next();
+					for (_, c) in chars.by_ref() {
+						if c == quote {
+							break;
+						}
+						value.push(c);
+					}
+				}
+				_ => {
+					while let Some((_, c)) = chars.next_if(|(_, c)| !c.is_ascii_whitespace()) {
+						value.push(c);
+					}
+				}
+			}
+		}
+		attrs.push((name, value));
+	}
+	Some(attrs)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use image::{Rgb, RgbImage};
+
+	use crate::app::testing::TempDir;
+
+	fn conf() -> ImagesConf {
+		ImagesConf {
+			enabled: true,
+			widths: vec![40, 80, 200],
+			formats: vec![ImageFormat::Webp],
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn test_helpers() {
+		assert_eq!(variant_widths(&[480, 960, 1440], 1000), [480, 960, 1000]);
+		assert_eq!(variant_widths(&[480, 960], 300), [300]);
+		assert_eq!(variant_widths(&[0, 480, 480], 480), [480]);
+		assert_eq!(scaled_height(1000, 500, 480), 240);
+		assert_eq!(scaled_height(3, 1, 1), 1);
+		assert_eq!(content_hash(b""), "cbf29ce484222325");
+		assert_ne!(content_hash(b"a"), content_hash(b"b"));
+		assert_eq!(percent_decode("%E7%8C%AB%20a.png"), "猫 a.png");
+		assert_eq!(percent_decode("100%"), "100%");
+		assert_eq!(percent_decode("%+1"), "%+1");
+		assert_eq!(file_stem(Path::new("写真 1.png")), "1");
+		assert_eq!(file_stem(Path::new("写真.png")), "image");
+		assert_eq!(
+			parse_img_attrs(r#"<img src="a.png" alt='b c' title=d hidden />"#),
+			Some(vec![
+				("src".to_string(), Some("a.png".to_string())),
+				("alt".to_string(), Some("b c".to_string())),
+				("title".to_string(), Some("d".to_string())),
+				("hidden".to_string(), None),
+			])
+		);
+		assert_eq!(parse_img_attrs("<image src=\"a.png\">"), None);
+	}
+
+	#[test]
+	fn test_write_cache() {
+		let dir = TempDir::new("images_cache");
+		let path = dir.join("a/0123.webp");
+		// 同じ内容の画像は同じパスに並列に書かれる
+		std::thread::scope(|s| {
+			let handles = (0..8)
+				.map(|_| s.spawn(|| write_cache(&path, b"webp")))
+				.collect::<Vec<_>>();
+			for handle in handles {
+				handle.join().unwrap().unwrap();
+			}
+		});
+		assert_eq!(fs::read(&path).unwrap(), b"webp");
+		assert_eq!(fs::read_dir(dir.join("a")).unwrap().count(), 1);
+	}
+
+	#[test]
+	fn test_images() {
+		let dir = TempDir::new("images");
+		let resources = dir.join("resources");
+		let cache = dir.join("cache");
+		fs::create_dir_all(resources.join("photos")).unwrap();
+		RgbImage::from_pixel(100, 50, Rgb([200, 100, 50]))
+			.save(resources.join("photos/cat.png"))
+			.unwrap();
+		fs::write(resources.join("broken.png"), b"not an image").unwrap();
+
+		let images = Images::new(&conf(), resources.clone(), cache.clone(), true);
+		assert_eq!(
+			images.resolve("/resources/photos/cat.png?v=1"),
+			Some(resources.join("photos/cat.png"))
+		);
+		assert_eq!(images.resolve("photos/../photos/cat.png"), None);
+		assert_eq!(images.resolve("https://example.com/cat.png"), None);
+
+		let html = images.rewrite(
+			r#"<p><img src="/photos/cat.png" alt="a &quot;cat&quot;" /> <img src="/missing.png" alt="m" /></p>"#,
+		);
+		let hash = content_hash(&fs::read(resources.join("photos/cat.png")).unwrap());
+		let name = format!("/images/cat-{}", &hash[..8]);
+		assert_eq!(
+			html,
+			format!(
+				concat!(
+					r#"<p><picture><source type="image/webp" srcset="{0}-40.webp 40w, {0}-80.webp 80w, {0}-100.webp 100w">"#,
+					r#"<img src="{0}-100.png" srcset="{0}-40.png 40w, {0}-80.png 80w, {0}-100.png 100w" alt="a &quot;cat&quot;" width="100" height="50"></picture>"#,
+					r#" <img src="/missing.png" alt="m" /></p>"#
+				),
+				name
+			)
+		);
+		// 書き手が指定した大きさはそのまま使う
+		let html = images.rewrite(r#"<img src="photos/cat.png" width="50" loading="lazy">"#);
+		assert!(html.contains(r#" width="50" loading="lazy"></picture>"#));
+		assert!(!html.contains("height="));
+
+		let variants = images.variants();
+		assert_eq!(variants.len(), 6);
+		assert!(variants.iter().all(|v| v.cache_path.is_file()));
+		let small = variants.iter().find(|v| v.width == 40).unwrap();
+		assert_eq!(small.height, 20);
+		assert_eq!(
+			image::image_dimensions(&small.cache_path).unwrap(),
+			(40, 20)
+		);
+		assert!(images.take_errors().is_empty());
+
+		let html = images.rewrite(r#"<img src="broken.png">"#);
+		assert_eq!(html, r#"<img src="broken.png">"#);
+		assert!(matches!(
+			images.take_errors().as_slice(),
+			[ImageError::Decode(..)]
+		));
+
+		// キャッシュにある変換結果は作り直さない
+		let modified = fs::metadata(&small.cache_path).unwrap().modified().unwrap();
+		let images = Images::new(&conf(), resources.clone(), cache.clone(), true);
+		images.rewrite(r#"<img src="photos/cat.png">"#);
+		assert_eq!(
+			fs::metadata(&small.cache_path).unwrap().modified().unwrap(),
+			modified
+		);
+
+		// 無効のときは書き換えない
+		let images = Images::new(&ImagesConf::default(), resources, cache, true);
+		let html = r#"<img src="photos/cat.png">"#;
+		assert_eq!(images.rewrite(html), html);
+		// resourcesディレクトリ名を含むパスは、サイトに置かれた元の画像を指す
+		assert_eq!(
+			images.rewrite(r#"<img src="/resources/photos/cat.png" alt="cat">"#),
+			r#"<img src="/photos/cat.png" alt="cat">"#
+		);
+		assert_eq!(
+			images.rewrite(r#"<img src="resources/anim.gif" />"#),
+			r#"<img src="/anim.gif">"#
+		);
+	}
+}
@@ -0,0 +1,345 @@
+use std::{collections::BTreeMap, fmt::Display, path::PathBuf};
+
+use serde::Serialize;
+
+use crate::app::{
+	category::Category,
+	ingot::{
+		Ingot,
+		ingot::{RKeyRaw, Status, UnresolvedKey},
+	},
+	recipe::MenuEntry,
+	suggest::{did_you_mean, near_miss_names},
+	tag::Tag,
+};
+use crate::msg;
+
+use super::render::{find_ingot, url_of, url_path_name};
+
+#[derive(Debug, Clone, Serialize)]
+/// Menu item resolved to its final URL and title. Passed to templates.
+pub struct MenuItem {
+	/// The title of the item.
+	pub title: String,
+	/// The URL of the item.
+	pub url: String,
+	/// The weight of the item.
+	pub weight: i64,
+	/// `true` if the item links to an external URL.
+	pub external: bool,
+	/// `true` if the item links to the page being rendered.
+	pub active: bool,
+	/// `true` if one of the descendants links to the page being rendered.
+	pub has_active_child: bool,
+	/// The nested items.
+	pub children: Vec<MenuItem>,
+}
+
+/// Resolved menus, keyed by the menu name.
+pub type Menus = BTreeMap<String, Vec<MenuItem>>;
+
+#[derive(Debug)]
+/// Represents an error in a menu entry. The location is the menu name and the entry indexes, e.g. `main/1/0`.
+pub enum MenuError {
+	/// The entry has no target.
+	NoTarget(String),
+	/// The entry has more than one target.
+	MultipleTargets(String),
+	/// The ingot of the entry was not found. Near-miss pnames are suggested.
+	IngotNotFound(String, RKeyRaw, Vec<String>),
+	/// The ingot of the entry is not published.
+	IngotNotPublished(String, PathBuf),
+	/// The category or tag of the entry was not found.
+	Unresolved(String, UnresolvedKey),
+	/// The entry links the category or tag of the given name, but no category or tag pages are built.
+	NoTermPage(String, String),
+}
+
+impl Display for MenuError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			MenuError::NoTarget(loc) => write!(f, "{}", msg!(MenuNoTarget, loc)),
+			MenuError::MultipleTargets(loc) => write!(f, "{}", msg!(MenuMultipleTargets, loc)),
+			MenuError::IngotNotFound(loc, key, suggestions) => write!(
+				f,
+				"{}",
+				msg!(MenuIngotNotFound, loc, key, did_you_mean(suggestions))
+			),
+			MenuError::IngotNotPublished(loc, path) => {
+				write!(f, "{}", msg!(MenuIngotNotPublished, loc, path.display()))
+			}
+			MenuError::Unresolved(loc, key) => write!(f, "{}", msg!(MenuUnresolved, loc, key)),
+			MenuError::NoTermPage(loc, name) => write!(f, "{}", msg!(MenuNoTermPage, loc, name)),
+		}
+	}
+}
+
+struct MenuResolver<'a> {
+	ingots: &'a BTreeMap<usize, (PathBuf, Ingot)>,
+	categories_index_map: &'a BTreeMap<usize, &'a Category>,
+	tags_index_map: &'a BTreeMap<usize, &'a Tag>,
+	lang: &'a str,
+}
+
+impl MenuResolver<'_> {
+	fn find_ingot(&self, key: &RKeyRaw) -> Option<&(PathBuf, Ingot)> {
+		find_ingot(self.ingots, key, self.lang)
+	}
+
+	fn find_category(&self, key: &RKeyRaw) -> Option<&Category> {
+		match key {
+			RKeyRaw::Usize(id) => self.categories_index_map.get(id).copied(),
+			RKeyRaw::String(name) => self
+				.categories_index_map
+				.values()
+				.find(|c| &c.name == name || &c.path_name == name)
+				.copied(),
+		}
+	}
+
+	fn find_tag(&self, key: &RKeyRaw) -> Option<&Tag> {
+		match key {
+			RKeyRaw::Usize(id) => self.tags_index_map.get(id).copied(),
+			RKeyRaw::String(name) => self
+				.tags_index_map
+				.values()
+				.find(|t| &t.name == name || &t.path_name == name)
+				.copied(),
+		}
+	}
+
+	/// Resolves the target of the entry into a title and an URL.
+	fn resolve_target(
+		&self,
+		entry: &MenuEntry,
+		loc: &str,
+	) -> Result<(String, String, bool), MenuError> {
+		let targets = [
+			entry.ingot.is_some(),
+			entry.category.is_some(),
+			entry.tag.is_some(),
+			entry.url.is_some(),
+		];
+		match targets.iter().filter(|t| **t).count() {
+			0 => return Err(MenuError::NoTarget(loc.to_string())),
+			1 => {}
+			_ => return Err(MenuError::MultipleTargets(loc.to_string())),
+		}
+
+		if let Some(key) = &entry.ingot {
+			let (path, ingot) = self.find_ingot(key).ok_or_else(|| {
+				let pnames = self
+					.ingots
+					.values()
+					.map(|(_, i)| url_path_name(i))
+					.collect::<Vec<_>>();
+				let suggestions = match key {
+					RKeyRaw::String(name) => near_miss_names(name, pnames.iter().map(|s| s.as_str())),
+					RKeyRaw::Usize(_) => Vec::new(),
+				};
+				MenuError::IngotNotFound(loc.to_string(), key.clone(), suggestions)
+			})?;
+			if !matches!(ingot.status, Status::Publish) {
+				return Err(MenuError::IngotNotPublished(loc.to_string(), path.clone()));
+			}
+			Ok((ingot.title.clone(), url_of(ingot), false))
+		} else if let Some(key) = &entry.category {
+			let category = self.find_category(key).ok_or_else(|| {
+				MenuError::Unresolved(loc.to_string(), UnresolvedKey::Category(key.clone()))
+			})?;
+			// カテゴリーのページは作らないので、リンク切れにせずエラーにする
+			Err(MenuError::NoTermPage(
+				loc.to_string(),
+				category.name.clone(),
+			))
+		} else if let Some(key) = &entry.tag {
+			let tag = self.find_tag(key).ok_or_else(|| {
+				MenuError::Unresolved(loc.to_string(), UnresolvedKey::Tag(key.clone()))
+			})?;
+			Err(MenuError::NoTermPage(loc.to_string(), tag.name.clone()))
+		} else {
+			let url = entry.url.clone().unwrap_or_default();
+			let external = is_external_url(&url);
+			Ok((url.clone(), url, external))
+		}
+	}
+
+	fn resolve_entries(
+		&self,
+		entries: &[MenuEntry],
+		loc: &str,
+		errors: &mut Vec<MenuError>,
+	) -> Vec<MenuItem> {
+		let mut items = entries
+			.iter()
+			.enumerate()
+			.filter_map(|(index, entry)| {
+				let loc = format!("{loc}/{index}");
+				let children = self.resolve_entries(&entry.children, &loc, errors);
+				match self.resolve_target(entry, &loc) {
+					Ok((title, url, external)) => Some(MenuItem {
+						title: entry.title.clone().unwrap_or(title),
+						url,
+						weight: entry.weight,
+						external,
+						active: false,
+						has_active_child: false,
+						children,
+					}),
+					Err(e) => {
+						errors.push(e);
+						None
+					}
+				}
+			})
+			.collect::<Vec<_>>();
+		// 重みが同じ場合は書かれた順を保つ
+		items.sort_by_key(|item| item.weight);
+		items
+	}
+}
+
+/// Returns `true` if the URL points outside of the site.
+pub fn is_external_url(url: &str) -> bool {
+	url.contains("://") || url.starts_with("//") || url.starts_with("mailto:")
+}
+
+/// Resolves the menus of the recipe against the ingots, categories and tags for the pages in the given language.
+/// The ingots in the language are preferred to the ones of the same path name in other languages.
+/// Returns the resolved menus and the errors of the entries which could not be resolved.
+pub fn resolve_menus(
+	menus: &BTreeMap<String, Vec<MenuEntry>>,
+	ingots: &BTreeMap<usize, (PathBuf, Ingot)>,
+	categories_index_map: &BTreeMap<usize, &Category>,
+	tags_index_map: &BTreeMap<usize, &Tag>,
+	lang: &str,
+) -> (Menus, Vec<MenuError>) {
+	let resolver = MenuResolver {
+		ingots,
+		categories_index_map,
+		tags_index_map,
+		lang,
+	};
+	let mut errors = Vec::new();
+	let resolved = menus
+		.iter()
+		.map(|(name, entries)| {
+			(
+				name.clone(),
+				resolver.resolve_entries(entries, name, &mut errors),
+			)
+		})
+		.collect();
+	(resolved, errors)
+}
+
+fn mark_active(items: &mut [MenuItem], current_url: &str) -> bool {
+	let mut found = false;
+	for item in items.iter_mut() {
+		item.has_active_child = mark_active(&mut item.children, current_url);
+		item.active = !item.external && item.url == current_url;
+		found |= item.active || item.has_active_child;
+	}
+	found
+}
+
+/// Returns the menus with the active flags set for the page of the given URL.
+pub fn menus_for_page(menus: &Menus, current_url: &str) -> Menus {
+	let mut menus = menus.clone();
+	for items in menus.values_mut() {
+		mark_active(items, current_url);
+	}
+	menus
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn ingot(id: usize, pname: &str, title: &str) -> (usize, (PathBuf, Ingot)) {
+		let ingot = Ingot {
+			id,
+			pname: pname.to_string(),
+			title: title.to_string(),
+			status: Status::Publish,
+			..Default::default()
+		};
+		(id, (PathBuf::from(format!("{pname}.ingot")), ingot))
+	}
+
+	fn entry(ingot: Option<RKeyRaw>, url: Option<&str>, weight: i64) -> MenuEntry {
+		MenuEntry {
+			ingot,
+			url: url.map(|u| u.to_string()),
+			weight,
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn test_resolve_menus() {
+		let ingots = BTreeMap::from([ingot(1, "about", "About"), ingot(2, "blog", "Blog")]);
+		let mut blog = entry(Some(RKeyRaw::String("blog".into())), None, 1);
+		blog.children = vec![entry(Some(RKeyRaw::Usize(1)), None, 0)];
+		let menus = BTreeMap::from([(
+			"main".to_string(),
+			vec![
+				blog,
+				entry(None, Some("https://example.com"), 0),
+				entry(Some(RKeyRaw::String("abuot".into())), None, 0),
+			],
+		)]);
+		let mut menus = menus;
+		menus.insert(
+			"footer".to_string(),
+			vec![MenuEntry {
+				tag: Some(RKeyRaw::String("rust".into())),
+				..Default::default()
+			}],
+		);
+		let tag = Tag::new(1, "rust".into(), "Rust".into(), String::new());
+		let tags = BTreeMap::from([(1, &tag)]);
+		let (resolved, errors) = resolve_menus(&menus, &ingots, &BTreeMap::new(), &tags, "");
+		assert_eq!(errors.len(), 2);
+		// タグのページは作らないのでリンクしない
+		assert!(matches!(
+			&errors[0],
+			MenuError::NoTermPage(loc, name) if loc == "footer/0" && name == "Rust"
+		));
+		assert!(matches!(
+			&errors[1],
+			MenuError::IngotNotFound(loc, _, suggestions) if loc == "main/2" && suggestions == &["about"]
+		));
+
+		let main = &resolved["main"];
+		assert_eq!(main[0].url, "https://example.com");
+		assert!(main[0].external);
+		assert_eq!(main[1].title, "Blog");
+		assert_eq!(main[1].children[0].url, "/about/");
+
+		let page = menus_for_page(&resolved, "/about/");
+		assert!(page["main"][1].has_active_child);
+		assert!(!page["main"][1].active);
+		assert!(page["main"][1].children[0].active);
+
+		// 同じpnameの生地はページと同じ言語のものを選ぶ
+		let mut ingots = ingots;
+		for (id, lang) in [(3, "ja"), (4, "en")] {
+			let (_, (path, mut ingot)) = ingot(id, "about", &format!("About ({lang})"));
+			ingot.lang = lang.to_string();
+			ingot.url_prefix = lang.to_string();
+			ingots.insert(id, (path, ingot));
+		}
+		let menus = BTreeMap::from([(
+			"main".to_string(),
+			vec![entry(Some(RKeyRaw::String("about".into())), None, 0)],
+		)]);
+		for lang in ["ja", "en"] {
+			let (resolved, errors) =
+				resolve_menus(&menus, &ingots, &BTreeMap::new(), &BTreeMap::new(), lang);
+			assert!(errors.is_empty());
+			assert_eq!(resolved["main"][0].title, format!("About ({lang})"));
+			assert_eq!(resolved["main"][0].url, format!("/{lang}/about/"));
+		}
+	}
+}
@@ -0,0 +1,272 @@
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+};
+
+use minijinja::{Value, context};
+use serde::{Deserialize, Serialize};
+
+use super::{
+	BuildError,
+	images::Images,
+	menu::{Menus, menus_for_page},
+	shortcode::Shortcodes,
+};
+use crate::app::{
+	category::Category,
+	fs::path::is_contained_path,
+	i18n::translations_of,
+	igata::{
+		igata::Igata,
+		messages::{Messages, messages_for},
+	},
+	ingot::{
+		Ingot,
+		ingot::{RKeyList, RKeyRaw, To},
+	},
+	markdown::markdown_to_html,
+	recipe::Recipe,
+	tag::Tag,
+};
+
+/// The igata name used when the igata table has no entry for the ingot build type.
+pub const DEFAULT_IGATA: &str = "default";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Represents a rendered page.
+pub struct RenderedPage {
+	/// The output path relative to the site directory.
+	pub path: PathBuf,
+	/// The rendered contents.
+	pub body: String,
+}
+
+/// Returns the path name of the ingot used in its URL. Falls back to the ingot ID.
+pub fn url_path_name(ingot: &Ingot) -> String {
+	let pname = ingot.pname.trim().trim_matches('/');
+	if pname.is_empty() {
+		ingot.id.to_string()
+	} else {
+		pname.to_string()
+	}
+}
+
+/// Finds the ingot by its ID or its path name.
+/// If the ingots in several languages have the path name, the one in the given language is preferred.
+pub fn find_ingot<'a>(
+	ingots: &'a BTreeMap<usize, (PathBuf, Ingot)>,
+	key: &RKeyRaw,
+	lang: &str,
+) -> Option<&'a (PathBuf, Ingot)> {
+	match key {
+		RKeyRaw::Usize(id) => ingots.get(id),
+		RKeyRaw::String(name) => {
+			let name = name.trim_matches('/');
+			let candidates = ingots
+				.values()
+				.filter(|(_, ingot)| url_path_name(ingot) == name)
+				.collect::<Vec<_>>();
+			candidates
+				.iter()
+				.find(|(_, ingot)| ingot.lang == lang)
+				.or(candidates.first())
+				.copied()
+		}
+	}
+}
+
+/// Returns the permalink prefix of the ingot without slashes. Empty if the ingot has no prefix.
+pub fn url_prefix(ingot: &Ingot) -> &str {
+	ingot.url_prefix.trim().trim_matches('/')
+}
+
+/// Returns the URL of the ingot from the site root. The permalink prefix of its language is prepended.
+pub fn url_of(ingot: &Ingot) -> String {
+	let base = match url_prefix(ingot) {
+		"" => "/".to_string(),
+		prefix => format!("/{prefix}/"),
+	};
+	match ingot.to {
+		To::Top => base,
+		To::AsIs => format!("{base}{}", url_path_name(ingot)),
+		_ => format!("{base}{}/", url_path_name(ingot)),
+	}
+}
+
+/// Returns the path name of the tag or category used in the URLs and the output paths.
+pub fn term_path_name(path_name: &str) -> &str {
+	path_name.trim().trim_matches('/')
+}
+
+/// Returns the output path of the ingot relative to the site directory.
+pub fn output_rel_path(ingot: &Ingot) -> PathBuf {
+	let base = Path::new(url_prefix(ingot));
+	match ingot.to {
+		To::Top => base.join("index.html"),
+		To::AsIs => base.join(url_path_name(ingot)),
+		_ => base.join(url_path_name(ingot)).join("index.html"),
+	}
+}
+
+/// Returns the igata name for the given build type from the igata table.
+pub fn igata_name_of<'a>(igata_table: &'a BTreeMap<String, String>, to: &To) -> &'a str {
+	igata_table
+		.get(to.name())
+		.map(|s| s.as_str())
+		.unwrap_or(DEFAULT_IGATA)
+}
+
+/// Returns the collated IDs of the list. Raw keys are not collated yet, so an empty slice is returned.
+pub fn collated_ids(list: &RKeyList) -> &[usize] {
+	match list {
+		RKeyList::CollatedId(ids) => ids,
+		RKeyList::Raw(_) => &[],
+	}
+}
+
+/// Returns the template value of the tag or category, with the name and description in the language of the page.
+pub fn localized_term_value<T: Serialize>(term: &T, name: &str, description: &str) -> Value {
+	let value = Value::from_serialize(term);
+	let mut map = BTreeMap::new();
+	if let Ok(keys) = value.try_iter() {
+		for key in keys {
+			if let Ok(item) = value.get_item(&key) {
+				map.insert(key.to_string(), item);
+			}
+		}
+	}
+	map.insert("name".to_string(), Value::from(name));
+	map.insert("description".to_string(), Value::from(description));
+	Value::from_serialize(&map)
+}
+
+/// Returns the template value of the ingot.
+/// The names of the tags and categories are in the language of the ingot.
+pub fn ingot_value(
+	ingot: &Ingot,
+	content_html: String,
+	categories_index_map: &BTreeMap<usize, &Category>,
+	tags_index_map: &BTreeMap<usize, &Tag>,
+) -> Value {
+	let tags = collated_ids(&ingot.tags)
+		.iter()
+		.filter_map(|id| tags_index_map.get(id))
+		.map(|tag| {
+			localized_term_value(
+				tag,
+				tag.name_in(&ingot.lang),
+				tag.description_in(&ingot.lang),
+			)
+		})
+		.collect::<Vec<_>>();
+	let categories = collated_ids(&ingot.categories)
+		.iter()
+		.filter_map(|id| categories_index_map.get(id))
+		.map(|category| {
+			localized_term_value(
+				category,
+				category.name_in(&ingot.lang),
+				category.description_in(&ingot.lang),
+			)
+		})
+		.collect::<Vec<_>>();
+	context! {
+		id => ingot.id,
+		author => ingot.author,
+		pname => ingot.pname,
+		title => ingot.title,
+		excerpt => ingot.excerpt,
+		content => Value::from_safe_string(content_html),
+		status => ingot.status.to_string().to_ascii_lowercase(),
+		comment_status => ingot.comment_status.to_string().to_ascii_lowercase(),
+		published => ingot.published.to_string(),
+		updated => ingot.updated.to_string(),
+		to => ingot.to.name(),
+		lang => ingot.lang,
+		translation_key => ingot.translation_key,
+		url => url_of(ingot),
+		tags => tags,
+		categories => categories,
+	}
+}
+
+/// The shared inputs to render ingots.
+pub struct RenderContext<'a> {
+	/// The igata to render with.
+	pub igata: &'a Igata,
+	/// The recipe.
+	pub recipe: &'a Recipe,
+	/// The template values.
+	pub values: &'a Value,
+	/// The resolved menus, keyed by the language code.
+	pub menus: &'a BTreeMap<String, Menus>,
+	/// The context to expand shortcodes in.
+	pub shortcodes: Shortcodes<'a>,
+	/// The categories by their IDs.
+	pub categories_index_map: &'a BTreeMap<usize, &'a Category>,
+	/// The tags by their IDs.
+	pub tags_index_map: &'a BTreeMap<usize, &'a Tag>,
+	/// The published ingots grouped by their translation keys.
+	pub translations: BTreeMap<&'a str, Vec<&'a Ingot>>,
+	/// The messages of the packs, keyed by the language code.
+	pub messages: &'a BTreeMap<String, Messages>,
+	/// The default language of the site.
+	pub default_lang: &'a str,
+	/// The images referenced from the ingots.
+	pub images: &'a Images,
+}
+
+#[derive(Debug)]
+/// Represents an ingot rendered into a page.
+pub struct RenderedIngot {
+	/// The ingot ID.
+	pub id: usize,
+	/// The rendered page.
+	pub page: RenderedPage,
+	/// The content converted into HTML with the shortcodes expanded. The content of `as-is` ingots is kept as is.
+	pub content_html: String,
+}
+
+/// Renders the ingot into a page.
+/// The shortcodes in the Markdown content are expanded, then the content is converted into HTML,
+/// the images in the resources directory are replaced with their variants,
+/// and the page is rendered with the igata for its build type.
+pub fn render_ingot(rc: &RenderContext, ingot: &Ingot) -> Result<RenderedIngot, BuildError> {
+	let path = output_rel_path(ingot);
+	// pnameなどに `..` があってもサイトのディレクトリの外には書き出さない
+	if !is_contained_path(&path) {
+		return Err(BuildError::InvalidOutputPath(ingot.path.clone(), path));
+	}
+	if let To::AsIs = ingot.to {
+		return Ok(RenderedIngot {
+			id: ingot.id,
+			page: RenderedPage {
+				path,
+				body: ingot.content.clone(),
+			},
+			content_html: ingot.content.clone(),
+		});
+	}
+	// ショートコードはMarkdownの変換前に展開する
+	let content = rc
+		.shortcodes
+		.expand(&ingot.content, ingot)
+		.map_err(|e| BuildError::Shortcode(ingot.path.clone(), e))?;
+	let content_html = rc.images.rewrite(&markdown_to_html(&content));
+	let ctx = context! {
+		values => rc.values,
+		menus => Value::from_serialize(rc.menus.get(&ingot.lang).map(|menus| menus_for_page(menus, &url_of(ingot)))),
+		ingot => ingot_value(ingot, content_html.clone(), rc.categories_index_map, rc.tags_index_map),
+		translations => Value::from_serialize(translations_of(&rc.translations, ingot)),
+		messages => Value::from_serialize(messages_for(rc.messages, &ingot.lang, rc.default_lang)),
+	};
+	let body = rc
+		.igata
+		.render(igata_name_of(&rc.recipe.igata_table, &ingot.to), ctx)
+		.map_err(|e| BuildError::Render(ingot.path.clone(), e))?;
+	Ok(RenderedIngot {
+		id: ingot.id,
+		page: RenderedPage { path, body },
+		content_html,
+	})
+}
@@ -0,0 +1,283 @@
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+};
+
+use serde::Serialize;
+
+use super::{
+	BuildError,
+	render::{RenderedPage, collated_ids, url_of},
+};
+use crate::app::{
+	category::Category,
+	ingot::{
+		Ingot,
+		ingot::{Status, To},
+	},
+	markdown::html_to_text,
+	recipe::SearchConf,
+	tag::Tag,
+};
+
+/// The output path of the search index relative to the site directory.
+pub const SEARCH_INDEX_PATH: &str = "search.json";
+
+/// The version of the search index format. Bumped when the format changes.
+pub const SEARCH_INDEX_VERSION: u32 = 1;
+
+/// Returns `true` if the character is written without spaces between words, such as Japanese.
+pub fn is_cjk(c: char) -> bool {
+	matches!(c,
+		'\u{3005}' | '\u{3006}' // 々〆
+		| '\u{3040}'..='\u{309F}' // ひらがな
+		| '\u{30A0}'..='\u{30FF}' // カタカナ
+		| '\u{3400}'..='\u{4DBF}'
+		| '\u{4E00}'..='\u{9FFF}'
+		| '\u{F900}'..='\u{FAFF}'
+		| '\u{AC00}'..='\u{D7AF}'
+		| '\u{FF66}'..='\u{FF9F}' // 半角カタカナ
+	)
+}
+
+/// Normalizes the character for search. Full-width ASCII is converted into half-width, and letters are lowercased.
+pub fn normalize_char(c: char) -> impl Iterator<Item = char> {
+	let c = match c {
+		'\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+		'\u{3000}' => ' ',
+		_ => c,
+	};
+	c.to_lowercase()
+}
+
+fn push_ngrams(run: &[char], n: usize, tokens: &mut Vec<String>) {
+	if run.is_empty() {
+		return;
+	}
+	if run.len() <= n {
+		tokens.push(run.iter().collect());
+		return;
+	}
+	for window in run.windows(n) {
+		tokens.push(window.iter().collect());
+	}
+}
+
+/// Splits the text into search tokens.
+///
+/// Words of letters and digits are split by the other characters and kept if they have two or more characters.
+/// Runs of CJK characters, which have no spaces between words, are split into character n-grams.
+/// The default search partial tokenizes queries in the same way.
+pub fn tokenize(text: &str, n: usize) -> Vec<String> {
+	let n = n.max(1);
+	let mut tokens = Vec::new();
+	let mut word = String::new();
+	let mut run: Vec<char> = Vec::new();
+	for c in text.chars().flat_map(normalize_char) {
+		if is_cjk(c) {
+			if word.chars().nth(1).is_some() {
+				tokens.push(std::mem::take(&mut word));
+			}
+			word.clear();
+			run.push(c);
+		} else {
+			push_ngrams(&run, n, &mut tokens);
+			run.clear();
+			if c.is_alphanumeric() {
+				word.push(c);
+			} else {
+				if word.chars().nth(1).is_some() {
+					tokens.push(std::mem::take(&mut word));
+				}
+				word.clear();
+			}
+		}
+	}
+	push_ngrams(&run, n, &mut tokens);
+	if word.chars().nth(1).is_some() {
+		tokens.push(word);
+	}
+	tokens
+}
+
+#[derive(Debug, Serialize)]
+/// A document of the search index. The keys are shortened to keep the index small.
+pub struct SearchDoc<'a> {
+	#[serde(rename = "t")]
+	/// The title.
+	pub title: &'a str,
+	#[serde(rename = "u")]
+	/// The URL.
+	pub url: String,
+	#[serde(rename = "l", skip_serializing_if = "str::is_empty")]
+	/// The language code.
+	pub lang: &'a str,
+	#[serde(rename = "e")]
+	/// The excerpt.
+	pub excerpt: &'a str,
+	#[serde(rename = "g", skip_serializing_if = "Vec::is_empty")]
+	/// The tag names.
+	pub tags: Vec<&'a str>,
+	#[serde(rename = "c", skip_serializing_if = "Vec::is_empty")]
+	/// The category names.
+	pub categories: Vec<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+/// The search index. `idx` maps each token to the positions of the documents in `docs` which contain it.
+pub struct SearchIndex<'a> {
+	/// The version of the format.
+	pub v: u32,
+	/// The length of the character n-grams.
+	pub n: usize,
+	/// The documents.
+	pub docs: Vec<SearchDoc<'a>>,
+	/// The inverted index from the tokens to the document positions.
+	pub idx: BTreeMap<String, Vec<usize>>,
+}
+
+/// Builds the search index of the published ingots.
+/// The body text is taken from the rendered content, and `as-is` ingots are not indexed.
+pub fn build_search_index<'a>(
+	conf: &SearchConf,
+	ingots: &'a BTreeMap<usize, (PathBuf, Ingot)>,
+	contents: &BTreeMap<usize, String>,
+	categories_index_map: &BTreeMap<usize, &'a Category>,
+	tags_index_map: &BTreeMap<usize, &'a Tag>,
+) -> SearchIndex<'a> {
+	let mut docs = Vec::new();
+	let mut idx: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+	for (_, ingot) in ingots.values() {
+		if !matches!(ingot.status, Status::Publish) || matches!(ingot.to, To::AsIs) {
+			continue;
+		}
+		let Some(content) = contents.get(&ingot.id) else {
+			continue;
+		};
+		let doc = SearchDoc {
+			title: &ingot.title,
+			url: url_of(ingot),
+			lang: &ingot.lang,
+			excerpt: &ingot.excerpt,
+			tags: collated_ids(&ingot.tags)
+				.iter()
+				.filter_map(|id| tags_index_map.get(id))
+				.map(|tag| tag.name_in(&ingot.lang))
+				.collect(),
+			categories: collated_ids(&ingot.categories)
+				.iter()
+				.filter_map(|id| categories_index_map.get(id))
+				.map(|category| category.name_in(&ingot.lang))
+				.collect(),
+		};
+		let text = [
+			doc.title,
+			doc.excerpt,
+			&doc.tags.join(" "),
+			&doc.categories.join(" "),
+			&html_to_text(content),
+		]
+		.join("\n");
+		let pos = docs.len();
+		let tokens = tokenize(&text, conf.ngram)
+			.into_iter()
+			.collect::<BTreeSet<_>>();
+		for token in tokens {
+			idx.entry(token).or_default().push(pos);
+		}
+		docs.push(doc);
+	}
+	SearchIndex {
+		v: SEARCH_INDEX_VERSION,
+		n: conf.ngram,
+		docs,
+		idx,
+	}
+}
+
+/// Returns the search index page, written as compact JSON.
+pub fn render_search_index(index: &SearchIndex) -> Result<RenderedPage, BuildError> {
+	let path = PathBuf::from(SEARCH_INDEX_PATH);
+	let body = serde_json::to_string(index).map_err(|e| BuildError::Api(path.clone(), e))?;
+	Ok(RenderedPage { path, body })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use minijinja::context;
+
+	use crate::app::{igata::igata::Igata, ingot::ingot::RKeyList};
+
+	#[test]
+	fn test_tokenize() {
+		assert_eq!(
+			tokenize("吾輩は猫である。Ｒｕｓｔで書く a b2 x", 2),
+			[
+				"吾輩", "輩は", "は猫", "猫で", "であ", "ある", "rust", "で書", "書く", "b2"
+			]
+		);
+		assert_eq!(tokenize("猫", 2), ["猫"]);
+		assert_eq!(tokenize("日本語", 3), ["日本語"]);
+	}
+
+	#[test]
+	fn test_build_search_index() {
+		let tags = [Tag::new(1, "rust".into(), "Rust".into(), String::new())];
+		let tags_index_map = tags.iter().map(|t| (t.id, t)).collect();
+		let mut ingots = BTreeMap::new();
+		for (id, status) in [
+			(1, Status::Publish),
+			(2, Status::Draft),
+			(3, Status::Publish),
+		] {
+			let mut ingot = Ingot::new(id);
+			ingot.status = status;
+			ingot.pname = format!("p{id}");
+			ingot.title = format!("記事{id}");
+			ingot.tags = RKeyList::CollatedId(if id == 1 { vec![1] } else { vec![] });
+			ingots.insert(id, (PathBuf::new(), ingot));
+		}
+		let contents = [
+			(1, "<p>静的サイト</p>".to_string()),
+			(2, "<p>下書き</p>".to_string()),
+			(3, "<p>静的<b>型付け</b></p>".to_string()),
+		]
+		.into_iter()
+		.collect();
+		let index = build_search_index(
+			&SearchConf::default(),
+			&ingots,
+			&contents,
+			&BTreeMap::new(),
+			&tags_index_map,
+		);
+		assert_eq!(index.docs.len(), 2);
+		assert_eq!(index.docs[0].url, "/p1/");
+		assert_eq!(index.docs[0].tags, ["Rust"]);
+		assert_eq!(index.idx["静的"], [0, 1]);
+		assert_eq!(index.idx["rust"], [0]);
+		assert_eq!(index.idx["型付"], [1]);
+		assert!(!index.idx.contains_key("下書"));
+		let page = render_search_index(&index).unwrap();
+		assert!(
+			page
+				.body
+				.starts_with("{\"v\":1,\"n\":2,\"docs\":[{\"t\":\"記事1\"")
+		);
+	}
+
+	#[test]
+	fn test_default_search_partial() {
+		let igata = Igata::new(vec![]);
+		assert!(igata.exists("partials/search"));
+		let body = igata
+			.render(
+				"partials/search",
+				context! { values => context! { search_placeholder => "検索" } },
+			)
+			.unwrap();
+		assert!(body.contains("search.json\""));
+		assert!(body.contains("placeholder=\"検索\""));
+	}
+}
@@ -0,0 +1,752 @@
+use std::{
+	collections::BTreeMap,
+	fmt::{Display, Write},
+	path::PathBuf,
+};
+
+use minijinja::{Value, context};
+
+use crate::app::{
+	igata::igata::{IGATA_EXT, Igata},
+	ingot::{
+		Ingot,
+		ingot::{RKeyRaw, Status},
+	},
+	markdown::{escape_html, markdown_to_html},
+	suggest::{did_you_mean, near_miss_names},
+};
+use crate::msg;
+
+use super::render::{find_ingot, url_of, url_path_name};
+
+/// The directory of the shortcode templates in igata packs.
+pub const SHORTCODE_DIR: &str = "shortcodes";
+
+/// The names of the built-in shortcodes.
+pub const BUILTIN_SHORTCODES: [&str; 4] = ["callout", "figure", "ref", "youtube"];
+
+const OPEN: &str = "{{<";
+const CLOSE: &str = ">}}";
+
+#[derive(Debug)]
+/// Represents an error while expanding shortcodes. The first field is the line number in the content.
+pub enum ShortcodeError {
+	/// The shortcode is not closed with `>}}`.
+	Unterminated(usize),
+	/// The shortcode could not be parsed.
+	InvalidSyntax(usize, String),
+	/// A closing shortcode has no matching opening shortcode.
+	UnexpectedClose(usize, String),
+	/// The shortcode is neither a template in the packs nor a built-in. Near-miss names are suggested.
+	Unknown(usize, String, Vec<String>),
+	/// A required argument is missing.
+	MissingArg(usize, String, &'static str),
+	/// An argument has an invalid value.
+	InvalidArg(usize, String, &'static str, String),
+	/// The ingot referenced by `ref` was not found. Near-miss pnames are suggested.
+	RefNotFound(usize, RKeyRaw, Vec<String>),
+	/// The ingot referenced by `ref` is not published.
+	RefNotPublished(usize, RKeyRaw),
+	/// Failed to render the shortcode template.
+	Render(usize, String, minijinja::Error),
+}
+
+impl Display for ShortcodeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ShortcodeError::Unterminated(line) => {
+				write!(f, "{}", msg!(ShortcodeUnterminated, line, CLOSE))
+			}
+			ShortcodeError::InvalidSyntax(line, detail) => {
+				write!(f, "{}", msg!(ShortcodeInvalidSyntax, line, detail))
+			}
+			ShortcodeError::UnexpectedClose(line, name) => {
+				write!(f, "{}", msg!(ShortcodeUnexpectedClose, line, name))
+			}
+			ShortcodeError::Unknown(line, name, suggestions) => write!(
+				f,
+				"{}",
+				msg!(ShortcodeNotFound, line, name, did_you_mean(suggestions))
+			),
+			ShortcodeError::MissingArg(line, name, arg) => {
+				write!(f, "{}", msg!(ShortcodeMissingArg, line, name, arg))
+			}
+			ShortcodeError::InvalidArg(line, name, arg, value) => {
+				write!(f, "{}", msg!(ShortcodeInvalidArg, line, name, arg, value))
+			}
+			ShortcodeError::RefNotFound(line, key, suggestions) => write!(
+				f,
+				"{}",
+				msg!(ShortcodeRefNotFound, line, key, did_you_mean(suggestions))
+			),
+			ShortcodeError::RefNotPublished(line, key) => {
+				write!(f, "{}", msg!(ShortcodeRefNotPublished, line, key))
+			}
+			ShortcodeError::Render(line, name, e) => {
+				write!(f, "{}", msg!(ShortcodeRender, line, name, format!("{e:#}")))
+			}
+		}
+	}
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// Represents the arguments of a shortcode.
+pub struct ShortcodeArgs {
+	/// The positional arguments.
+	pub positional: Vec<String>,
+	/// The named arguments.
+	pub named: BTreeMap<String, String>,
+}
+
+impl ShortcodeArgs {
+	/// Returns the named argument, or the positional argument at the index.
+	pub fn get(&self, name: &str, index: Option<usize>) -> Option<&str> {
+		self
+			.named
+			.get(name)
+			.or_else(|| index.and_then(|i| self.positional.get(i)))
+			.map(|s| s.as_str())
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Represents a node of the content split by shortcodes.
+pub enum Node {
+	/// A text.
+	Text(String),
+	/// A shortcode. `inner` is `Some` for block shortcodes.
+	Shortcode {
+		/// The line number of the opening shortcode.
+		line: usize,
+		/// The name.
+		name: String,
+		/// The arguments.
+		args: ShortcodeArgs,
+		/// The nodes between the opening and closing shortcodes.
+		inner: Option<Vec<Node>>,
+	},
+}
+
+enum Token {
+	Text(String),
+	Open(usize, String, ShortcodeArgs, bool),
+	Close(usize, String),
+}
+
+fn line_of(src: &str, offset: usize) -> usize {
+	src[..offset].matches('\n').count() + 1
+}
+
+fn tokenize_args(line: usize, body: &str) -> Result<(String, ShortcodeArgs), ShortcodeError> {
+	let mut chars = body.chars().peekable();
+	let mut words: Vec<(Option<String>, String)> = Vec::new();
+	loop {
+		while chars.next_if(|c| c.is_whitespace()).is_some() {}
+		let Some(&first) = chars.peek() else {
+			break;
+		};
+		let read_value = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+			let mut value = String::new();
+			match chars.peek().copied() {
+				Some(quote @ ('"' | '\'')) => {
+					chars.next();
+					loop {
+						match chars.next() {
+							Some('\\') => value.extend(chars.next()),
+							Some(c) if c == quote => return Ok(value),
+							Some(c) => value.push(c),
+							None => {
+								return Err(ShortcodeError::InvalidSyntax(
+									line,
+									msg!(ShortcodeUnterminatedQuote, body.trim()),
+								));
+							}
+						}
+					}
+				}
+				_ => {
+					while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+						value.push(c);
+					}
+					Ok(value)
+				}
+			}
+		};
+		if first == '"' || first == '\'' {
+			words.push((None, read_value(&mut chars)?));
+			continue;
+		}
+		let mut word = String::new();
+		while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+			word.push(c);
+		}
+		if chars.next_if_eq(&'=').is_some() {
+			words.push((Some(word), read_value(&mut chars)?));
+		} else {
+			words.push((None, word));
+		}
+	}
+
+	let mut words = words.into_iter();
+	let name = match words.next() {
+		Some((None, name)) if !name.is_empty() => name,
+		_ => {
+			return Err(ShortcodeError::InvalidSyntax(
+				line,
+				msg!(ShortcodeNameMissing, body.trim()),
+			));
+		}
+	};
+	let mut args = ShortcodeArgs::default();
+	for (key, value) in words {
+		match key {
+			Some(key) => {
+				args.named.insert(key, value);
+			}
+			None => args.positional.push(value),
+		}
+	}
+	Ok((name, args))
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, ShortcodeError> {
+	let mut tokens = Vec::new();
+	let mut text = String::new();
+	let mut pos = 0;
+	while let Some(found) = src[pos..].find(OPEN) {
+		let start = pos + found;
+		text.push_str(&src[pos..start]);
+		let line = line_of(src, start);
+		let body_start = start + OPEN.len();
+		// {{</* name */>}} はショートコードとして展開せず、そのまま出力する
+		if src[body_start..].trim_start().starts_with("/*") {
+			let Some(end) = src[body_start..].find("*/>}}") else {
+				return Err(ShortcodeError::Unterminated(line));
+			};
+			let body = src[body_start..body_start + end].trim_start();
+			text.push_str(OPEN);
+			text.push_str(body.strip_prefix("/*").unwrap_or(body));
+			text.push_str(CLOSE);
+			pos = body_start + end + "*/>}}".len();
+			continue;
+		}
+		let Some(end) = src[body_start..].find(CLOSE) else {
+			return Err(ShortcodeError::Unterminated(line));
+		};
+		let body = src[body_start..body_start + end].trim();
+		pos = body_start + end + CLOSE.len();
+		if !text.is_empty() {
+			tokens.push(Token::Text(std::mem::take(&mut text)));
+		}
+		if let Some(name) = body.strip_prefix('/') {
+			tokens.push(Token::Close(line, name.trim().to_string()));
+		} else {
+			let (body, self_closing) = match body.strip_suffix('/') {
+				Some(body) => (body, true),
+				None => (body, false),
+			};
+			let (name, args) = tokenize_args(line, body)?;
+			tokens.push(Token::Open(line, name, args, self_closing));
+		}
+	}
+	text.push_str(&src[pos..]);
+	if !text.is_empty() {
+		tokens.push(Token::Text(text));
+	}
+	Ok(tokens)
+}
+
+struct Frame {
+	line: usize,
+	name: String,
+	args: ShortcodeArgs,
+	children: Vec<Node>,
+}
+
+impl Frame {
+	/// Unwinds the frame as an inline shortcode followed by its children.
+	fn unwind(self, nodes: &mut Vec<Node>) {
+		nodes.push(Node::Shortcode {
+			line: self.line,
+			name: self.name,
+			args: self.args,
+			inner: None,
+		});
+		nodes.extend(self.children);
+	}
+}
+
+/// Parses the shortcodes in the content.
+/// An opening shortcode without a matching closing shortcode is an inline shortcode.
+pub fn parse_shortcodes(src: &str) -> Result<Vec<Node>, ShortcodeError> {
+	let mut root = Vec::new();
+	let mut stack: Vec<Frame> = Vec::new();
+	for token in tokenize(src)? {
+		match token {
+			Token::Text(text) => {
+				let nodes = stack.last_mut().map_or(&mut root, |f| &mut f.children);
+				nodes.push(Node::Text(text));
+			}
+			Token::Open(line, name, args, true) => {
+				let nodes = stack.last_mut().map_or(&mut root, |f| &mut f.children);
+				nodes.push(Node::Shortcode {
+					line,
+					name,
+					args,
+					inner: None,
+				});
+			}
+			Token::Open(line, name, args, false) => stack.push(Frame {
+				line,
+				name,
+				args,
+				children: Vec::new(),
+			}),
+			Token::Close(line, name) => {
+				let Some(index) = stack.iter().rposition(|f| f.name == name) else {
+					return Err(ShortcodeError::UnexpectedClose(line, name));
+				};
+				// 対応する開始タグより内側で閉じられていないものはインラインとして扱う
+				let mut unclosed = stack.split_off(index + 1);
+				let mut frame = stack.pop().expect("index is in the stack");
+				let mut children = std::mem::take(&mut frame.children);
+				for f in unclosed.drain(..) {
+					f.unwind(&mut children);
+				}
+				let node = Node::Shortcode {
+					line: frame.line,
+					name: frame.name,
+					args: frame.args,
+					inner: Some(children),
+				};
+				let nodes = stack.last_mut().map_or(&mut root, |f| &mut f.children);
+				nodes.push(node);
+			}
+		}
+	}
+	while let Some(frame) = stack.pop() {
+		let nodes = stack.last_mut().map_or(&mut root, |f| &mut f.children);
+		frame.unwind(nodes);
+	}
+	Ok(root)
+}
+
+/// The context to expand shortcodes in.
+pub struct Shortcodes<'a> {
+	igata: &'a Igata,
+	values: &'a Value,
+	ingots: &'a BTreeMap<usize, (PathBuf, Ingot)>,
+}
+
+fn is_truthy(value: Option<&str>) -> bool {
+	matches!(
+		value.map(|v| v.trim().to_ascii_lowercase()).as_deref(),
+		Some("true" | "yes" | "1")
+	)
+}
+
+impl<'a> Shortcodes<'a> {
+	/// Creates a new context with the igata for templates, the template values and the ingots for `ref`.
+	pub fn new(
+		igata: &'a Igata,
+		values: &'a Value,
+		ingots: &'a BTreeMap<usize, (PathBuf, Ingot)>,
+	) -> Self {
+		Self {
+			igata,
+			values,
+			ingots,
+		}
+	}
+
+	/// Expands the shortcodes in the Markdown content of the ingot.
+	pub fn expand(&self, content: &str, ingot: &Ingot) -> Result<String, ShortcodeError> {
+		if !content.contains(OPEN) {
+			return Ok(content.to_string());
+		}
+		let nodes = parse_shortcodes(content)?;
+		self.expand_nodes(&nodes, ingot)
+	}
+
+	fn expand_nodes(&self, nodes: &[Node], ingot: &Ingot) -> Result<String, ShortcodeError> {
+		let mut out = String::new();
+		for node in nodes {
+			match node {
+				Node::Text(text) => out.push_str(text),
+				Node::Shortcode {
+					line,
+					name,
+					args,
+					inner,
+				} => {
+					let inner = inner
+						.as_ref()
+						.map(|nodes| self.expand_nodes(nodes, ingot))
+						.transpose()?;
+					out.push_str(&self.call(*line, name, args, inner.as_deref(), ingot)?);
+				}
+			}
+		}
+		Ok(out)
+	}
+
+	fn template_name(name: &str) -> String {
+		format!("{SHORTCODE_DIR}/{name}")
+	}
+
+	fn call(
+		&self,
+		line: usize,
+		name: &str,
+		args: &ShortcodeArgs,
+		inner: Option<&str>,
+		ingot: &Ingot,
+	) -> Result<String, ShortcodeError> {
+		// packのテンプレートが組み込みのショートコードより優先される
+		let template = Self::template_name(name);
+		if self.igata.exists(&template) {
+			let inner_html = inner.map(markdown_to_html);
+			let ctx = context! {
+				args => args.named,
+				params => args.positional,
+				inner => inner,
+				inner_html => inner_html.map(Value::from_safe_string),
+				values => self.values,
+				ingot => context! {
+					id => ingot.id,
+					pname => ingot.pname,
+					title => ingot.title,
+					url => url_of(ingot),
+				},
+			};
+			return self
+				.igata
+				.render(&template, ctx)
+				.map(|s| s.trim().to_string())
+				.map_err(|e| ShortcodeError::Render(line, name.to_string(), e));
+		}
+		match name {
+			"callout" => Ok(callout(args, inner)),
+			"figure" => figure(line, args, inner),
+			"ref" => self.reference(line, args, inner, ingot),
+			"youtube" => youtube(line, args),
+			_ => {
+				let prefix = format!("{SHORTCODE_DIR}/");
+				let suffix = format!(".{IGATA_EXT}");
+				let names = self
+					.igata
+					.template_names()
+					.into_iter()
+					.filter_map(|t| {
+						t.strip_prefix(&prefix)
+							.and_then(|t| t.strip_suffix(&suffix))
+							.map(|t| t.to_string())
+					})
+					.collect::<Vec<_>>();
+				let candidates = names.iter().map(|s| s.as_str()).chain(BUILTIN_SHORTCODES);
+				Err(ShortcodeError::Unknown(
+					line,
+					name.to_string(),
+					near_miss_names(name, candidates),
+				))
+			}
+		}
+	}
+
+	fn reference(
+		&self,
+		line: usize,
+		args: &ShortcodeArgs,
+		inner: Option<&str>,
+		ingot: &Ingot,
+	) -> Result<String, ShortcodeError> {
+		let key = args
+			.get("id", None)
+			.or_else(|| args.get("pname", Some(0)))
+			.ok_or_else(|| ShortcodeError::MissingArg(line, "ref".to_string(), "id or pname"))?;
+		let key = RKeyRaw::from(key.to_string());
+		// 同じpnameの生地が複数の言語にあれば、このページと同じ言語のものにリンクする
+		let (_, target) = find_ingot(self.ingots, &key, &ingot.lang).ok_or_else(|| {
+			let pnames = self
+				.ingots
+				.values()
+				.map(|(_, i)| url_path_name(i))
+				.collect::<Vec<_>>();
+			let suggestions = match &key {
+				RKeyRaw::String(name) => near_miss_names(name, pnames.iter().map(|s| s.as_str())),
+				RKeyRaw::Usize(_) => Vec::new(),
+			};
+			ShortcodeError::RefNotFound(line, key.clone(), suggestions)
+		})?;
+		if !matches!(target.status, Status::Publish) {
+			return Err(ShortcodeError::RefNotPublished(line, key));
+		}
+		let url = url_of(target);
+		// テキストがなければURLだけを返し、Markdownのリンクの中で使えるようにする
+		match (args.get("text", None), inner) {
+			(Some(text), _) => Ok(format!(
+				r#"<a href="{}">{}</a>"#,
+				escape_html(&url),
+				escape_html(text)
+			)),
+			(None, Some(inner)) => Ok(format!(
+				r#"<a href="{}">{}</a>"#,
+				escape_html(&url),
+				inner.trim()
+			)),
+			(None, None) => Ok(url),
+		}
+	}
+}
+
+fn class_name(value: &str) -> String {
+	value
+		.chars()
+		.filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+		.collect::<String>()
+		.to_ascii_lowercase()
+}
+
+/// Converts the inner Markdown of a built-in shortcode into HTML.
+/// The expanded HTML goes back into the Markdown, and a blank line ends an HTML block there,
+/// so the newline after each blank line is written as a character reference.
+fn inner_to_html(inner: &str) -> String {
+	let html = markdown_to_html(inner);
+	let mut out = String::with_capacity(html.len());
+	for line in html.trim().split_inclusive('\n') {
+		match line.strip_suffix('\n') {
+			Some(blank) if blank.trim().is_empty() => {
+				out.push_str(blank);
+				out.push_str("&#10;");
+			}
+			_ => out.push_str(line),
+		}
+	}
+	out
+}
+
+fn callout(args: &ShortcodeArgs, inner: Option<&str>) -> String {
+	let kind = class_name(args.get("type", Some(0)).unwrap_or("note"));
+	let mut out = format!(r#"<div class="nibi-callout nibi-callout-{kind}" role="note">"#);
+	if let Some(title) = args.get("title", None) {
+		let _ = write!(
+			out,
+			r#"<p class="nibi-callout-title">{}</p>"#,
+			escape_html(title)
+		);
+	}
+	if let Some(inner) = inner {
+		out.push_str(&inner_to_html(inner));
+	}
+	out.push_str("</div>");
+	out
+}
+
+fn figure(
+	line: usize,
+	args: &ShortcodeArgs,
+	inner: Option<&str>,
+) -> Result<String, ShortcodeError> {
+	let src = args
+		.get("src", Some(0))
+		.ok_or_else(|| ShortcodeError::MissingArg(line, "figure".to_string(), "src"))?;
+	let mut img = format!(r#"<img src="{}""#, escape_html(src));
+	for attr in ["alt", "title", "width", "height"] {
+		if let Some(value) = args.get(attr, None) {
+			let _ = write!(img, r#" {attr}="{}""#, escape_html(value));
+		}
+	}
+	img.push_str(r#" loading="lazy">"#);
+
+	let mut out = String::from(r#"<figure class="nibi-figure"#);
+	if let Some(class) = args.get("class", None) {
+		let _ = write!(out, " {}", escape_html(class));
+	}
+	out.push_str("\">");
+	match args.get("link", None) {
+		Some(link) => {
+			let _ = write!(out, r#"<a href="{}">{img}</a>"#, escape_html(link));
+		}
+		None => out.push_str(&img),
+	}
+	match (args.get("caption", None), inner) {
+		(Some(caption), _) => {
+			let _ = write!(out, "<figcaption>{}</figcaption>", escape_html(caption));
+		}
+		(None, Some(inner)) => {
+			let _ = write!(out, "<figcaption>{}</figcaption>", inner_to_html(inner));
+		}
+		(None, None) => {}
+	}
+	out.push_str("</figure>");
+	Ok(out)
+}
+
+fn youtube(line: usize, args: &ShortcodeArgs) -> Result<String, ShortcodeError> {
+	let id = args
+		.get("id", Some(0))
+		.ok_or_else(|| ShortcodeError::MissingArg(line, "youtube".to_string(), "id"))?;
+	if id.is_empty()
+		|| !id
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+	{
+		return Err(ShortcodeError::InvalidArg(
+			line,
+			"youtube".to_string(),
+			"id",
+			id.to_string(),
+		));
+	}
+	let title = escape_html(args.get("title", None).unwrap_or("YouTube video"));
+	// 静的な表示はサムネイルから動画のページへリンクするだけで、外部のiframeを読み込まない
+	if is_truthy(args.get("static", None)) {
+		let watch_url = format!("https://www.youtube.com/watch?v={id}");
+		return Ok(format!(
+			r#"<div class="nibi-embed nibi-youtube"><a class="nibi-embed-fallback" href="{watch_url}"><img src="https://i.ytimg.com/vi/{id}/hqdefault.jpg" alt="{title}" loading="lazy"></a></div>"#
+		));
+	}
+	Ok(format!(
+		r#"<div class="nibi-embed nibi-youtube"><iframe src="https://www.youtube-nocookie.com/embed/{id}" title="{title}" loading="lazy" allow="encrypted-media; picture-in-picture" allowfullscreen></iframe></div>"#
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn published(id: usize, pname: &str) -> (usize, (PathBuf, Ingot)) {
+		let mut ingot = Ingot::new(id);
+		ingot.pname = pname.to_string();
+		ingot.status = Status::Publish;
+		(id, (PathBuf::from(format!("{pname}.ingot")), ingot))
+	}
+
+	#[test]
+	fn test_parse_shortcodes() {
+		let nodes = parse_shortcodes(
+			"a {{< x 1 k=\"v w\" >}} b {{< box >}}c {{< y />}}{{< /box >}} {{</* z */>}}",
+		)
+		.unwrap();
+		let args = |positional: &[&str], named: &[(&str, &str)]| ShortcodeArgs {
+			positional: positional.iter().map(|s| s.to_string()).collect(),
+			named: named
+				.iter()
+				.map(|(k, v)| (k.to_string(), v.to_string()))
+				.collect(),
+		};
+		assert_eq!(
+			nodes,
+			vec![
+				Node::Text("a ".to_string()),
+				Node::Shortcode {
+					line: 1,
+					name: "x".to_string(),
+					args: args(&["1"], &[("k", "v w")]),
+					inner: None,
+				},
+				Node::Text(" b ".to_string()),
+				Node::Shortcode {
+					line: 1,
+					name: "box".to_string(),
+					args: args(&[], &[]),
+					inner: Some(vec![
+						Node::Text("c ".to_string()),
+						Node::Shortcode {
+							line: 1,
+							name: "y".to_string(),
+							args: args(&[], &[]),
+							inner: None,
+						},
+					]),
+				},
+				Node::Text(" {{< z >}}".to_string()),
+			]
+		);
+		assert!(matches!(
+			parse_shortcodes("{{< /box >}}"),
+			Err(ShortcodeError::UnexpectedClose(1, _))
+		));
+		assert!(matches!(
+			parse_shortcodes("\n{{< box"),
+			Err(ShortcodeError::Unterminated(2))
+		));
+	}
+
+	#[test]
+	fn test_builtin_shortcodes() {
+		let igata = Igata::new(Vec::new());
+		let values = Value::UNDEFINED;
+		let mut draft = published(3, "draft");
+		draft.1.1.status = Status::Draft;
+		let ingots = BTreeMap::from([published(1, "about"), draft]);
+		let shortcodes = Shortcodes::new(&igata, &values, &ingots);
+		let page = Ingot::new(2);
+
+		assert_eq!(
+			shortcodes
+				.expand("[a]({{< ref about >}}) {{< ref 1 text=\"A&B\" >}}", &page)
+				.unwrap(),
+			r#"[a](/about/) <a href="/about/">A&amp;B</a>"#
+		);
+		assert!(matches!(
+			shortcodes.expand("{{< ref abuot >}}", &page),
+			Err(ShortcodeError::RefNotFound(_, _, s)) if s == ["about"]
+		));
+		assert!(matches!(
+			shortcodes.expand("{{< ref 3 >}}", &page),
+			Err(ShortcodeError::RefNotPublished(..))
+		));
+		assert_eq!(
+			shortcodes
+				.expand("{{< callout warning >}}**b**{{< /callout >}}", &page)
+				.unwrap(),
+			r#"<div class="nibi-callout nibi-callout-warning" role="note"><p><strong>b</strong></p></div>"#
+		);
+		// コードブロックの空行でHTMLブロックが終わらないこと
+		let callout = shortcodes
+			.expand(
+				"{{< callout >}}\n```\na\n\n  \n<b>\n```\n{{< /callout >}}\n\nc",
+				&page,
+			)
+			.unwrap();
+		assert_eq!(
+			markdown_to_html(&callout),
+			"<div class=\"nibi-callout nibi-callout-note\" role=\"note\"><pre><code>a\n&#10;  &#10;&lt;b&gt;\n</code></pre></div>\n<p>c</p>\n"
+		);
+		assert_eq!(
+			shortcodes
+				.expand(
+					"{{< figure a.png >}}*b*\n\nc{{< /figure >}}{{< figure a.png caption=\"<d>\" />}}",
+					&page
+				)
+				.unwrap(),
+			concat!(
+				r#"<figure class="nibi-figure"><img src="a.png" loading="lazy"><figcaption><p><em>b</em></p>"#,
+				"\n",
+				r#"<p>c</p></figcaption></figure>"#,
+				r#"<figure class="nibi-figure"><img src="a.png" loading="lazy"><figcaption>&lt;d&gt;</figcaption></figure>"#
+			)
+		);
+		assert!(
+			shortcodes
+				.expand("{{< youtube abc static=true >}}", &page)
+				.unwrap()
+				.contains("https://www.youtube.com/watch?v=abc")
+		);
+		assert!(
+			shortcodes
+				.expand("{{< youtube abc >}}", &page)
+				.unwrap()
+				.contains(r#"allowfullscreen></iframe></div>"#)
+		);
+		assert!(matches!(
+			shortcodes.expand("{{< youtube \"a b\" >}}", &page),
+			Err(ShortcodeError::InvalidArg(..))
+		));
+		assert!(matches!(
+			shortcodes.expand("{{< figur a.png >}}", &page),
+			Err(ShortcodeError::Unknown(_, _, s)) if s == ["figure"]
+		));
+	}
+}
@@ -7,7 +7,10 @@ use combu::Vector;
 
 use serde::{Deserialize, Serialize};
 
-use super::serde::{DeResult, FileType, read_deserialized_value};
+use super::{
+	i18n::localized_attr,
+	serde::{DeResult, FileType, read_deserialized_file, read_deserialized_value},
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 /// Represents a category for the site articles.
@@ -76,6 +79,16 @@ impl Category {
 		)
 	}
 
+	/// Returns the name of the category in the given language. `name.<lang>` in the optional attributes is used if exists.
+	pub fn name_in(&self, lang: &str) -> &str {
+		localized_attr(&self.opt_attrs, "name", lang).unwrap_or(&self.name)
+	}
+
+	/// Returns the description of the category in the given language. `description.<lang>` in the optional attributes is used if exists.
+	pub fn description_in(&self, lang: &str) -> &str {
+		localized_attr(&self.opt_attrs, "description", lang).unwrap_or(&self.description)
+	}
+
 	/// Appends a child category to this category.
 	pub fn append_child(&mut self, child: Category) {
 		self.children.push(child);
@@ -156,17 +169,21 @@ pub fn exists_id_in_category_list(list: &[Category], id: usize) -> bool {
 	list.iter().any(|category| category.exists_id(id))
 }
 
-fn categories_file_path(dir_path: &Path) -> PathBuf {
-	dir_path.join("categories.ron")
+/// Returns the base path (without extension) of the categories file in the given directory path.
+pub fn categories_file_base_path(dir_path: &Path) -> PathBuf {
+	dir_path.join("categories")
 }
-fn read_categories<R: std::io::Read>(reader: R, file_type: FileType) -> DeResult<Vec<Category>> {
+/// Reads the categories from the given reader.
+pub fn read_categories<R: std::io::Read>(
+	reader: R,
+	file_type: FileType,
+) -> DeResult<Vec<Category>> {
 	read_deserialized_value(reader, file_type)
 }
 /// Returns the list of categories from the given directory path, if the categories file exists.
+/// The categories file can be written in any supported file type.
 pub fn get_categories_from_dir_path(dir_path: &Path) -> Option<Vec<Category>> {
-	let file_path = categories_file_path(dir_path);
-	let file = std::fs::File::open(file_path).ok()?;
-	read_categories(file, FileType::Ron).ok()
+	read_deserialized_file(&categories_file_base_path(dir_path)).ok()
 }
 
 /// Returns a map of category IDs to category references for the given list of categories.
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::Error as IOError;
 use std::path::{Path, PathBuf};
@@ -10,6 +11,8 @@ use super::fs::io::{new_empty_file, open_file_with_overwrite_mode, open_file_wit
 use super::serde::{
 	DeResult, FileType, SerResult, read_deserialized_value, write_serialized_string_all,
 };
+use super::value::ValueMap;
+use crate::msg;
 
 #[derive(Debug, Deserialize, Serialize)]
 /// Config struct
@@ -18,8 +21,17 @@ pub struct Config {
 	site_name: String,
 	#[serde(default, skip_serializing_if = "DirConf::is_default")]
 	dir_conf: DirConf,
-	#[serde(default, skip_serializing_if = "is_default_recipe_name")]
+	#[serde(
+		default = "recipe_path_default",
+		skip_serializing_if = "is_default_recipe_name"
+	)]
 	recipe: String,
+	#[serde(default, skip_serializing_if = "ExcerptConf::is_default")]
+	excerpt: ExcerptConf,
+	#[serde(default, skip_serializing_if = "I18nConf::is_default")]
+	i18n: I18nConf,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	profiles: BTreeMap<String, Profile>,
 }
 /// Default project name
 pub fn default_project_name() -> String {
@@ -47,6 +59,9 @@ impl Default for Config {
 			site_name: default_site_name(),
 			dir_conf: DirConf::default(),
 			recipe: recipe_path_default(),
+			excerpt: ExcerptConf::default(),
+			i18n: I18nConf::default(),
+			profiles: BTreeMap::new(),
 		}
 	}
 }
@@ -59,6 +74,9 @@ impl Config {
 			site_name,
 			dir_conf: DirConf::default(),
 			recipe: recipe_path_default(),
+			excerpt: ExcerptConf::default(),
+			i18n: I18nConf::default(),
+			profiles: BTreeMap::new(),
 		}
 	}
 	/// Sets the project name
@@ -93,6 +111,22 @@ impl Config {
 	pub fn get_recipe(&self) -> &String {
 		&self.recipe
 	}
+	/// Returns the excerpt configuration
+	pub fn get_excerpt_conf(&self) -> &ExcerptConf {
+		&self.excerpt
+	}
+	/// Returns the i18n configuration
+	pub fn get_i18n_conf(&self) -> &I18nConf {
+		&self.i18n
+	}
+	/// Returns the build profile with the given name
+	pub fn get_profile(&self, name: &str) -> Option<&Profile> {
+		self.profiles.get(name)
+	}
+	/// Returns the build profiles
+	pub fn get_profiles(&self) -> &BTreeMap<String, Profile> {
+		&self.profiles
+	}
 	/// Takes the recipe name out of the config
 	pub fn take_recipe(mut self) -> String {
 		core::mem::take(&mut self.recipe)
@@ -122,6 +156,16 @@ pub struct DirConf {
 		skip_serializing_if = "is_gears_path_default"
 	)]
 	gears: PathBuf, //アドオン設定置き予定
+	#[serde(
+		default = "resources_path_default",
+		skip_serializing_if = "is_resources_path_default"
+	)]
+	resources: PathBuf, // 画像ファイルなど
+	#[serde(
+		default = "cache_path_default",
+		skip_serializing_if = "is_cache_path_default"
+	)]
+	cache: PathBuf, // 変換した画像などのキャッシュ
 }
 
 fn site_path_default() -> PathBuf {
@@ -156,6 +200,22 @@ fn is_gears_path_default(path: &Path) -> bool {
 	path == gears_path_default()
 }
 
+fn resources_path_default() -> PathBuf {
+	PathBuf::from(String::from("resources"))
+}
+
+fn is_resources_path_default(path: &Path) -> bool {
+	path == resources_path_default()
+}
+
+fn cache_path_default() -> PathBuf {
+	PathBuf::from(String::from(".cache"))
+}
+
+fn is_cache_path_default(path: &Path) -> bool {
+	path == cache_path_default()
+}
+
 impl Default for DirConf {
 	fn default() -> Self {
 		Self {
@@ -163,6 +223,8 @@ impl Default for DirConf {
 			zairyo: zairyo_path_default(),
 			igata: igata_path_default(),
 			gears: gears_path_default(),
+			resources: resources_path_default(),
+			cache: cache_path_default(),
 		}
 	}
 }
@@ -171,7 +233,7 @@ impl DirConf {
 	/// Creates the source directories for the app
 	pub fn create_src_dirs(&self, parent_path: &Path) -> Result<(), Vec<(IOError, &PathBuf)>> {
 		let mut errs = vec![];
-		for path in [&self.zairyo, &self.igata, &self.gears] {
+		for path in [&self.zairyo, &self.igata, &self.gears, &self.resources] {
 			if let Err(e) = fs::create_dir(parent_path.join(path)) {
 				errs.push((e, path));
 			}
@@ -197,11 +259,160 @@ impl DirConf {
 		parent_path.join(&self.gears)
 	}
 
+	/// Returns the path to the resources directory
+	pub fn get_resources_path(&self, parent_path: &Path) -> PathBuf {
+		parent_path.join(&self.resources)
+	}
+
 	/// Returns the path to the site directory
 	pub fn get_site_path(&self, parent_path: &Path) -> PathBuf {
 		parent_path.join(&self.site)
 	}
+
+	/// Returns the path to the cache directory
+	pub fn get_cache_path(&self, parent_path: &Path) -> PathBuf {
+		parent_path.join(&self.cache)
+	}
+}
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq)]
+/// Build profile. Selects a recipe, an output directory and value overrides for a build
+pub struct Profile {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	recipe: Option<String>, // 使うレシピ名
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	site: Option<PathBuf>, // 出力先
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	values: ValueMap, // レシピの値の上書き
+}
+
+impl Profile {
+	/// Creates a new build profile
+	pub fn new(recipe: Option<String>, site: Option<PathBuf>, values: ValueMap) -> Self {
+		Self {
+			recipe,
+			site,
+			values,
+		}
+	}
+	/// Returns the recipe name of the profile
+	pub fn get_recipe(&self) -> Option<&String> {
+		self.recipe.as_ref()
+	}
+	/// Returns the path to the site directory of the profile, if specified
+	pub fn get_site_path(&self, parent_path: &Path) -> Option<PathBuf> {
+		self.site.as_ref().map(|site| parent_path.join(site))
+	}
+	/// Returns the value overrides of the profile
+	pub fn get_values(&self) -> &ValueMap {
+		&self.values
+	}
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+/// Excerpt configuration for ingots
+pub struct ExcerptConf {
+	#[serde(
+		default = "excerpt_separator_default",
+		skip_serializing_if = "is_excerpt_separator_default"
+	)]
+	separator: String, // 本文中で抜粋の終わりを示す区切り
+	#[serde(
+		default = "summary_length_default",
+		skip_serializing_if = "is_summary_length_default"
+	)]
+	summary_length: usize, // 自動生成する要約の文字数
+}
+
+fn excerpt_separator_default() -> String {
+	String::from("<!--more-->")
+}
+
+fn is_excerpt_separator_default(separator: &String) -> bool {
+	separator == &excerpt_separator_default()
+}
+
+fn summary_length_default() -> usize {
+	120
+}
+
+fn is_summary_length_default(length: &usize) -> bool {
+	*length == summary_length_default()
+}
+
+impl Default for ExcerptConf {
+	fn default() -> Self {
+		Self {
+			separator: excerpt_separator_default(),
+			summary_length: summary_length_default(),
+		}
+	}
+}
+
+impl ExcerptConf {
+	/// Creates a new excerpt configuration
+	pub fn new<T: Into<String>>(separator: T, summary_length: usize) -> Self {
+		Self {
+			separator: separator.into(),
+			summary_length,
+		}
+	}
+	/// Returns `true` if the config is the default
+	pub fn is_default(&self) -> bool {
+		self == &ExcerptConf::default()
+	}
+	/// Returns the separator that marks the end of the excerpt in an ingot body
+	pub fn get_separator(&self) -> &str {
+		&self.separator
+	}
+	/// Returns the number of characters of the auto-generated summary
+	pub fn get_summary_length(&self) -> usize {
+		self.summary_length
+	}
 }
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+/// Multilingual site configuration
+pub struct I18nConf {
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	default_lang: String, // 言語の指定がない生地の言語
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	prefixes: BTreeMap<String, String>, // 言語ごとのパーマリンクの接頭辞
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	cli_lang: String, // CLIのメッセージの言語
+}
+
+impl I18nConf {
+	/// Creates a new i18n configuration
+	pub fn new<T: Into<String>>(default_lang: T, prefixes: BTreeMap<String, String>) -> Self {
+		Self {
+			default_lang: default_lang.into(),
+			prefixes,
+			cli_lang: String::new(),
+		}
+	}
+	/// Returns `true` if the config is the default
+	pub fn is_default(&self) -> bool {
+		self == &I18nConf::default()
+	}
+	/// Returns the language of the ingots which do not declare one
+	pub fn get_default_lang(&self) -> &str {
+		&self.default_lang
+	}
+	/// Returns the language of the CLI messages (e.g. `en`, `ja`). Empty means the locale of the environment.
+	pub fn get_cli_lang(&self) -> &str {
+		&self.cli_lang
+	}
+	/// Returns the permalink prefix of the given language, without slashes.
+	/// The prefix in `prefixes` is used if exists. Otherwise the default language has no prefix,
+	/// and the other languages use the language code if the default language is set.
+	pub fn prefix_of<'a>(&'a self, lang: &'a str) -> &'a str {
+		match self.prefixes.get(lang) {
+			Some(prefix) => prefix.trim().trim_matches('/'),
+			None if lang.is_empty() || self.default_lang.is_empty() || lang == self.default_lang => "",
+			None => lang,
+		}
+	}
+}
+
 /// Returns the default config file type (RON)
 pub fn default_config_file_type() -> FileType {
 	FileType::Ron
@@ -224,7 +435,7 @@ pub fn create_config_file(
 			if config.to_file(&target_file, file_type).is_ok() {
 				Ok(target_file)
 			} else {
-				Err(IOError::other("configファイルの作成に失敗しました"))
+				Err(IOError::other(msg!(ConfigFileWriteFailed)))
 			}
 		}
 		err => err,
@@ -237,7 +448,7 @@ pub fn reset_config_file(config_path: &Path, config: &Config) -> Result<File, IO
 			if config.to_file(&target_file, FileType::Ron).is_ok() {
 				Ok(target_file)
 			} else {
-				Err(IOError::other("configファイルの作成に失敗しました"))
+				Err(IOError::other(msg!(ConfigFileWriteFailed)))
 			}
 		}
 		err => err,
@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+	author::{Author, get_authors_from_dir_path},
+	build::{
+		BuildError, BuildOptions, collect_ingot_paths, load_ingots, read_effective_recipe,
+		resolve_packs, select_profile,
+	},
+	category::{Category, get_categories_from_dir_path, get_index_map_from_categories},
+	config::Config,
+	igata::pack::PackProperties,
+	ingot::Ingot,
+	recipe::Recipe,
+	serde::{FileType, SerResult, get_serialized_string},
+	tag::{Tag, get_index_map_from_tags, get_tags_from_dir_path},
+};
+
+#[derive(Serialize, Deserialize)]
+/// Represents the whole content model of a project, for integrations and backups.
+pub struct ExportDump {
+	/// The site name in the config.
+	pub site_name: String,
+	/// The parsed ingots in the order of their IDs, with the categories and tags collated to IDs.
+	/// The paths of the ingots are relative to the zairyo directory.
+	pub ingots: Vec<Ingot>,
+	/// The tags.
+	pub tags: Vec<Tag>,
+	/// The category tree.
+	pub categories: Vec<Category>,
+	/// The authors.
+	pub authors: Vec<Author>,
+	/// The recipe used for the build, with the value overrides of the profile merged.
+	pub recipe: Recipe,
+	/// The packs of the recipe with their dependencies, in the order of priority.
+	pub packs: Vec<PackProperties>,
+}
+
+impl ExportDump {
+	/// Returns the dump serialized in the given file type.
+	pub fn to_serialized_string(&self, file_type: FileType) -> SerResult<String> {
+		get_serialized_string(self, file_type)
+	}
+}
+
+/// Collects the content model of the project as the build sees it.
+///
+/// The recipe and the profile are selected in the same way as the build.
+/// Returns the dump with the problems found on the way, such as invalid ingots and unresolved packs,
+/// or the error if the profile or the recipe could not be read.
+pub fn export(
+	config: &Config,
+	proj_path: &Path,
+	options: &BuildOptions,
+) -> Result<(ExportDump, Vec<BuildError>), BuildError> {
+	let profile = select_profile(config, options)?;
+	let recipe = read_effective_recipe(config, proj_path, options, profile)?;
+	let dir_conf = config.get_dir_conf();
+	let (packs, pack_errors) = resolve_packs(&recipe, &dir_conf.get_igata_path(proj_path));
+	let mut errors = pack_errors
+		.into_iter()
+		.map(BuildError::Pack)
+		.collect::<Vec<_>>();
+
+	let zairyo_dir = dir_conf.get_zairyo_path(proj_path);
+	let categories = get_categories_from_dir_path(&zairyo_dir).unwrap_or_default();
+	let tags = get_tags_from_dir_path(&zairyo_dir).unwrap_or_default();
+	let (ingots, mut ingot_errors) = load_ingots(
+		&collect_ingot_paths(&zairyo_dir),
+		config.get_excerpt_conf(),
+		config.get_i18n_conf(),
+		&get_index_map_from_categories(&categories),
+		&get_index_map_from_tags(&tags),
+	);
+	errors.append(&mut ingot_errors);
+
+	let dump = ExportDump {
+		site_name: config.site_name_ref().clone(),
+		// ローカルの絶対パスを出力しないように、zairyoディレクトリからの相対パスにする
+		ingots: ingots
+			.into_values()
+			.map(|(_, mut ingot)| {
+				if let Ok(path) = ingot.path.strip_prefix(&zairyo_dir) {
+					ingot.path = path.to_path_buf();
+				}
+				ingot
+			})
+			.collect(),
+		tags,
+		categories,
+		authors: get_authors_from_dir_path(&zairyo_dir).unwrap_or_default(),
+		recipe,
+		packs,
+	};
+	Ok((dump, errors))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::testing::TempDir;
+	use crate::app::{
+		config::create_config_file,
+		ingot::ingot::{RKeyList, RKeyRaw, Status, To},
+		serde::get_deselialized_value,
+	};
+	use strum::IntoEnumIterator;
+
+	#[test]
+	fn test_export_round_trip() {
+		let dir = TempDir::new("export");
+		let zairyo = dir.join("zairyo");
+		std::fs::create_dir_all(&zairyo).unwrap();
+		let config = Config::default();
+		create_config_file(&dir.join("config.ron"), &config, FileType::Ron).unwrap();
+		std::fs::write(dir.join("recipe.ron"), "(pack: [], overrides: ())").unwrap();
+		std::fs::write(
+			zairyo.join("tags.ron"),
+			"[(id: 4, name: \"Rust\", path_name: \"rust\", description: \"\")]",
+		)
+		.unwrap();
+		std::fs::write(
+			zairyo.join("a.ingot"),
+			"id: 2\nstatus: publish\nto: page\ntags: [rust]\n\nA\n\nbody\n",
+		)
+		.unwrap();
+		std::fs::write(zairyo.join("b.ingot"), "id: 1\ntags: [none]\n\nB\n\nb\n").unwrap();
+
+		let (dump, errors) = export(&config, &dir, &BuildOptions::default()).unwrap();
+		assert_eq!(errors.len(), 1);
+		assert_eq!(dump.ingots.iter().map(|i| i.id).collect::<Vec<_>>(), [1, 2]);
+
+		for file_type in FileType::iter() {
+			assert!(dump.to_serialized_string(file_type).is_ok(), "{file_type}");
+		}
+		for file_type in [FileType::Ron, FileType::Json] {
+			let s = dump.to_serialized_string(file_type).unwrap();
+			let read: ExportDump = get_deselialized_value(&s, file_type).unwrap();
+			let a = &read.ingots[1];
+			assert_eq!(a.title, "A");
+			assert_eq!(a.path, Path::new("a.ingot"));
+			assert!(matches!(a.status, Status::Publish));
+			assert!(matches!(a.to, To::Page));
+			assert!(matches!(&a.tags, RKeyList::Raw(raw) if matches!(raw[..], [RKeyRaw::Usize(4)])));
+			assert_eq!(read.tags[0].path_name, "rust");
+			assert_eq!(read.recipe.values.len(), dump.recipe.values.len());
+		}
+	}
+}
@@ -1,4 +1,9 @@
-use std::path::{Path, PathBuf};
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use walkdir::WalkDir;
 
 /// io utility module
 pub mod io;
@@ -16,3 +21,21 @@ pub fn get_child_dirs(dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
 			.collect()
 	})
 }
+
+/// Copies the directory and all its contents into the destination directory.
+/// The destination directory is created if it does not exist.
+pub fn copy_dir_all(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
+	for entry in WalkDir::new(src) {
+		let entry = entry.map_err(std::io::Error::other)?;
+		let Ok(rel) = entry.path().strip_prefix(src) else {
+			continue;
+		};
+		let target = dst.join(rel);
+		if entry.file_type().is_dir() {
+			fs::create_dir_all(&target)?;
+		} else {
+			fs::copy(entry.path(), &target)?;
+		}
+	}
+	Ok(())
+}
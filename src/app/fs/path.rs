@@ -1,7 +1,7 @@
 use std::{
 	collections::BTreeMap,
 	env::current_dir,
-	path::{self, Path, PathBuf},
+	path::{self, Component, Path, PathBuf},
 };
 /// Returns the absolute path of the given path string.
 pub fn get_abs_path<T: Into<PathBuf>>(path_str: T) -> PathBuf {
@@ -44,6 +44,11 @@ pub fn append_ext(path: PathBuf, ext: &str) -> PathBuf {
 	path
 }
 
+/// Returns true if the path is relative and does not go up the directory.
+pub fn is_contained_path(path: &Path) -> bool {
+	!path.as_os_str().is_empty() && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
 /// Returns the parent path of the given path.
 pub fn to_parent_path(path: PathBuf) -> PathBuf {
 	path.parent().unwrap().to_path_buf()
@@ -0,0 +1,676 @@
+use std::{
+	collections::BTreeMap,
+	fmt::Display,
+	io::Error as IOError,
+	path::{Path, PathBuf},
+	str::FromStr,
+	sync::Arc,
+};
+
+use minijinja::{Error as TemplateError, ErrorKind as TemplateErrorKind, Value, value::Rest};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::{
+	build::render::{RenderedPage, collated_ids, url_of},
+	fs::path::is_contained_path,
+	igata::igata::Igata,
+	ingot::{
+		Ingot,
+		ingot::{RKeyList, Status, To},
+	},
+	serde::{DeError, FileType, read_deserialized_file},
+	value::{ValueMap, merge_values},
+};
+use crate::{app::i18n::catalog::Msg, msg};
+
+/// external process module
+pub mod process;
+/// WebAssembly runtime module
+pub mod wasm;
+
+use process::{ProcessError, call_process};
+use wasm::{DEFAULT_FUEL, WasmError, WasmGear};
+
+#[derive(
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Serialize,
+	Deserialize,
+	strum::Display,
+	strum::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+/// The build stages that gears can hook.
+pub enum Hook {
+	/// After the ingots are loaded. Gears can transform the ingots.
+	AfterIngotLoad,
+	/// Before the ingots are rendered. Gears can inject template values.
+	BeforeRender,
+	/// After the ingots are rendered. Gears can transform the rendered pages.
+	AfterRender,
+	/// After the site is written. Not called in check mode.
+	AfterBuild,
+}
+
+fn enabled_default() -> bool {
+	true
+}
+
+fn is_enabled_default(enabled: &bool) -> bool {
+	*enabled
+}
+
+fn is_empty_path(path: &Path) -> bool {
+	path.as_os_str().is_empty()
+}
+
+fn fuel_default() -> u64 {
+	DEFAULT_FUEL
+}
+
+fn is_fuel_default(fuel: &u64) -> bool {
+	*fuel == DEFAULT_FUEL
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Represents the permissions granted to a WASM gear. Nothing is granted by default.
+pub struct Grants {
+	/// The directories the gear can read, relative to the gears directory.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub read: Vec<PathBuf>,
+}
+
+impl Grants {
+	/// Returns true if nothing is granted.
+	pub fn is_empty(&self) -> bool {
+		self.read.is_empty()
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Represents a gear declared in a file of the gears directory.
+pub struct Gear {
+	/// The name of the gear. The file name is used if empty.
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pub name: String,
+	/// The command to run. Relative paths are resolved from the gears directory.
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	pub command: String,
+	/// The arguments of the command.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub args: Vec<String>,
+	/// The WASM module to run instead of the command, relative to the gears directory.
+	#[serde(default, skip_serializing_if = "is_empty_path")]
+	pub wasm: PathBuf,
+	/// The permissions granted to the WASM module.
+	#[serde(default, skip_serializing_if = "Grants::is_empty")]
+	pub grants: Grants,
+	/// The fuel a call of the WASM module can consume before it is aborted.
+	#[serde(default = "fuel_default", skip_serializing_if = "is_fuel_default")]
+	pub fuel: u64,
+	/// The template filters provided by the WASM module.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub filters: Vec<String>,
+	/// The hooks the gear is called at.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub hooks: Vec<Hook>,
+	/// Whether the gear is enabled.
+	#[serde(
+		default = "enabled_default",
+		skip_serializing_if = "is_enabled_default"
+	)]
+	pub enabled: bool,
+	/// The options passed to the gear on every call.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub options: ValueMap,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// Represents an ingot exchanged with gears.
+pub struct GearIngot {
+	/// The ID of the ingot. Used to match the ingot in responses and can not be changed.
+	pub id: usize,
+	/// The author ID.
+	#[serde(default)]
+	pub author: usize,
+	/// The path name.
+	#[serde(default)]
+	pub pname: String,
+	/// The title.
+	#[serde(default)]
+	pub title: String,
+	/// The excerpt.
+	#[serde(default)]
+	pub excerpt: String,
+	/// The Markdown content.
+	#[serde(default)]
+	pub content: String,
+	/// The status (draft, publish or private).
+	#[serde(default)]
+	pub status: String,
+	/// The build type.
+	#[serde(default)]
+	pub to: String,
+	/// The published timestamp.
+	#[serde(default)]
+	pub published: String,
+	/// The updated timestamp.
+	#[serde(default)]
+	pub updated: String,
+	/// The collated tag IDs.
+	#[serde(default)]
+	pub tags: Vec<usize>,
+	/// The collated category IDs.
+	#[serde(default)]
+	pub categories: Vec<usize>,
+	/// The URL of the ingot. Ignored in responses.
+	#[serde(default, skip_deserializing)]
+	pub url: String,
+}
+
+impl From<&Ingot> for GearIngot {
+	fn from(ingot: &Ingot) -> Self {
+		GearIngot {
+			id: ingot.id,
+			author: ingot.author,
+			pname: ingot.pname.clone(),
+			title: ingot.title.clone(),
+			excerpt: ingot.excerpt.clone(),
+			content: ingot.content.clone(),
+			status: ingot.status.to_string().to_ascii_lowercase(),
+			to: ingot.to.name().to_string(),
+			published: ingot.published.to_string(),
+			updated: ingot.updated.to_string(),
+			tags: collated_ids(&ingot.tags).to_vec(),
+			categories: collated_ids(&ingot.categories).to_vec(),
+			url: url_of(ingot),
+		}
+	}
+}
+
+impl GearIngot {
+	/// Applies the fields to the ingot. Returns the name of the invalid field on failure.
+	pub fn apply(self, ingot: &mut Ingot) -> Result<(), &'static str> {
+		let status = Status::from_str(&self.status).map_err(|_| "status")?;
+		let published = self.published.parse().map_err(|_| "published")?;
+		let updated = self.updated.parse().map_err(|_| "updated")?;
+		ingot.author = self.author;
+		ingot.pname = self.pname;
+		ingot.title = self.title;
+		ingot.excerpt = self.excerpt;
+		ingot.content = self.content;
+		ingot.status = status;
+		ingot.to = To::from(self.to.as_str());
+		ingot.published = published;
+		ingot.updated = updated;
+		ingot.tags = RKeyList::CollatedId(self.tags);
+		ingot.categories = RKeyList::CollatedId(self.categories);
+		Ok(())
+	}
+}
+
+#[derive(Debug, Serialize)]
+/// Represents a request written to the stdin of a gear as JSON.
+pub struct GearRequest<'a> {
+	/// The hook being called.
+	pub hook: Hook,
+	/// The name of the gear.
+	pub gear: &'a str,
+	/// The options of the gear.
+	pub options: &'a ValueMap,
+	/// The ingots. Sent at `after_ingot_load` and `before_render`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub ingots: Option<Vec<GearIngot>>,
+	/// The template values. Sent at `before_render`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub values: Option<&'a ValueMap>,
+	/// The rendered pages. Sent at `after_render` and `after_build`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub pages: Option<&'a [RenderedPage]>,
+	/// The site directory. Sent at `after_build`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub site_dir: Option<&'a Path>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+/// Represents a response read from the stdout of a gear as JSON.
+/// Every field is optional, and an empty output means no change.
+pub struct GearResponse {
+	/// The transformed ingots. Ingots not included are left as is.
+	#[serde(default)]
+	pub ingots: Option<Vec<GearIngot>>,
+	/// The values merged into the template values.
+	#[serde(default)]
+	pub values: Option<ValueMap>,
+	/// The pages replacing the rendered pages.
+	#[serde(default)]
+	pub pages: Option<Vec<RenderedPage>>,
+	/// The extra files written into the site directory.
+	#[serde(default)]
+	pub files: Vec<RenderedPage>,
+}
+
+#[derive(Debug)]
+/// Represents an error while loading or calling gears.
+pub enum GearError {
+	/// Failed to read the gears directory.
+	ReadDir(PathBuf, IOError),
+	/// Failed to read a gear declaration file.
+	Load(PathBuf, Box<DeError>),
+	/// The gear declaration is invalid.
+	Declaration(PathBuf, Msg),
+	/// Failed to load the WASM module of the gear.
+	WasmLoad(PathBuf, WasmError),
+	/// The WASM module of the gear failed.
+	Wasm(String, Hook, WasmError),
+	/// Failed to run the gear.
+	Process(String, Hook, ProcessError),
+	/// The output of the gear is not a valid response.
+	Protocol(String, Hook, serde_json::Error),
+	/// The gear returned an ingot with an invalid field.
+	InvalidIngot(String, Hook, usize, &'static str),
+	/// The gear returned a file path outside the site directory.
+	InvalidPath(String, Hook, PathBuf),
+}
+
+impl Display for GearError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			GearError::ReadDir(path, e) => write!(f, "{}: {e}", path.display()),
+			GearError::Load(path, e) => write!(f, "{}", msg!(GearReadFailed, path.display(), e)),
+			GearError::Declaration(path, reason) => write!(f, "{}: {reason}", path.display()),
+			GearError::WasmLoad(path, e) => write!(f, "{}: {e}", path.display()),
+			GearError::Wasm(name, hook, e) => write!(f, "{}", msg!(GearFailed, name, hook, e)),
+			GearError::Process(name, hook, e) => write!(f, "{}", msg!(GearFailed, name, hook, e)),
+			GearError::Protocol(name, hook, e) => {
+				write!(f, "{}", msg!(GearInvalidResponse, name, hook, e))
+			}
+			GearError::InvalidIngot(name, hook, id, field) => {
+				write!(f, "{}", msg!(GearInvalidIngot, name, hook, field, id))
+			}
+			GearError::InvalidPath(name, hook, path) => {
+				write!(f, "{}", msg!(GearInvalidPath, name, hook, path.display()))
+			}
+		}
+	}
+}
+
+/// Represents the enabled gears of the project.
+pub struct Gears {
+	dir: PathBuf,
+	gears: Vec<Gear>,
+	wasm: BTreeMap<String, Arc<WasmGear>>,
+	processes: bool,
+}
+
+impl Gears {
+	/// Loads the enabled gears from the declaration files in the gears directory.
+	/// The files are read in the order of their names, which is also the order the gears are called in.
+	/// A missing gears directory means no gears.
+	pub fn load(gears_dir: &Path) -> (Gears, Vec<GearError>) {
+		let mut gears = Gears {
+			dir: gears_dir.to_path_buf(),
+			gears: Vec::new(),
+			wasm: BTreeMap::new(),
+			processes: true,
+		};
+		if !gears_dir.is_dir() {
+			return (gears, Vec::new());
+		}
+		let mut paths = match gears_dir.read_dir() {
+			Ok(entries) => entries
+				.filter_map(|entry| entry.ok().map(|e| e.path()))
+				.filter(|path| {
+					path.is_file()
+						&& path
+							.extension()
+							.is_some_and(|ext| FileType::from_str(&ext.to_string_lossy()).is_ok())
+				})
+				.collect::<Vec<_>>(),
+			Err(e) => return (gears, vec![GearError::ReadDir(gears_dir.to_path_buf(), e)]),
+		};
+		paths.sort();
+
+		let mut errors = Vec::new();
+		for path in paths {
+			match read_deserialized_file::<Gear>(&path) {
+				Ok(mut gear) => {
+					if gear.name.is_empty() {
+						gear.name = path
+							.file_stem()
+							.map(|s| s.to_string_lossy().to_string())
+							.unwrap_or_default();
+					}
+					if !gear.enabled {
+						continue;
+					}
+					match gears.prepare(&gear, &path) {
+						Ok(Some(wasm)) => {
+							gears.wasm.insert(gear.name.clone(), Arc::new(wasm));
+							gears.gears.push(gear);
+						}
+						Ok(None) => gears.gears.push(gear),
+						Err(e) => errors.push(e),
+					}
+				}
+				Err(e) => errors.push(GearError::Load(path, Box::new(e))),
+			}
+		}
+		(gears, errors)
+	}
+
+	/// Checks the declaration of the gear and loads its WASM module if specified.
+	fn prepare(&self, gear: &Gear, path: &Path) -> Result<Option<WasmGear>, GearError> {
+		let declared = |reason| Err(GearError::Declaration(path.to_path_buf(), reason));
+		if self.gears.iter().any(|g| g.name == gear.name) {
+			return declared(Msg::GearDuplicateName);
+		}
+		match (gear.command.is_empty(), is_empty_path(&gear.wasm)) {
+			(true, true) => declared(Msg::GearNoRuntime),
+			(false, false) => declared(Msg::GearBothRuntimes),
+			(false, true) if !gear.filters.is_empty() => declared(Msg::GearFiltersWasmOnly),
+			(false, true) if !gear.grants.is_empty() => declared(Msg::GearGrantsWasmOnly),
+			(false, true) => Ok(None),
+			(true, false) => WasmGear::load(
+				&gear.name,
+				&self.dir.join(&gear.wasm),
+				&self.dir,
+				&gear.grants.read,
+				gear.fuel,
+				!gear.filters.is_empty(),
+			)
+			.map(Some)
+			.map_err(|e| GearError::WasmLoad(path.to_path_buf(), e)),
+		}
+	}
+	/// Returns the loaded gears.
+	pub fn get_gears(&self) -> &[Gear] {
+		&self.gears
+	}
+	/// Returns true if no gear is loaded.
+	pub fn is_empty(&self) -> bool {
+		self.gears.is_empty()
+	}
+	/// Stops calling the gears which run external commands. The WASM gears are still called.
+	pub fn skip_processes(&mut self) {
+		self.processes = false;
+	}
+
+	/// Registers the template filters provided by the WASM gears to the igata.
+	pub fn register_filters(&self, igata: &mut Igata) {
+		for gear in self.gears.iter() {
+			let Some(wasm) = self.wasm.get(&gear.name) else {
+				continue;
+			};
+			for filter in gear.filters.iter() {
+				let wasm = Arc::clone(wasm);
+				let gear_name = gear.name.clone();
+				let filter_name = filter.clone();
+				igata.add_filter(filter.clone(), move |value: Value, args: Rest<Value>| {
+					let error = |detail: String| {
+						TemplateError::new(
+							TemplateErrorKind::InvalidOperation,
+							msg!(GearFilterFailed, gear_name, filter_name, detail),
+						)
+					};
+					let input = serde_json::json!({
+						"filter": filter_name,
+						"value": value,
+						"args": args.0,
+					});
+					let output = wasm
+						.filter(&input.to_string())
+						.map_err(|e| error(e.to_string()))?;
+					serde_json::from_str::<serde_json::Value>(&output)
+						.map(|v| Value::from_serialize(&v))
+						.map_err(|e| error(e.to_string()))
+				});
+			}
+		}
+	}
+
+	fn hooked(&self, hook: Hook) -> impl Iterator<Item = &Gear> {
+		self.gears.iter().filter(move |g| {
+			g.hooks.contains(&hook) && (self.processes || self.wasm.contains_key(&g.name))
+		})
+	}
+
+	fn call(&self, gear: &Gear, request: &GearRequest) -> Result<GearResponse, GearError> {
+		let input = serde_json::to_string(request)
+			.map_err(|e| GearError::Protocol(gear.name.clone(), request.hook, e))?;
+		let output = match self.wasm.get(&gear.name) {
+			Some(wasm) => wasm
+				.call(&input)
+				.map_err(|e| GearError::Wasm(gear.name.clone(), request.hook, e))?,
+			None => call_process(&gear.command, &gear.args, &self.dir, input)
+				.map_err(|e| GearError::Process(gear.name.clone(), request.hook, e))?,
+		};
+		if output.trim().is_empty() {
+			return Ok(GearResponse::default());
+		}
+		let response = serde_json::from_str::<GearResponse>(&output)
+			.map_err(|e| GearError::Protocol(gear.name.clone(), request.hook, e))?;
+		if let Some(file) = response.files.iter().find(|f| !is_contained_path(&f.path)) {
+			return Err(GearError::InvalidPath(
+				gear.name.clone(),
+				request.hook,
+				file.path.clone(),
+			));
+		}
+		if let Some(page) = response
+			.pages
+			.iter()
+			.flatten()
+			.find(|p| !is_contained_path(&p.path))
+		{
+			return Err(GearError::InvalidPath(
+				gear.name.clone(),
+				request.hook,
+				page.path.clone(),
+			));
+		}
+		Ok(response)
+	}
+
+	fn request<'a>(&self, gear: &'a Gear, hook: Hook) -> GearRequest<'a> {
+		GearRequest {
+			hook,
+			gear: &gear.name,
+			options: &gear.options,
+			ingots: None,
+			values: None,
+			pages: None,
+			site_dir: None,
+		}
+	}
+
+	/// Calls the gears hooked at `after_ingot_load`. The returned ingots replace the loaded ones with the same ID.
+	/// Extra files are appended to `files`.
+	pub fn after_ingot_load(
+		&self,
+		ingots: &mut BTreeMap<usize, (PathBuf, Ingot)>,
+		files: &mut Vec<RenderedPage>,
+	) -> Vec<GearError> {
+		let hook = Hook::AfterIngotLoad;
+		let mut errors = Vec::new();
+		for gear in self.hooked(hook) {
+			let request = GearRequest {
+				ingots: Some(ingots.values().map(|(_, i)| GearIngot::from(i)).collect()),
+				..self.request(gear, hook)
+			};
+			match self.call(gear, &request) {
+				Ok(response) => {
+					for gi in response.ingots.into_iter().flatten() {
+						let id = gi.id;
+						let result = match ingots.get_mut(&id) {
+							Some((_, ingot)) => gi.apply(ingot),
+							None => Err("id"),
+						};
+						if let Err(field) = result {
+							errors.push(GearError::InvalidIngot(gear.name.clone(), hook, id, field));
+						}
+					}
+					files.extend(response.files);
+				}
+				Err(e) => errors.push(e),
+			}
+		}
+		errors
+	}
+
+	/// Calls the gears hooked at `before_render`. The returned values are merged into the template values.
+	/// Extra files are appended to `files`.
+	pub fn before_render(
+		&self,
+		values: &mut ValueMap,
+		ingots: &BTreeMap<usize, (PathBuf, Ingot)>,
+		files: &mut Vec<RenderedPage>,
+	) -> Vec<GearError> {
+		let hook = Hook::BeforeRender;
+		let mut errors = Vec::new();
+		for gear in self.hooked(hook) {
+			let request = GearRequest {
+				ingots: Some(ingots.values().map(|(_, i)| GearIngot::from(i)).collect()),
+				values: Some(values),
+				..self.request(gear, hook)
+			};
+			match self.call(gear, &request) {
+				Ok(response) => {
+					if let Some(v) = response.values {
+						merge_values(values, v);
+					}
+					files.extend(response.files);
+				}
+				Err(e) => errors.push(e),
+			}
+		}
+		errors
+	}
+
+	/// Calls the gears hooked at `after_render`. The returned pages replace all the rendered pages.
+	/// Extra files are appended to `files`.
+	pub fn after_render(
+		&self,
+		pages: &mut Vec<RenderedPage>,
+		files: &mut Vec<RenderedPage>,
+	) -> Vec<GearError> {
+		let hook = Hook::AfterRender;
+		let mut errors = Vec::new();
+		for gear in self.hooked(hook) {
+			let request = GearRequest {
+				pages: Some(pages),
+				..self.request(gear, hook)
+			};
+			match self.call(gear, &request) {
+				Ok(response) => {
+					if let Some(p) = response.pages {
+						*pages = p;
+					}
+					files.extend(response.files);
+				}
+				Err(e) => errors.push(e),
+			}
+		}
+		errors
+	}
+
+	/// Calls the gears hooked at `after_build` with the written pages and files.
+	/// Extra files are appended to `files`.
+	pub fn after_build(
+		&self,
+		site_dir: &Path,
+		pages: &[RenderedPage],
+		files: &mut Vec<RenderedPage>,
+	) -> Vec<GearError> {
+		let hook = Hook::AfterBuild;
+		let mut errors = Vec::new();
+		for gear in self.hooked(hook) {
+			let request = GearRequest {
+				pages: Some(pages),
+				site_dir: Some(site_dir),
+				..self.request(gear, hook)
+			};
+			match self.call(gear, &request) {
+				Ok(response) => files.extend(response.files),
+				Err(e) => errors.push(e),
+			}
+		}
+		errors
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::testing::TempDir;
+	use crate::app::value::RecipeValue;
+
+	fn gear(script: &str, hooks: Vec<Hook>) -> Gear {
+		Gear {
+			name: "test".to_string(),
+			command: "sh".to_string(),
+			args: vec!["-c".to_string(), script.to_string()],
+			wasm: PathBuf::new(),
+			grants: Grants::default(),
+			fuel: DEFAULT_FUEL,
+			filters: Vec::new(),
+			hooks,
+			enabled: true,
+			options: ValueMap::new(),
+		}
+	}
+
+	#[test]
+	fn test_gear_hooks() {
+		let dir = TempDir::new("gears");
+		let mut gears = Gears {
+			dir: dir.to_path_buf(),
+			wasm: BTreeMap::new(),
+			processes: true,
+			gears: vec![
+				gear(
+					r#"cat > /dev/null; echo '{"values": {"x": 1}, "files": [{"path": "a.txt", "body": "a"}]}'"#,
+					vec![Hook::BeforeRender],
+				),
+				gear("cat > /dev/null; exit 3", vec![Hook::AfterRender]),
+				gear(
+					r#"cat > /dev/null; echo '{"files": [{"path": "../a.txt", "body": "a"}]}'"#,
+					vec![Hook::AfterBuild],
+				),
+			],
+		};
+
+		let mut values = ValueMap::new();
+		let mut files = Vec::new();
+		let errors = gears.before_render(&mut values, &BTreeMap::new(), &mut files);
+		assert!(errors.is_empty());
+		assert_eq!(values.get("x"), Some(&RecipeValue::Int(1)));
+		assert_eq!(files.len(), 1);
+
+		let errors = gears.after_render(&mut Vec::new(), &mut files);
+		assert!(matches!(
+			errors.as_slice(),
+			[GearError::Process(_, _, ProcessError::Exit(Some(3)))]
+		));
+
+		let errors = gears.after_build(&dir, &[], &mut files);
+		assert!(matches!(errors.as_slice(), [GearError::InvalidPath(..)]));
+		assert_eq!(files.len(), 1);
+
+		// 外部コマンドのgearは呼ばない
+		gears.skip_processes();
+		let mut values = ValueMap::new();
+		let errors = gears.before_render(&mut values, &BTreeMap::new(), &mut files);
+		assert!(errors.is_empty());
+		assert!(values.is_empty());
+		assert!(gears.after_render(&mut Vec::new(), &mut files).is_empty());
+		assert_eq!(files.len(), 1);
+	}
+}
@@ -0,0 +1,64 @@
+use std::{
+	fmt::Display,
+	io::{Error as IOError, Write},
+	path::Path,
+	process::{Command, Stdio},
+	thread,
+};
+
+use crate::msg;
+
+#[derive(Debug)]
+/// Represents an error while running an external process.
+pub enum ProcessError {
+	/// Failed to start or wait for the process.
+	IO(IOError),
+	/// The process exited with a non-zero status. `None` means it was terminated by a signal.
+	Exit(Option<i32>),
+	/// The output of the process is not valid UTF-8.
+	Utf8(std::string::FromUtf8Error),
+}
+
+impl Display for ProcessError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ProcessError::IO(e) => write!(f, "{e}"),
+			ProcessError::Exit(Some(code)) => write!(f, "{}", msg!(ProcessExited, code)),
+			ProcessError::Exit(None) => write!(f, "{}", msg!(ProcessSignaled)),
+			ProcessError::Utf8(e) => write!(f, "{}", msg!(InvalidOutput, e)),
+		}
+	}
+}
+
+/// Runs the command in the working directory, writes the input to its stdin and returns its stdout.
+/// The stderr of the command is inherited so that gears can print logs.
+pub fn call_process(
+	command: &str,
+	args: &[String],
+	working_dir: &Path,
+	input: String,
+) -> Result<String, ProcessError> {
+	let mut child = Command::new(command)
+		.args(args)
+		.current_dir(working_dir)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::inherit())
+		.spawn()
+		.map_err(ProcessError::IO)?;
+	// 大きな入力で詰まらないよう、書き込みは別スレッドで行う
+	let writer = child.stdin.take().map(|mut stdin| {
+		thread::spawn(move || {
+			// 入力を読まずに終了するgearもあるため、書き込みの失敗は無視する
+			let _ = stdin.write_all(input.as_bytes());
+		})
+	});
+	let output = child.wait_with_output().map_err(ProcessError::IO)?;
+	if let Some(writer) = writer {
+		let _ = writer.join();
+	}
+	if !output.status.success() {
+		return Err(ProcessError::Exit(output.status.code()));
+	}
+	String::from_utf8(output.stdout).map_err(ProcessError::Utf8)
+}
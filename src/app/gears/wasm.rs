@@ -0,0 +1,459 @@
+use std::{
+	fmt::Display,
+	fs,
+	io::Error as IOError,
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+
+use crate::msg;
+use wasmi::{
+	AsContext, AsContextMut, Caller, Config, Engine, Extern, Instance, Linker, Memory, Module,
+	Store, StoreLimits, StoreLimitsBuilder, TypedFunc,
+};
+
+/// The default fuel consumed by a call before it is aborted.
+pub const DEFAULT_FUEL: u64 = 1_000_000_000;
+
+/// The maximum size of the linear memory of a module in bytes.
+pub const MEMORY_LIMIT: usize = 64 << 20;
+
+/// The maximum number of the elements of a table of a module.
+pub const TABLE_ELEMENTS_LIMIT: u32 = 10_000;
+
+#[derive(Debug)]
+/// Represents an error of a WASM gear.
+pub enum WasmError {
+	/// Failed to read the module file.
+	Read(PathBuf, IOError),
+	/// Failed to compile or instantiate the module.
+	Load(wasmi::Error),
+	/// The module does not export the required item.
+	MissingExport(&'static str),
+	/// The call trapped or ran out of fuel.
+	Trap(wasmi::Error),
+	/// The module returned a range out of its memory.
+	OutOfBounds,
+	/// The output of the module is not valid UTF-8.
+	Utf8(std::string::FromUtf8Error),
+}
+
+impl Display for WasmError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			WasmError::Read(path, e) => write!(f, "{}: {e}", path.display()),
+			WasmError::Load(e) => write!(f, "{}", msg!(WasmLoadFailed, e)),
+			WasmError::MissingExport(name) => write!(f, "{}", msg!(WasmExportNotFound, name)),
+			WasmError::Trap(e) => write!(f, "{e}"),
+			WasmError::OutOfBounds => write!(f, "{}", msg!(WasmOutOfBounds)),
+			WasmError::Utf8(e) => write!(f, "{}", msg!(InvalidOutput, e)),
+		}
+	}
+}
+
+struct HostState {
+	name: String,
+	base_dir: PathBuf,
+	read_dirs: Vec<PathBuf>,
+	limits: StoreLimits,
+}
+
+struct WasmInstance {
+	store: Store<HostState>,
+	memory: Memory,
+	alloc: TypedFunc<i32, i32>,
+	call: TypedFunc<(i32, i32), i64>,
+	filter: Option<TypedFunc<(i32, i32), i64>>,
+}
+
+/// Represents a loaded WASM gear. Calls are serialized since an instance has a single memory.
+///
+/// The module exports:
+///
+/// - `memory`: the linear memory.
+/// - `nibi_alloc(len: i32) -> i32`: allocates `len` bytes and returns the pointer.
+/// - `nibi_call(ptr: i32, len: i32) -> i64`: handles the JSON request at `ptr` and returns the JSON response
+///   packed as `(ptr << 32) | len`. `0` means an empty response.
+/// - `nibi_filter(ptr: i32, len: i32) -> i64`: optional. Handles a template filter call in the same manner.
+///   The request is `{"filter": name, "value": value, "args": [args]}` and the response is the JSON value.
+///
+/// The module can import the following host functions from the `nibi` module:
+///
+/// - `log(ptr: i32, len: i32)`: prints the UTF-8 message to stderr.
+/// - `read_file(ptr: i32, len: i32) -> i64`: reads the file at the UTF-8 path and returns its contents
+///   allocated with `nibi_alloc`, or `-1` on failure. Linked only if read directories are granted,
+///   and only files under the granted directories can be read.
+///
+/// No other access to the filesystem, the network or the environment is given.
+/// A call is bounded by the fuel, and the memory by [`MEMORY_LIMIT`]; growing the memory over it fails.
+pub struct WasmGear {
+	instance: Mutex<WasmInstance>,
+	fuel: u64,
+}
+
+fn read_memory(memory: &Memory, ctx: impl AsContext, ptr: i32, len: i32) -> Option<Vec<u8>> {
+	let mut buf = vec![0; usize::try_from(len).ok()?];
+	memory
+		.read(ctx, usize::try_from(ptr).ok()?, &mut buf)
+		.ok()?;
+	Some(buf)
+}
+
+fn caller_memory(caller: &Caller<'_, HostState>) -> Option<Memory> {
+	caller.get_export("memory").and_then(Extern::into_memory)
+}
+
+fn host_log(caller: Caller<'_, HostState>, ptr: i32, len: i32) {
+	if let Some(memory) = caller_memory(&caller)
+		&& let Some(buf) = read_memory(&memory, &caller, ptr, len)
+	{
+		eprintln!("[{}] {}", caller.data().name, String::from_utf8_lossy(&buf));
+	}
+}
+
+fn host_read_file(mut caller: Caller<'_, HostState>, ptr: i32, len: i32) -> i64 {
+	let Some(memory) = caller_memory(&caller) else {
+		return -1;
+	};
+	let Some(path) = read_memory(&memory, &caller, ptr, len)
+		.and_then(|buf| String::from_utf8(buf).ok())
+		.map(|path| caller.data().base_dir.join(path))
+	else {
+		return -1;
+	};
+	// 許可されたディレクトリの外は読ませない
+	let Ok(path) = path.canonicalize() else {
+		return -1;
+	};
+	if !caller
+		.data()
+		.read_dirs
+		.iter()
+		.any(|dir| path.starts_with(dir))
+	{
+		return -1;
+	}
+	let Ok(contents) = fs::read(&path) else {
+		return -1;
+	};
+	let Some(alloc) = caller
+		.get_export("nibi_alloc")
+		.and_then(Extern::into_func)
+		.and_then(|f| f.typed::<i32, i32>(&caller).ok())
+	else {
+		return -1;
+	};
+	let Ok(len) = i32::try_from(contents.len()) else {
+		return -1;
+	};
+	let Ok(out_ptr) = alloc.call(&mut caller, len) else {
+		return -1;
+	};
+	match memory.write(&mut caller, out_ptr as usize, &contents) {
+		Ok(()) => pack(out_ptr, len),
+		Err(_) => -1,
+	}
+}
+
+fn pack(ptr: i32, len: i32) -> i64 {
+	((ptr as u32 as i64) << 32) | (len as u32 as i64)
+}
+
+fn unpack(packed: i64) -> (i32, i32) {
+	((packed >> 32) as u32 as i32, packed as u32 as i32)
+}
+
+impl WasmGear {
+	/// Loads the module and instantiates it.
+	/// `read_dirs` are the directories the module is allowed to read, relative to `base_dir`.
+	pub fn load(
+		name: &str,
+		module_path: &Path,
+		base_dir: &Path,
+		read_dirs: &[PathBuf],
+		fuel: u64,
+		with_filter: bool,
+	) -> Result<WasmGear, WasmError> {
+		let bytes =
+			fs::read(module_path).map_err(|e| WasmError::Read(module_path.to_path_buf(), e))?;
+		let mut config = Config::default();
+		config.consume_fuel(true);
+		let engine = Engine::new(&config);
+		let module = Module::new(&engine, &bytes[..]).map_err(WasmError::Load)?;
+
+		let read_dirs = read_dirs
+			.iter()
+			.map(|dir| {
+				let dir = base_dir.join(dir);
+				dir.canonicalize().map_err(|e| WasmError::Read(dir, e))
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		let grants_read = !read_dirs.is_empty();
+		let mut store = Store::new(
+			&engine,
+			HostState {
+				name: name.to_string(),
+				base_dir: base_dir.to_path_buf(),
+				read_dirs,
+				limits: StoreLimitsBuilder::new()
+					.memory_size(MEMORY_LIMIT)
+					.table_elements(TABLE_ELEMENTS_LIMIT)
+					.instances(1)
+					.memories(1)
+					.tables(1)
+					.build(),
+			},
+		);
+		store.limiter(|state| &mut state.limits);
+		let mut linker = Linker::<HostState>::new(&engine);
+		linker
+			.func_wrap("nibi", "log", host_log)
+			.map_err(|e| WasmError::Load(e.into()))?;
+		if grants_read {
+			linker
+				.func_wrap("nibi", "read_file", host_read_file)
+				.map_err(|e| WasmError::Load(e.into()))?;
+		}
+		// 開始関数も燃料を消費する
+		store
+			.set_fuel(fuel)
+			.map_err(|e| WasmError::Load(e.into()))?;
+		let instance: Instance = linker
+			.instantiate(&mut store, &module)
+			.and_then(|pre| pre.start(&mut store))
+			.map_err(WasmError::Load)?;
+
+		let memory = instance
+			.get_memory(&store, "memory")
+			.ok_or(WasmError::MissingExport("memory"))?;
+		let alloc = instance
+			.get_typed_func::<i32, i32>(&store, "nibi_alloc")
+			.map_err(|_| WasmError::MissingExport("nibi_alloc"))?;
+		let call = instance
+			.get_typed_func::<(i32, i32), i64>(&store, "nibi_call")
+			.map_err(|_| WasmError::MissingExport("nibi_call"))?;
+		let filter = if with_filter {
+			Some(
+				instance
+					.get_typed_func::<(i32, i32), i64>(&store, "nibi_filter")
+					.map_err(|_| WasmError::MissingExport("nibi_filter"))?,
+			)
+		} else {
+			None
+		};
+		Ok(WasmGear {
+			instance: Mutex::new(WasmInstance {
+				store,
+				memory,
+				alloc,
+				call,
+				filter,
+			}),
+			fuel,
+		})
+	}
+
+	fn invoke(&self, input: &str, filter: bool) -> Result<String, WasmError> {
+		// 途中でパニックしても状態は呼び出しごとに書き直すので、毒化は無視する
+		let mut guard = self
+			.instance
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner());
+		let WasmInstance {
+			store,
+			memory,
+			alloc,
+			call,
+			filter: filter_func,
+		} = &mut *guard;
+		let func = if filter {
+			filter_func.ok_or(WasmError::MissingExport("nibi_filter"))?
+		} else {
+			*call
+		};
+		store
+			.set_fuel(self.fuel)
+			.map_err(|e| WasmError::Trap(e.into()))?;
+		let len = i32::try_from(input.len()).map_err(|_| WasmError::OutOfBounds)?;
+		let ptr = alloc
+			.call(store.as_context_mut(), len)
+			.map_err(WasmError::Trap)?;
+		memory
+			.write(store.as_context_mut(), ptr as usize, input.as_bytes())
+			.map_err(|_| WasmError::OutOfBounds)?;
+		let packed = func
+			.call(store.as_context_mut(), (ptr, len))
+			.map_err(WasmError::Trap)?;
+		if packed == 0 {
+			return Ok(String::new());
+		}
+		let (out_ptr, out_len) = unpack(packed);
+		let buf =
+			read_memory(memory, store.as_context(), out_ptr, out_len).ok_or(WasmError::OutOfBounds)?;
+		String::from_utf8(buf).map_err(WasmError::Utf8)
+	}
+
+	/// Calls `nibi_call` with the JSON request and returns the JSON response.
+	pub fn call(&self, input: &str) -> Result<String, WasmError> {
+		self.invoke(input, false)
+	}
+
+	/// Calls `nibi_filter` with the JSON request and returns the JSON value.
+	pub fn filter(&self, input: &str) -> Result<String, WasmError> {
+		self.invoke(input, true)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::testing::TempDir;
+
+	const MODULE: &str = r#"
+		(module
+			(import "nibi" "log" (func $log (param i32 i32)))
+			(memory (export "memory") 1)
+			(global $bump (mut i32) (i32.const 1024))
+			(data (i32.const 0) "{\"values\":{\"w\":true}}")
+			(data (i32.const 32) "\"X\"")
+			(func (export "nibi_alloc") (param $len i32) (result i32)
+				(local $ptr i32)
+				(local.set $ptr (global.get $bump))
+				(global.set $bump (i32.add (global.get $bump) (local.get $len)))
+				(local.get $ptr))
+			(func (export "nibi_call") (param i32 i32) (result i64)
+				(call $log (i32.const 0) (i32.const 1))
+				(i64.const 21))
+			(func (export "nibi_filter") (param i32 i32) (result i64)
+				(i64.const 0x2000000003)))
+	"#;
+
+	const LOOP_MODULE: &str = r#"
+		(module
+			(memory (export "memory") 1)
+			(func (export "nibi_alloc") (param i32) (result i32) (i32.const 0))
+			(func (export "nibi_call") (param i32 i32) (result i64)
+				(loop $l (br $l))
+				(i64.const 0)))
+	"#;
+
+	const READ_MODULE: &str = r#"
+		(module
+			(import "nibi" "read_file" (func (param i32 i32) (result i64)))
+			(memory (export "memory") 1)
+			(func (export "nibi_alloc") (param i32) (result i32) (i32.const 0))
+			(func (export "nibi_call") (param i32 i32) (result i64) (i64.const 0)))
+	"#;
+
+	const ECHO_FILE_MODULE: &str = r#"
+		(module
+			(import "nibi" "read_file" (func $read (param i32 i32) (result i64)))
+			(memory (export "memory") 1)
+			(func (export "nibi_alloc") (param i32) (result i32) (i32.const 0))
+			(func (export "nibi_call") (param $ptr i32) (param $len i32) (result i64)
+				(local $r i64)
+				(local.set $r (call $read (local.get $ptr) (local.get $len)))
+				(if (result i64) (i64.eq (local.get $r) (i64.const -1))
+					(then (i64.const 0))
+					(else (local.get $r)))))
+	"#;
+
+	const LARGE_MEMORY_MODULE: &str = r#"
+		(module
+			(memory (export "memory") 1025)
+			(func (export "nibi_alloc") (param i32) (result i32) (i32.const 0))
+			(func (export "nibi_call") (param i32 i32) (result i64) (i64.const 0)))
+	"#;
+
+	#[test]
+	fn test_wasm_gear() {
+		let dir = TempDir::new("wasm");
+		let write = |name: &str, wat_src: &str| {
+			let path = dir.join(name);
+			fs::write(&path, wat::parse_str(wat_src).unwrap()).unwrap();
+			path
+		};
+
+		let gear =
+			WasmGear::load("t", &write("t.wasm", MODULE), &dir, &[], DEFAULT_FUEL, true).unwrap();
+		assert_eq!(gear.call("{}").unwrap(), r#"{"values":{"w":true}}"#);
+		assert_eq!(gear.filter("{}").unwrap(), r#""X""#);
+
+		let looping =
+			WasmGear::load("l", &write("l.wasm", LOOP_MODULE), &dir, &[], 10_000, false).unwrap();
+		assert!(matches!(looping.call("{}"), Err(WasmError::Trap(_))));
+		assert!(matches!(
+			looping.filter("{}"),
+			Err(WasmError::MissingExport(_))
+		));
+
+		// 読み込みが許可されていないgearはread_fileをimportできない
+		let read_path = write("r.wasm", READ_MODULE);
+		assert!(matches!(
+			WasmGear::load("r", &read_path, &dir, &[], DEFAULT_FUEL, false),
+			Err(WasmError::Load(_))
+		));
+		assert!(
+			WasmGear::load(
+				"r",
+				&read_path,
+				&dir,
+				&[PathBuf::from(".")],
+				DEFAULT_FUEL,
+				false
+			)
+			.is_ok()
+		);
+	}
+
+	#[test]
+	fn test_wasm_gear_limits() {
+		let dir = TempDir::new("wasm_limits");
+		let write = |name: &str, wat_src: &str| {
+			let path = dir.join(name);
+			fs::write(&path, wat::parse_str(wat_src).unwrap()).unwrap();
+			path
+		};
+		fs::create_dir_all(dir.join("granted")).unwrap();
+		fs::write(dir.join("granted/a.txt"), "ok").unwrap();
+		fs::write(dir.join("secret.txt"), "secret").unwrap();
+
+		let gear = WasmGear::load(
+			"e",
+			&write("e.wasm", ECHO_FILE_MODULE),
+			&dir,
+			&[PathBuf::from("granted")],
+			DEFAULT_FUEL,
+			false,
+		)
+		.unwrap();
+		assert_eq!(gear.call("granted/a.txt").unwrap(), "ok");
+		// 許可されたディレクトリの外は読めない
+		for path in [
+			"secret.txt",
+			"granted/../secret.txt",
+			dir.join("secret.txt").to_str().unwrap(),
+			"granted/missing.txt",
+		] {
+			assert_eq!(gear.call(path).unwrap(), "", "{path}");
+		}
+		#[cfg(unix)]
+		{
+			std::os::unix::fs::symlink(dir.join("secret.txt"), dir.join("granted/link.txt")).unwrap();
+			assert_eq!(gear.call("granted/link.txt").unwrap(), "");
+		}
+
+		// 上限を超えるメモリは確保できない
+		assert!(matches!(
+			WasmGear::load(
+				"m",
+				&write("m.wasm", LARGE_MEMORY_MODULE),
+				&dir,
+				&[],
+				DEFAULT_FUEL,
+				false
+			),
+			Err(WasmError::Load(_))
+		));
+	}
+}
@@ -0,0 +1,153 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use serde::Serialize;
+
+use super::{
+	build::render::url_of,
+	ingot::{Ingot, ingot::Status},
+};
+
+/// catalog module
+pub mod catalog;
+
+/// Returns `true` if the language code only has ASCII letters, digits and hyphens (e.g. `ja`, `en-US`).
+/// The code is used as a path segment of the permalinks, so the other characters are not allowed.
+/// An empty code means no language.
+pub fn is_lang_tag(lang: &str) -> bool {
+	lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Returns the key of the optional attribute for the given attribute in the given language (e.g. `name.en`).
+pub fn localized_attr_key(attr: &str, lang: &str) -> String {
+	format!("{attr}.{lang}")
+}
+
+/// Returns the value of the given attribute in the given language from the optional attributes, if exists.
+pub fn localized_attr<'a>(
+	opt_attrs: &'a Option<BTreeMap<String, String>>,
+	attr: &str,
+	lang: &str,
+) -> Option<&'a str> {
+	if lang.is_empty() {
+		return None;
+	}
+	opt_attrs
+		.as_ref()?
+		.get(&localized_attr_key(attr, lang))
+		.map(|s| s.as_str())
+		.filter(|s| !s.trim().is_empty())
+}
+
+/// Returns the published ingots grouped by their translation keys. Each group is sorted by the language.
+pub fn translation_index(
+	ingots: &BTreeMap<usize, (PathBuf, Ingot)>,
+) -> BTreeMap<&str, Vec<&Ingot>> {
+	let mut index: BTreeMap<&str, Vec<&Ingot>> = BTreeMap::new();
+	for (_, ingot) in ingots.values() {
+		let key = ingot.translation_key.trim();
+		if !key.is_empty() && matches!(ingot.status, Status::Publish) {
+			index.entry(key).or_default().push(ingot);
+		}
+	}
+	for group in index.values_mut() {
+		group.sort_by(|a, b| a.lang.cmp(&b.lang).then(a.id.cmp(&b.id)));
+	}
+	index
+}
+
+#[derive(Debug, Serialize)]
+/// Represents a variant of an ingot in another language.
+pub struct Translation<'a> {
+	/// The ingot ID.
+	pub id: usize,
+	/// The language code.
+	pub lang: &'a str,
+	/// The title.
+	pub title: &'a str,
+	/// The URL of the page.
+	pub url: String,
+}
+
+/// Returns the variants of the ingot in the other languages, linked by its translation key.
+pub fn translations_of<'a>(
+	index: &BTreeMap<&str, Vec<&'a Ingot>>,
+	ingot: &Ingot,
+) -> Vec<Translation<'a>> {
+	index
+		.get(ingot.translation_key.trim())
+		.map(|group| {
+			group
+				.iter()
+				.filter(|other| other.id != ingot.id)
+				.map(|other| Translation {
+					id: other.id,
+					lang: &other.lang,
+					title: &other.title,
+					url: url_of(other),
+				})
+				.collect()
+		})
+		.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::{config::I18nConf, tag::Tag};
+
+	fn ingot(id: usize, lang: &str, key: &str, status: Status) -> (usize, (PathBuf, Ingot)) {
+		let mut ingot = Ingot {
+			id,
+			pname: "about".to_string(),
+			title: format!("title-{lang}"),
+			lang: lang.to_string(),
+			translation_key: key.to_string(),
+			status,
+			..Default::default()
+		};
+		let conf = I18nConf::new("ja", BTreeMap::new());
+		ingot.fill_lang(&conf);
+		(id, (PathBuf::new(), ingot))
+	}
+
+	#[test]
+	fn test_translations_of() {
+		let ingots = BTreeMap::from([
+			ingot(1, "ja", "about", Status::Publish),
+			ingot(2, "en", "about", Status::Publish),
+			ingot(3, "fr", "about", Status::Draft),
+			ingot(4, "", "other", Status::Publish),
+		]);
+		let index = translation_index(&ingots);
+		let translations = translations_of(&index, &ingots[&1].1);
+		assert_eq!(translations.len(), 1);
+		assert_eq!(translations[0].lang, "en");
+		assert_eq!(translations[0].url, "/en/about/");
+		let translations = translations_of(&index, &ingots[&2].1);
+		assert_eq!(translations[0].url, "/about/");
+		assert_eq!(ingots[&4].1.lang, "ja");
+		assert!(translations_of(&index, &ingots[&4].1).is_empty());
+	}
+
+	#[test]
+	fn test_is_lang_tag() {
+		assert!(is_lang_tag("ja"));
+		assert!(is_lang_tag("en-US"));
+		assert!(is_lang_tag(""));
+		assert!(!is_lang_tag("../evil"));
+		assert!(!is_lang_tag("en/us"));
+		assert!(!is_lang_tag("日本語"));
+	}
+
+	#[test]
+	fn test_localized_attr() {
+		let mut tag = Tag::new(1, "diary".into(), "日記".into(), String::new());
+		tag.opt_attr = Some(BTreeMap::from([(
+			localized_attr_key("name", "en"),
+			"Diary".to_string(),
+		)]));
+		assert_eq!(tag.name_in("en"), "Diary");
+		assert_eq!(tag.name_in("ja"), "日記");
+		assert_eq!(tag.name_in(""), "日記");
+	}
+}
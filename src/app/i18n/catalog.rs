@@ -0,0 +1,1173 @@
+use std::{fmt::Display, sync::RwLock};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The language of the CLI messages.
+pub enum CliLang {
+	#[default]
+	/// English
+	En,
+	/// Japanese
+	Ja,
+}
+
+impl CliLang {
+	/// Returns the language of the given language code or locale (e.g. `ja`, `ja_JP.UTF-8`, `en-US`, `C`).
+	/// Returns `None` if the language is not supported.
+	pub fn from_locale(locale: &str) -> Option<Self> {
+		let lang = locale
+			.trim()
+			.split(['_', '-', '.', '@'])
+			.next()
+			.unwrap_or_default()
+			.to_lowercase();
+		match lang.as_str() {
+			"ja" => Some(CliLang::Ja),
+			"en" | "c" | "posix" => Some(CliLang::En),
+			_ => None,
+		}
+	}
+}
+
+// 優先順位は --lang フラグ > config > 環境変数
+static FLAG_LANG: RwLock<Option<CliLang>> = RwLock::new(None);
+static CONFIG_LANG: RwLock<Option<CliLang>> = RwLock::new(None);
+
+/// Sets the language given by the `--lang` flag.
+pub fn set_flag_lang(lang: Option<CliLang>) {
+	if let Ok(mut l) = FLAG_LANG.write() {
+		*l = lang;
+	}
+}
+
+/// Sets the language given by the `cli_lang` setting in the config.
+pub fn set_config_lang(lang: Option<CliLang>) {
+	if let Ok(mut l) = CONFIG_LANG.write() {
+		*l = lang;
+	}
+}
+
+#[cfg(not(test))]
+fn env_lang() -> Option<CliLang> {
+	["LC_ALL", "LC_MESSAGES", "LANG"]
+		.iter()
+		.filter_map(|key| std::env::var(key).ok())
+		.find(|v| !v.trim().is_empty())
+		.and_then(|v| CliLang::from_locale(&v))
+}
+
+#[cfg(test)]
+// テストの結果が実行環境のロケールに左右されないよう、環境変数は見ない
+fn env_lang() -> Option<CliLang> {
+	None
+}
+
+/// Returns the language of the CLI messages.
+/// The `--lang` flag wins, then the `cli_lang` setting in the config, then `LC_ALL`, `LC_MESSAGES` and `LANG`.
+/// Falls back to English.
+pub fn current_lang() -> CliLang {
+	let flag = FLAG_LANG.read().ok().and_then(|l| *l);
+	let config = || CONFIG_LANG.read().ok().and_then(|l| *l);
+	flag.or_else(config).or_else(env_lang).unwrap_or_default()
+}
+
+/// Returns the value of the `--lang` (or `--language`) flag in the command line arguments, if exists.
+/// The arguments are scanned before parsing so that the command descriptions in help are also localized.
+pub fn lang_arg<I: IntoIterator<Item = String>>(args: I) -> Option<String> {
+	let mut args = args.into_iter();
+	while let Some(arg) = args.next() {
+		if arg == "--" {
+			break;
+		}
+		if let Some(value) = arg
+			.strip_prefix("--lang=")
+			.or_else(|| arg.strip_prefix("--language="))
+		{
+			return Some(value.to_string());
+		}
+		if arg == "--lang" || arg == "--language" {
+			return args.next();
+		}
+	}
+	None
+}
+
+/// Replaces the positional placeholders (`{0}`, `{1}`, ...) in the template with the arguments.
+/// Placeholders without an argument are left as they are.
+pub fn format_template(template: &str, args: &[&dyn Display]) -> String {
+	let mut out = String::with_capacity(template.len());
+	let mut rest = template;
+	while let Some(start) = rest.find('{') {
+		out.push_str(&rest[..start]);
+		let after = &rest[start + 1..];
+		let end = after.find('}');
+		match end
+			.and_then(|end| after[..end].parse::<usize>().ok())
+			.and_then(|i| args.get(i))
+		{
+			Some(arg) => {
+				out.push_str(&arg.to_string());
+				rest = &after[end.unwrap_or_default() + 1..];
+			}
+			None => {
+				out.push('{');
+				rest = after;
+			}
+		}
+	}
+	out.push_str(rest);
+	out
+}
+
+macro_rules! catalog {
+	($($name:ident { en: $en:literal, ja: $ja:literal $(,)? })*) => {
+		#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+		/// The messages of the CLI. Each variant is documented with its English template.
+		// テンプレートをそのまま説明に使うため、doc_markdownは適用しない
+		#[allow(clippy::doc_markdown)]
+		pub enum Msg {
+			$(
+				#[doc = $en]
+				$name,
+			)*
+		}
+
+		impl Msg {
+			/// All messages.
+			pub const ALL: &[Msg] = &[$(Msg::$name),*];
+
+			/// Returns the template of the message in the given language.
+			pub fn template(self, lang: CliLang) -> &'static str {
+				match (self, lang) {
+					$(
+						(Msg::$name, CliLang::En) => $en,
+						(Msg::$name, CliLang::Ja) => $ja,
+					)*
+				}
+			}
+		}
+	};
+}
+
+impl Msg {
+	/// Returns the message in the current language, with the placeholders replaced by the arguments.
+	pub fn format(self, args: &[&dyn Display]) -> String {
+		format_template(self.template(current_lang()), args)
+	}
+}
+
+impl Display for Msg {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.template(current_lang()))
+	}
+}
+
+#[macro_export]
+/// Returns the message of the catalog in the current language, e.g. `msg!(ConfigNotFound)` or `msg!(NotDirectory, path.display())`.
+macro_rules! msg {
+	($name:ident) => {
+		$crate::app::i18n::catalog::Msg::$name.format(&[])
+	};
+	($name:ident, $($arg:expr),+ $(,)?) => {
+		$crate::app::i18n::catalog::Msg::$name.format(&[$(&$arg as &dyn std::fmt::Display),+])
+	};
+}
+
+catalog! {
+	// 共通
+	LangFlag {
+		en: "language of the messages (en/ja)",
+		ja: "メッセージの言語 (en/ja)",
+	}
+	UnknownLang {
+		en: "unknown language: {0} (en/ja is supported)",
+		ja: "未対応の言語です: {0} (en/jaに対応しています)",
+	}
+	NoSubcommand {
+		en: "no subcommand is specified, showing help",
+		ja: "サブコマンドの指定がないため、ヘルプを表示します",
+	}
+	SpecifySubcommand {
+		en: "specify subcommand.",
+		ja: "サブコマンドを指定してください。",
+	}
+	AvailableSubcommands {
+		en: "now available subcommand:",
+		ja: "指定できるサブコマンド:",
+	}
+	OverwriteConfirm {
+		en: "overwrite?",
+		ja: "上書きしますか？",
+	}
+	ProjectDirFlag {
+		en: "specify project(,or target) directory",
+		ja: "プロジェクト(または対象)のディレクトリを指定",
+	}
+	FileTypeFlag {
+		en: "file type of the created file (ron/toml/json/xml/hcl)",
+		ja: "作成するファイルの形式 (ron/toml/json/xml/hcl)",
+	}
+	ConfigNotFound {
+		en: "config not found, please run `nibi init`",
+		ja: "configが見つかりません。`nibi init`を実行してください",
+	}
+	ConfigPath {
+		en: "config: {0}",
+		ja: "config: {0}",
+	}
+	ProjectDir {
+		en: "project directory: {0}",
+		ja: "プロジェクトディレクトリ: {0}",
+	}
+	NotDirectory {
+		en: "{0} is not directory or does not exist",
+		ja: "{0}はディレクトリではないか、存在しません",
+	}
+	UnknownFileType {
+		en: "unknown file type",
+		ja: "未対応のファイル形式です",
+	}
+	WriteFailed {
+		en: "failed to write {0}: {1}",
+		ja: "{0}の書き込みに失敗しました: {1}",
+	}
+	FileNotFound {
+		en: "{0} not found",
+		ja: "{0}が見つかりません",
+	}
+	ReadFailed {
+		en: "failed to read {0}",
+		ja: "{0}の読み込みに失敗しました",
+	}
+	DidYouMean {
+		en: " (did you mean: {0}?)",
+		ja: " (もしかして: {0})",
+	}
+	// init
+	InitCmd {
+		en: "init nibi project",
+		ja: "nibiプロジェクトを初期化",
+	}
+	InitUsage {
+		en: "nibi init [directory path: default is current]",
+		ja: "nibi init [ディレクトリのパス: 省略時はカレントディレクトリ]",
+	}
+	ImportIngotsDirFlag {
+		en: "path of the directory of the ingots to import",
+		ja: "取り込みたいingotsを収納したディレクトリのパス",
+	}
+	SkipCreatePromptFlag {
+		en: "skip prompt for project information",
+		ja: "初期化時のプロンプトをスキップ",
+	}
+	InitForceFlag {
+		en: "force init dir even if files exist",
+		ja: "ファイルが存在しても強制的に初期化",
+	}
+	ProjectName {
+		en: "project name",
+		ja: "プロジェクト名",
+	}
+	SiteName {
+		en: "site name",
+		ja: "サイト名",
+	}
+	ConfigFileType {
+		en: "file type of config",
+		ja: "コンフィグファイルの形式",
+	}
+	VcsFlag {
+		en: "put the project under management of vcs (git) after initialization",
+		ja: "初期化後vcs(git)の初期化処理を実行してvcsの管理下に置く",
+	}
+	InitEarlyExit {
+		en: "aborted the initialization, exiting the program.",
+		ja: "初期化処理を中断し、プログラムを終了します。",
+	}
+	CreatingProjectDir {
+		en: "creating project directory...",
+		ja: "プロジェクトフォルダを作成中...",
+	}
+	CreatingConfigFile {
+		en: "creating config file...",
+		ja: "コンフィグファイルを作成中...",
+	}
+	CreatingSrcDirs {
+		en: "creating source directories...",
+		ja: "サブフォルダ群を作成中...",
+	}
+	ImportIngotsFrom {
+		en: "import ingots from {0}",
+		ja: "{0}から生地を取り込みます",
+	}
+	ImportIngotsFailed {
+		en: "failed to import ingots: {0}",
+		ja: "生地の取り込みに失敗しました: {0}",
+	}
+	ProjectDirCreated {
+		en: "created project directory: {0}",
+		ja: "プロジェクトフォルダ: {0}を作成しました。",
+	}
+	EmptyProjectDirExists {
+		en: "empty project directory {0} already exists",
+		ja: "空のプロジェクトフォルダ{0}が既に存在します",
+	}
+	NotEmptyDirConfirm {
+		en: "the specified directory is not empty\ninitialize with the directory anyway?",
+		ja: "指定されたディレクトリは空ではありません\nこのまま指定されたディレクトリを使用して初期化しますか？",
+	}
+	InitWithExistingDir {
+		en: "initialize with the existing project directory {0}",
+		ja: "既存のプロジェクトフォルダ{0}を使用して初期化します",
+	}
+	ProjectDirExists {
+		en: "aborted the initialization because {0} already exists.",
+		ja: "{0}が既に存在するため、初期化プロセスを中断しました。",
+	}
+	PathError {
+		en: "an error occurred with the specified path: {0}",
+		ja: "指定されたパスでエラーが発生しました: {0}",
+	}
+	InputProjectInfo {
+		en: "input your project information for initialization.",
+		ja: "初期化のためのプロジェクト情報を入力してください。",
+	}
+	IngotsDirToImport {
+		en: "ingots directory to import (empty to skip)",
+		ja: "取り込む生地のディレクトリ (空欄でスキップ)",
+	}
+	GitInitConfirm {
+		en: "run git init at the end of the initialization?",
+		ja: "初期化プロセスの最後にgit init を行いますか？",
+	}
+	ConfigFileCreated {
+		en: "created config file: {0}",
+		ja: "configファイルを作成しました: {0}",
+	}
+	ConfigFileWriteFailed {
+		en: "failed to write config file",
+		ja: "configファイルの作成に失敗しました",
+	}
+	ConfigResetFailed {
+		en: "failed to reset config: {0}",
+		ja: "コンフィグの初期化処理に失敗しました: {0}",
+	}
+	ConfigFileOverwritten {
+		en: "overwrote config file",
+		ja: "コンフィグファイルを上書きしました",
+	}
+	NotOverwritten {
+		en: "not overwritten.",
+		ja: "上書きしません。",
+	}
+	ConfigFileCreateError {
+		en: "an error occurred while creating config file: {0}",
+		ja: "コンフィグファイルの作成中にエラーが発生しました: {0}",
+	}
+	DirExists {
+		en: "directory {0} already exists",
+		ja: "ディレクトリ {0} は既に存在します",
+	}
+	DirCreateError {
+		en: "an error occurred while creating directory {0}: {1}",
+		ja: "ディレクトリ {0} の作成中にエラーが発生しました: {1}",
+	}
+	SrcDirsCreated {
+		en: "created source directories",
+		ja: "ソースフォルダ群を作成しました",
+	}
+	InitVcs {
+		en: "init vcs by: {0}",
+		ja: "vcsを初期化します: {0}",
+	}
+	// build, check
+	BuildCmd {
+		en: "build nibi project",
+		ja: "nibiプロジェクトをビルド",
+	}
+	BuildUsage {
+		en: "nibi build [directory path: default is current] [--recipe name] [--profile name]",
+		ja: "nibi build [ディレクトリのパス: 省略時はカレントディレクトリ] [--recipe レシピ名] [--profile プロファイル名]",
+	}
+	RecipeFlag {
+		en: "recipe name to build with",
+		ja: "ビルドに使うレシピ名",
+	}
+	ProfileFlag {
+		en: "build profile name in config",
+		ja: "configに書かれたビルドプロファイル名",
+	}
+	JobsFlag {
+		en: "number of build threads (0: number of CPUs)",
+		ja: "ビルドに使うスレッド数(0でCPU数)",
+	}
+	CheckCmd {
+		en: "check nibi project without writing site",
+		ja: "サイトを書き出さずにnibiプロジェクトを検査",
+	}
+	CheckUsage {
+		en: "nibi check [directory path: default is current] [--recipe name] [--profile name]",
+		ja: "nibi check [ディレクトリのパス: 省略時はカレントディレクトリ] [--recipe レシピ名] [--profile プロファイル名]",
+	}
+	CheckPassed {
+		en: "check passed: no problem found",
+		ja: "検査に合格しました: 問題は見つかりませんでした",
+	}
+	CheckFailed {
+		en: "check failed: {0} problem(s) found",
+		ja: "検査に失敗しました: {0}件の問題が見つかりました",
+	}
+	// new
+	NewCmd {
+		en: "create new [site(project)/ingot(post)/igata(template)/igata_set(theme)]",
+		ja: "新規作成 [site(プロジェクト)/ingot(投稿)/igata(テンプレート)/igata_set(テーマ)]",
+	}
+	NewIgataPackCmd {
+		en: "create new igata_pack (, or theme).",
+		ja: "新しいigata_pack(テーマ)を作成",
+	}
+	NewIgataPackUsage {
+		en: "nibi new igata_pack [igata set's name]",
+		ja: "nibi new igata_pack [packの名前]",
+	}
+	NewRecipeCmd {
+		en: "create new recipe",
+		ja: "新しいレシピを作成",
+	}
+	NewRecipeUsage {
+		en: "nibi new recipe [recipe name] [--file-type ron/toml/json/xml/hcl]",
+		ja: "nibi new recipe [レシピ名] [--file-type ron/toml/json/xml/hcl]",
+	}
+	SpecifyNewTarget {
+		en: "specify new target.",
+		ja: "新しく作成するものを指定してください。",
+	}
+	AvailableTargets {
+		en: "now available target:",
+		ja: "指定できる対象:",
+	}
+	SpecifyThemeName {
+		en: "specify theme name.",
+		ja: "テーマ名を指定してください。",
+	}
+	BeginCreation {
+		en: "begin {0} creation",
+		ja: "{0}の作成を開始します",
+	}
+	FinishCreation {
+		en: "finished {0} creation",
+		ja: "{0}の作成を完了しました",
+	}
+	PackDirCreated {
+		en: "created new pack directory {0}",
+		ja: "新しいpack用ディレクトリを作成しました: {0}",
+	}
+	PackDirCreateFailed {
+		en: "failed to create new pack directory: {0}",
+		ja: "pack用ディレクトリの作成に失敗しました: {0}",
+	}
+	RecipeExists {
+		en: "recipe file already exists: {0}",
+		ja: "レシピファイルは既に存在します: {0}",
+	}
+	RecipeCreated {
+		en: "created new recipe file {0}",
+		ja: "新しいレシピファイルを作成しました: {0}",
+	}
+	RecipeCreateFailed {
+		en: "failed to create new recipe file: {0}",
+		ja: "レシピファイルの作成に失敗しました: {0}",
+	}
+	RecipeReadFailed {
+		en: "failed to read recipe file: {0}",
+		ja: "レシピファイルの読み込みに失敗しました: {0}",
+	}
+	ReadTagsFrom {
+		en: "read tags from: {0}",
+		ja: "タグを読み込みます: {0}",
+	}
+	// igata
+	IgataCmd {
+		en: "manage igata packs (, or themes)",
+		ja: "鋳型pack(テーマ)を管理",
+	}
+	IgataInfoCmd {
+		en: "show the information and documented values of igata pack",
+		ja: "鋳型packの情報と値の説明を表示",
+	}
+	IgataInfoUsage {
+		en: "nibi igata info [pack name]",
+		ja: "nibi igata info [pack名]",
+	}
+	IgataListCmd {
+		en: "list igata packs in the project",
+		ja: "プロジェクトの鋳型packを一覧表示",
+	}
+	IgataValidateCmd {
+		en: "validate templates and config of igata pack",
+		ja: "鋳型packのテンプレートと設定を検証",
+	}
+	IgataValidateUsage {
+		en: "nibi igata validate [pack name]",
+		ja: "nibi igata validate [pack名]",
+	}
+	IgataForkCmd {
+		en: "copy igata pack under new name",
+		ja: "鋳型packを新しい名前で複製",
+	}
+	IgataForkUsage {
+		en: "nibi igata fork [pack name] [new pack name]",
+		ja: "nibi igata fork [pack名] [新しいpack名]",
+	}
+	IgataInstallCmd {
+		en: "install igata pack from tar.gz, zip, directory or local git repository",
+		ja: "tar.gz、zip、ディレクトリまたはローカルのgitリポジトリから鋳型packをインストール",
+	}
+	IgataInstallUsage {
+		en: "nibi igata install [path to tar.gz/zip/directory/git repository or file:// url] [--force]",
+		ja: "nibi igata install [tar.gz/zip/ディレクトリ/gitリポジトリのパスまたはfile:// URL] [--force]",
+	}
+	InstallForceFlag {
+		en: "replace installed pack with same name",
+		ja: "同名のpackを置き換える",
+	}
+	IgataUpdateCmd {
+		en: "pull installed igata packs again from recorded sources",
+		ja: "記録されたインストール元から鋳型packを再取得",
+	}
+	IgataUpdateUsage {
+		en: "nibi igata update [pack names: default is all installed packs]",
+		ja: "nibi igata update [pack名: 省略時はインストール済みの全pack]",
+	}
+	SpecifyPackName {
+		en: "specify pack name.",
+		ja: "pack名を指定してください。",
+	}
+	NoPackFound {
+		en: "no igata pack found",
+		ja: "packが見つかりませんでした",
+	}
+	PackInDirectory {
+		en: "{0} (directory: {1})",
+		ja: "{0} (ディレクトリ: {1})",
+	}
+	PackInfoNotFound {
+		en: "{0}: invalid pack ({1})",
+		ja: "{0}: packとして読み込めません ({1})",
+	}
+	IgataDirReadFailed {
+		en: "failed to read igata directory: {0}",
+		ja: "鋳型ディレクトリの読み込みに失敗しました: {0}",
+	}
+	ValidationPassed {
+		en: "validation passed: no problem found",
+		ja: "検証に合格しました: 問題は見つかりませんでした",
+	}
+	ValidationFailed {
+		en: "validation failed: {0} problem(s) found",
+		ja: "検証に失敗しました: {0}件の問題が見つかりました",
+	}
+	SpecifyForkNames {
+		en: "specify pack name and new pack name.",
+		ja: "元のpack名と新しいpack名を指定してください。",
+	}
+	PackForked {
+		en: "forked {0} into {1}",
+		ja: "{0}を{1}に複製しました",
+	}
+	ForkFailed {
+		en: "failed to fork pack: {0}",
+		ja: "packの複製に失敗しました: {0}",
+	}
+	SpecifyPackSource {
+		en: "specify pack source.",
+		ja: "インストール元を指定してください。",
+	}
+	PackUpdated {
+		en: "updated {0}: {1} -> {2}",
+		ja: "{0}を更新しました: {1} -> {2}",
+	}
+	PackInstalled {
+		en: "installed {0} {1} into {2}",
+		ja: "{0} {1}を{2}にインストールしました",
+	}
+	InstallFailed {
+		en: "failed to install pack: {0}",
+		ja: "packのインストールに失敗しました: {0}",
+	}
+	NoInstalledPack {
+		en: "no installed pack",
+		ja: "インストールされたpackはありません",
+	}
+	UpdateFailed {
+		en: "failed to update {0}: {1}",
+		ja: "{0}の更新に失敗しました: {1}",
+	}
+	PackDescName {
+		en: "name: {0}",
+		ja: "名前: {0}",
+	}
+	PackDescVersion {
+		en: "version: {0}",
+		ja: "バージョン: {0}",
+	}
+	PackDescDescription {
+		en: "description: {0}",
+		ja: "説明: {0}",
+	}
+	PackDescLicense {
+		en: "license: {0}",
+		ja: "ライセンス: {0}",
+	}
+	PackDescNibiVersion {
+		en: "nibi version: {0}",
+		ja: "nibiのバージョン: {0}",
+	}
+	PackDescDepends {
+		en: "depends: {0}",
+		ja: "依存: {0}",
+	}
+	PackDescAuthor {
+		en: "author: {0}",
+		ja: "作者: {0}",
+	}
+	PackDescDirectory {
+		en: "directory: {0}",
+		ja: "ディレクトリ: {0}",
+	}
+	SchemaDescAllowed {
+		en: "  allowed: {0}",
+		ja: "  指定できる値: {0}",
+	}
+	PackDescValues {
+		en: "values:",
+		ja: "値:",
+	}
+	NoDocumentedValues {
+		en: "values: no documented values",
+		ja: "値: 説明のある値はありません",
+	}
+	UndocumentedValues {
+		en: "undocumented values:",
+		ja: "説明のない値:",
+	}
+	RequiredValue {
+		en: " (required)",
+		ja: " (必須)",
+	}
+	// import, export
+	ImportCmd {
+		en: "import contents from other systems into zairyo",
+		ja: "他のシステムのコンテンツをzairyoに取り込む",
+	}
+	ImportForceFlag {
+		en: "overwrite existing files",
+		ja: "既存のファイルを上書きする",
+	}
+	ImportWordpressCmd {
+		en: "import posts, pages, tags, categories and authors from WordPress export (WXR) file",
+		ja: "WordPressのエクスポート(WXR)ファイルから投稿、固定ページ、タグ、カテゴリー、著者を取り込む",
+	}
+	ImportWordpressUsage {
+		en: "nibi import wordpress [path to export.xml] [--file-type ron] [--force]",
+		ja: "nibi import wordpress [export.xmlのパス] [--file-type ron] [--force]",
+	}
+	ImportHugoCmd {
+		en: "import contents and static files from Hugo site",
+		ja: "Hugoのサイトからコンテンツと静的ファイルを取り込む",
+	}
+	ImportHugoUsage {
+		en: "nibi import hugo [path to Hugo site directory] [--file-type ron] [--force]",
+		ja: "nibi import hugo [Hugoのサイトディレクトリのパス] [--file-type ron] [--force]",
+	}
+	ImportJekyllCmd {
+		en: "import posts, pages and static files from Jekyll site",
+		ja: "Jekyllのサイトから投稿、固定ページと静的ファイルを取り込む",
+	}
+	ImportJekyllUsage {
+		en: "nibi import jekyll [path to Jekyll site directory] [--file-type ron] [--force]",
+		ja: "nibi import jekyll [Jekyllのサイトディレクトリのパス] [--file-type ron] [--force]",
+	}
+	SpecifyImportSource {
+		en: "specify import source.",
+		ja: "インポート元を指定してください。",
+	}
+	ImportFailed {
+		en: "failed to import: {0}",
+		ja: "インポートに失敗しました: {0}",
+	}
+	ImportWrote {
+		en: "wrote {0}",
+		ja: "書き出しました: {0}",
+	}
+	ImportCopiedResources {
+		en: "copied {0} resource file(s)",
+		ja: "{0}件のリソースをコピーしました",
+	}
+	ImportSkipped {
+		en: "skipped {0}",
+		ja: "スキップしました: {0}",
+	}
+	ImportRejected {
+		en: "rejected {0}: {1}",
+		ja: "除外しました: {0}: {1}",
+	}
+	ImportSummary {
+		en: "imported {0} ingot(s), rejected {1} file(s)",
+		ja: "{0}件の生地をインポートし、{1}件のファイルを除外しました",
+	}
+	ParentCategoryNotFound {
+		en: "parent category {0} of {1} not found, imported as a root",
+		ja: "{1}の親カテゴリー{0}が見つからないため、最上位として取り込みました",
+	}
+	CyclicCategory {
+		en: "category {0} has a cyclic parent, imported as a root",
+		ja: "カテゴリー{0}の親が循環しているため、最上位として取り込みました",
+	}
+	UnsupportedPostType {
+		en: "{0} ({1}): unsupported post type",
+		ja: "{0} ({1}): 未対応の投稿タイプです",
+	}
+	SkippedByStatus {
+		en: "{0} ({1}): skipped by status",
+		ja: "{0} ({1}): ステータスにより除外しました",
+	}
+	PostIdNotFound {
+		en: "{0}: post id not found",
+		ja: "{0}: 投稿IDがありません",
+	}
+	ListPage {
+		en: "{0}: list page",
+		ja: "{0}: 一覧ページは取り込みません",
+	}
+	FrontMatterNotMap {
+		en: "front matter is not a map",
+		ja: "フロントマターがマップではありません",
+	}
+	FileEmpty {
+		en: "file is empty",
+		ja: "ファイルが空です",
+	}
+	TitleNotFound {
+		en: "title not found, the title line must be followed by a blank line",
+		ja: "タイトルが見つかりません。タイトル行の後には空行が必要です",
+	}
+	IdAlreadyUsed {
+		en: "id {0} is already used by {1}",
+		ja: "ID {0}は{1}で既に使われています",
+	}
+	NotWxr {
+		en: "channel element not found, this is not a WXR file",
+		ja: "channel要素が見つかりません。WXRファイルではありません",
+	}
+	NotHugoSite {
+		en: "content directory not found, this is not a Hugo site",
+		ja: "contentディレクトリが見つかりません。Hugoのサイトではありません",
+	}
+	NotJekyllSite {
+		en: "neither _posts nor _config.yml found, this is not a Jekyll site",
+		ja: "_postsも_config.ymlも見つかりません。Jekyllのサイトではありません",
+	}
+	ImportReadFailed {
+		en: "failed to read {0}: {1}",
+		ja: "{0}の読み込みに失敗しました: {1}",
+	}
+	InvalidDocument {
+		en: "invalid document at byte {0}: {1}",
+		ja: "{0}バイト目で文書が不正です: {1}",
+	}
+	SerializeFileFailed {
+		en: "failed to serialize {0}: {1}",
+		ja: "{0}を変換できませんでした: {1}",
+	}
+	FilesExist {
+		en: "{0} file(s) already exist, use --force to overwrite",
+		ja: "{0}件のファイルが既に存在します。上書きするには--forceを指定してください",
+	}
+	ExportCmd {
+		en: "export ingots, taxonomies, authors, recipe and packs as a single file",
+		ja: "生地、分類、著者、レシピとpackを一つのファイルに書き出す",
+	}
+	ExportUsage {
+		en: "nibi export [directory path: default is current] [--format ron] [--output path] [--recipe name] [--profile name]",
+		ja: "nibi export [ディレクトリのパス: 省略時はカレントディレクトリ] [--format ron] [--output 出力先] [--recipe レシピ名] [--profile プロファイル名]",
+	}
+	FormatFlag {
+		en: "file type of the export (ron/toml/json/xml/hcl)",
+		ja: "出力するファイルの形式 (ron/toml/json/xml/hcl)",
+	}
+	OutputFlag {
+		en: "output file path (default: export.[format] in the project)",
+		ja: "出力先のファイルパス (省略時はプロジェクト内のexport.[形式])",
+	}
+	ExportFailed {
+		en: "failed to export: {0}",
+		ja: "エクスポートに失敗しました: {0}",
+	}
+	SerializeAsFailed {
+		en: "failed to serialize as {0}: {1}",
+		ja: "{0}形式に変換できませんでした: {1}",
+	}
+	Exported {
+		en: "exported {0} ingot(s) to {1}",
+		ja: "{0}件の生地を{1}に書き出しました",
+	}
+	// build errors
+	ProfileNotFound {
+		en: "profile not found: {0}{1}",
+		ja: "プロファイルが見つかりません: {0}{1}",
+	}
+	IgataNotFound {
+		en: "igata not found: {0} (igata_table: {1}){2}",
+		ja: "鋳型が見つかりません: {0} (igata_table: {1}){2}",
+	}
+	IngotIdDuplicate {
+		en: "{0}: the ingot ID {1} is already used by {2}",
+		ja: "{0}: ingotのID {1} は {2} と重複しています",
+	}
+	IngotLangInvalid {
+		en: "{0}: the language can only have ASCII letters, digits and hyphens: {1}",
+		ja: "{0}: 言語に使えるのはASCIIの英数字とハイフンだけです: {1}",
+	}
+	OutputPathLangConflict {
+		en: "{0}: the output path is shared with {1} in another language: {2} (set default_lang or prefixes in the i18n config)",
+		ja: "{0}: 出力先が別の言語の {1} と重複しています: {2} (i18nの設定でdefault_langかprefixesを指定してください)",
+	}
+	OutputPathInvalid {
+		en: "{0}: the output path goes out of the site directory: {1}",
+		ja: "{0}: 出力先がサイトのディレクトリの外を指しています: {1}",
+	}
+	TermPathInvalid {
+		en: "the output path for the path name \"{0}\" goes out of the site directory: {1}",
+		ja: "パス名 \"{0}\" の出力先がサイトのディレクトリの外を指しています: {1}",
+	}
+	OutputPathDuplicate {
+		en: "{0}: the output path is already used by {1}: {2}",
+		ja: "{0}: 出力先が {1} と重複しています: {2}",
+	}
+	MessagesReadFailed {
+		en: "failed to read messages: {0}: {1}",
+		ja: "メッセージの読み込みに失敗しました: {0}: {1}",
+	}
+	ThreadsFailed {
+		en: "failed to prepare threads: {0}",
+		ja: "スレッドの準備に失敗しました: {0}",
+	}
+	ImageReadFailed {
+		en: "failed to read the image: {0}: {1}",
+		ja: "画像の読み込みに失敗しました: {0}: {1}",
+	}
+	ImageDecodeFailed {
+		en: "failed to decode the image: {0}: {1}",
+		ja: "画像のデコードに失敗しました: {0}: {1}",
+	}
+	ImageEncodeFailed {
+		en: "failed to encode the image variant: {0}: {1}",
+		ja: "画像の変換に失敗しました: {0}: {1}",
+	}
+	CategoryNotFound {
+		en: "category not found: {0}",
+		ja: "カテゴリーが見つかりません: {0}",
+	}
+	TagNotFound {
+		en: "tag not found: {0}",
+		ja: "タグが見つかりません: {0}",
+	}
+	ParseInvalid {
+		en: "ParseError: format is invalid",
+		ja: "ParseError: 形式が不正です",
+	}
+	ParseEmpty {
+		en: "ParseError: data is empty",
+		ja: "ParseError: データが空です",
+	}
+	MenuNoTarget {
+		en: "menu {0}: no target, set one of ingot, category, tag or url",
+		ja: "メニュー {0}: リンク先がありません。ingot、category、tag、urlのいずれかを指定してください",
+	}
+	MenuMultipleTargets {
+		en: "menu {0}: multiple targets, set only one of ingot, category, tag or url",
+		ja: "メニュー {0}: リンク先が複数あります。ingot、category、tag、urlのいずれか一つだけを指定してください",
+	}
+	MenuIngotNotFound {
+		en: "menu {0}: ingot not found: {1}{2}",
+		ja: "メニュー {0}: 生地が見つかりません: {1}{2}",
+	}
+	MenuIngotNotPublished {
+		en: "menu {0}: ingot is not published: {1}",
+		ja: "メニュー {0}: 生地が公開されていません: {1}",
+	}
+	MenuUnresolved {
+		en: "menu {0}: {1}",
+		ja: "メニュー {0}: {1}",
+	}
+	MenuNoTermPage {
+		en: "menu {0}: no page is built for \"{1}\", because category and tag pages are not built. Link an ingot or a URL instead",
+		ja: "メニュー {0}: カテゴリーとタグのページは作られないため、\"{1}\" にはリンクできません。生地かURLを指定してください",
+	}
+	ShortcodeUnterminated {
+		en: "line {0}: shortcode is not closed with {1}",
+		ja: "{0}行目: ショートコードが{1}で閉じられていません",
+	}
+	ShortcodeInvalidSyntax {
+		en: "line {0}: invalid shortcode: {1}",
+		ja: "{0}行目: ショートコードが不正です: {1}",
+	}
+	ShortcodeUnterminatedQuote {
+		en: "unterminated quote in: {0}",
+		ja: "引用符が閉じられていません: {0}",
+	}
+	ShortcodeNameMissing {
+		en: "name is missing in: {0}",
+		ja: "名前がありません: {0}",
+	}
+	ShortcodeUnexpectedClose {
+		en: "line {0}: closing shortcode without opening: {1}",
+		ja: "{0}行目: 開始のない閉じショートコードです: {1}",
+	}
+	ShortcodeNotFound {
+		en: "line {0}: shortcode not found: {1}{2}",
+		ja: "{0}行目: ショートコードが見つかりません: {1}{2}",
+	}
+	ShortcodeMissingArg {
+		en: "line {0}: shortcode {1}: missing argument: {2}",
+		ja: "{0}行目: ショートコード {1}: 引数がありません: {2}",
+	}
+	ShortcodeInvalidArg {
+		en: "line {0}: shortcode {1}: invalid {2}: {3}",
+		ja: "{0}行目: ショートコード {1}: {2}が不正です: {3}",
+	}
+	ShortcodeRefNotFound {
+		en: "line {0}: ref: ingot not found: {1}{2}",
+		ja: "{0}行目: ref: 生地が見つかりません: {1}{2}",
+	}
+	ShortcodeRefNotPublished {
+		en: "line {0}: ref: ingot is not published: {1}",
+		ja: "{0}行目: ref: 生地が公開されていません: {1}",
+	}
+	ShortcodeRender {
+		en: "line {0}: shortcode {1}: {2}",
+		ja: "{0}行目: ショートコード {1}: {2}",
+	}
+	// gears
+	GearReadFailed {
+		en: "{0}: failed to read gear: {1}",
+		ja: "{0}: gearの読み込みに失敗しました: {1}",
+	}
+	GearFailed {
+		en: "gear {0} ({1}): {2}",
+		ja: "gear {0} ({1}): {2}",
+	}
+	GearFilterFailed {
+		en: "gear {0} (filter {1}): {2}",
+		ja: "gear {0} (フィルター {1}): {2}",
+	}
+	GearInvalidResponse {
+		en: "gear {0} ({1}): invalid response: {2}",
+		ja: "gear {0} ({1}): 応答が不正です: {2}",
+	}
+	GearInvalidIngot {
+		en: "gear {0} ({1}): invalid {2} of ingot {3}",
+		ja: "gear {0} ({1}): 生地{3}の{2}が不正です",
+	}
+	GearInvalidPath {
+		en: "gear {0} ({1}): invalid file path: {2}",
+		ja: "gear {0} ({1}): ファイルパスが不正です: {2}",
+	}
+	GearDuplicateName {
+		en: "duplicate gear name",
+		ja: "gearの名前が重複しています",
+	}
+	GearNoRuntime {
+		en: "either command or wasm must be specified",
+		ja: "commandかwasmのいずれかを指定してください",
+	}
+	GearBothRuntimes {
+		en: "command and wasm can not be specified together",
+		ja: "commandとwasmは同時に指定できません",
+	}
+	GearFiltersWasmOnly {
+		en: "filters are only available for wasm gears",
+		ja: "filtersはwasmのgearでのみ使えます",
+	}
+	GearGrantsWasmOnly {
+		en: "grants are only available for wasm gears",
+		ja: "grantsはwasmのgearでのみ使えます",
+	}
+	WasmLoadFailed {
+		en: "failed to load module: {0}",
+		ja: "モジュールの読み込みに失敗しました: {0}",
+	}
+	WasmExportNotFound {
+		en: "export not found: {0}",
+		ja: "エクスポートが見つかりません: {0}",
+	}
+	WasmOutOfBounds {
+		en: "out of bounds memory access",
+		ja: "メモリの範囲外にアクセスしました",
+	}
+	InvalidOutput {
+		en: "invalid output: {0}",
+		ja: "出力が不正です: {0}",
+	}
+	ProcessExited {
+		en: "exited with status {0}",
+		ja: "ステータス{0}で終了しました",
+	}
+	ProcessSignaled {
+		en: "terminated by signal",
+		ja: "シグナルにより終了しました",
+	}
+	// packs
+	PackNotFound {
+		en: "pack not found: {0}{1}",
+		ja: "packが見つかりません: {0}{1}",
+	}
+	PackUnreadable {
+		en: "failed to read pack in {0}: {1}",
+		ja: "{0}のpackを読み込めません: {1}",
+	}
+	PackNameMismatch {
+		en: "pack name mismatch: {0} is named \"{1}\" in pack_info, but \"{2}\" is requested",
+		ja: "packの名前が一致しません: {0}のpack_infoでは\"{1}\"ですが、\"{2}\"が指定されました",
+	}
+	PackInvalidVersion {
+		en: "invalid version of pack {0}: \"{1}\" ({2})",
+		ja: "pack {0}のバージョンが不正です: \"{1}\" ({2})",
+	}
+	PackInvalidVersionReq {
+		en: "invalid version requirement for {0} in pack {1}: \"{2}\" ({3})",
+		ja: "pack {1}の{0}に対するバージョン指定が不正です: \"{2}\" ({3})",
+	}
+	PackNibiVersion {
+		en: "pack {0} requires nibi {1}, but nibi is {2}",
+		ja: "pack {0}はnibi {1}を必要としますが、nibiは{2}です",
+	}
+	PackDependencyNotFound {
+		en: "pack {0} required by {1} not found{2}",
+		ja: "{1}が必要とするpack {0}が見つかりません{2}",
+	}
+	PackDependencyVersion {
+		en: "pack {0} requires {1} {2}, but {1} is {3}",
+		ja: "pack {0}は{1} {2}を必要としますが、{1}は{3}です",
+	}
+	MissingRenderSource {
+		en: "additional_renders source not found: {0}",
+		ja: "additional_rendersの元ファイルが見つかりません: {0}",
+	}
+	MissingStaticSource {
+		en: "static_copy source not found: {0}",
+		ja: "static_copyの元ファイルが見つかりません: {0}",
+	}
+	InvalidSchemaDefault {
+		en: "schema default: {0}",
+		ja: "schemaの既定値: {0}",
+	}
+	ValueRequired {
+		en: "value {0} is required",
+		ja: "値{0}は必須です",
+	}
+	ValueTypeMismatch {
+		en: "value {0} must be {1}, but {2} is set",
+		ja: "値{0}は{1}でなければなりませんが、{2}が設定されています",
+	}
+	ValueNotAllowed {
+		en: "value {0} must be one of {1}, but {2} is set",
+		ja: "値{0}は{1}のいずれかでなければなりませんが、{2}が設定されています",
+	}
+	InvalidPackName {
+		en: "invalid pack name: \"{0}\"",
+		ja: "packの名前が不正です: \"{0}\"",
+	}
+	AlreadyExists {
+		en: "already exists: {0}",
+		ja: "既に存在します: {0}",
+	}
+	PackCopyFailed {
+		en: "failed to copy pack: {0}",
+		ja: "packのコピーに失敗しました: {0}",
+	}
+	UnknownPackSource {
+		en: "unknown source: {0} (tar.gz, zip, directory or git repository is supported)",
+		ja: "未対応のインストール元です: {0} (tar.gz、zip、ディレクトリ、gitリポジトリに対応しています)",
+	}
+	FetchFailed {
+		en: "failed to fetch {0}: {1}",
+		ja: "{0}の取得に失敗しました: {1}",
+	}
+	CloneFailed {
+		en: "failed to clone {0}: {1}",
+		ja: "{0}のcloneに失敗しました: {1}",
+	}
+	PackInfoNotFoundIn {
+		en: "pack_info or pack_config not found in {0}",
+		ja: "{0}にpack_infoかpack_configが見つかりません",
+	}
+	InvalidPackVersion {
+		en: "invalid pack version: \"{0}\" ({1})",
+		ja: "packのバージョンが不正です: \"{0}\" ({1})",
+	}
+	PackCollision {
+		en: "pack {0} already exists in {1}, use --force to replace it",
+		ja: "pack {0}は{1}に既に存在します。置き換えるには--forceを指定してください",
+	}
+	PackNameChanged {
+		en: "pack name changed in source: \"{0}\" is installed, but source is \"{1}\"",
+		ja: "インストール元でpackの名前が変わっています: \"{0}\"がインストールされていますが、インストール元は\"{1}\"です",
+	}
+	PackNotInstalled {
+		en: "pack is not installed with lockfile: {0}{1}",
+		ja: "lockfileにインストールの記録がないpackです: {0}{1}",
+	}
+	LockReadFailed {
+		en: "failed to read lockfile: {0}",
+		ja: "lockfileの読み込みに失敗しました: {0}",
+	}
+	LockWriteFailed {
+		en: "failed to write lockfile: {0}",
+		ja: "lockfileの書き込みに失敗しました: {0}",
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeSet;
+
+	use super::*;
+
+	fn placeholders(template: &str) -> BTreeSet<usize> {
+		template
+			.split('{')
+			.skip(1)
+			.filter_map(|s| s.split_once('}'))
+			.filter_map(|(n, _)| n.parse().ok())
+			.collect()
+	}
+
+	#[test]
+	fn test_catalog() {
+		for msg in Msg::ALL {
+			let en = msg.template(CliLang::En);
+			let ja = msg.template(CliLang::Ja);
+			assert!(!en.trim().is_empty(), "{msg:?}");
+			assert!(!ja.trim().is_empty(), "{msg:?}");
+			assert_eq!(placeholders(en), placeholders(ja), "{msg:?}");
+		}
+		assert_eq!(msg!(CheckFailed, 3), "check failed: 3 problem(s) found");
+		assert_eq!(
+			format_template("{1} -> {0} {2} {x}", &[&"a", &1]),
+			"1 -> a {2} {x}"
+		);
+	}
+
+	#[test]
+	fn test_lang() {
+		assert_eq!(CliLang::from_locale("ja_JP.UTF-8"), Some(CliLang::Ja));
+		assert_eq!(CliLang::from_locale("en-US"), Some(CliLang::En));
+		assert_eq!(CliLang::from_locale("C"), Some(CliLang::En));
+		assert_eq!(CliLang::from_locale("fr_FR"), None);
+		let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+		assert_eq!(lang_arg(args("nibi build --lang ja")), Some("ja".into()));
+		assert_eq!(lang_arg(args("nibi --lang=en check")), Some("en".into()));
+		assert_eq!(lang_arg(args("nibi build -- --lang ja")), None);
+	}
+}
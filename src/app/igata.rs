@@ -1,5 +1,13 @@
 #[allow(clippy::module_inception)]
 /// igata module
 pub mod igata;
+/// igata pack installation module
+pub mod install;
+/// igata pack management module
+pub mod manage;
+/// igata pack message files module
+pub mod messages;
 /// igata pack module
 pub mod pack;
+/// pack value schema module
+pub mod schema;
@@ -0,0 +1,142 @@
+use std::{
+	collections::BTreeSet,
+	fs,
+	io::ErrorKind,
+	path::{Path, PathBuf},
+};
+
+use minijinja::{Environment, Error, ErrorKind as TemplateErrorKind, State, Value, value::Rest};
+use walkdir::WalkDir;
+
+use crate::msg;
+
+/// The default extension of igata(template) files.
+pub const IGATA_EXT: &str = "html";
+
+/// The igata files built into nibi, as pairs of the file name and the source.
+/// A pack which has a file of the same name overrides it.
+pub const BUILTIN_IGATA: [(&str, &str); 1] = [(
+	"partials/search.html",
+	include_str!("builtin/partials/search.html"),
+)];
+
+/// Returns the source of the built-in igata file of the given file name.
+pub fn builtin_igata_source(file_name: &str) -> Option<&'static str> {
+	BUILTIN_IGATA
+		.iter()
+		.find(|(name, _)| *name == file_name)
+		.map(|(_, source)| *source)
+}
+
+/// The template function `t(key)`. Returns the message of the key in `messages` of the context,
+/// or the key itself if the message is not found.
+fn translate(state: &State, key: &str) -> String {
+	state
+		.lookup("messages")
+		.and_then(|messages| messages.get_attr(key).ok())
+		.filter(|message| !message.is_undefined() && !message.is_none())
+		.map(|message| message.to_string())
+		.unwrap_or_else(|| key.to_string())
+}
+
+/// Igata struct. Holds the template environment made from the igata packs.
+pub struct Igata {
+	env: Environment<'static>,
+	pack_dirs: Vec<PathBuf>,
+}
+
+/// Returns the template file name for the given igata name.
+/// The default extension is appended if the name does not have an extension.
+pub fn igata_file_name(name: &str) -> String {
+	if Path::new(name).extension().is_some() {
+		name.to_string()
+	} else {
+		format!("{name}.{IGATA_EXT}")
+	}
+}
+
+/// Finds the template file in the given pack directories. The first pack which has the file wins.
+pub fn find_igata_file(pack_dirs: &[PathBuf], file_name: &str) -> Option<PathBuf> {
+	pack_dirs
+		.iter()
+		.map(|dir| dir.join(file_name))
+		.find(|path| path.is_file())
+}
+
+impl Igata {
+	/// Creates a new `Igata` that loads templates from the given pack directories in order.
+	pub fn new(pack_dirs: Vec<PathBuf>) -> Self {
+		let mut env = Environment::new();
+		let loader_dirs = pack_dirs.clone();
+		env.set_loader(move |name| match find_igata_file(&loader_dirs, name) {
+			Some(path) => match fs::read_to_string(&path) {
+				Ok(source) => Ok(Some(source)),
+				Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+				Err(e) => Err(
+					Error::new(
+						TemplateErrorKind::InvalidOperation,
+						msg!(ReadFailed, path.display()),
+					)
+					.with_source(e),
+				),
+			},
+			// パックにない場合は組み込みのigataを使う
+			None => Ok(builtin_igata_source(name).map(|s| s.to_string())),
+		});
+		env.add_function("t", translate);
+		Self { env, pack_dirs }
+	}
+
+	/// Adds a template filter to the environment.
+	pub fn add_filter<F>(&mut self, name: String, filter: F)
+	where
+		F: Fn(Value, Rest<Value>) -> Result<Value, Error> + Send + Sync + 'static,
+	{
+		self.env.add_filter(name, filter);
+	}
+
+	/// Returns the pack directories of this `Igata`.
+	pub fn get_pack_dirs(&self) -> &[PathBuf] {
+		&self.pack_dirs
+	}
+
+	/// Returns `true` if a template file for the given igata name exists in the packs or is built in.
+	pub fn exists(&self, name: &str) -> bool {
+		let file_name = igata_file_name(name);
+		find_igata_file(&self.pack_dirs, &file_name).is_some()
+			|| builtin_igata_source(&file_name).is_some()
+	}
+
+	/// Returns the names of all template files in the packs, relative to each pack directory.
+	pub fn template_names(&self) -> BTreeSet<String> {
+		let mut names = BTreeSet::new();
+		for dir in self.pack_dirs.iter() {
+			for entry in WalkDir::new(dir)
+				.into_iter()
+				.filter_map(|e| e.ok())
+				.filter(|e| {
+					e.file_type().is_file() && e.path().extension().is_some_and(|ext| ext == IGATA_EXT)
+				}) {
+				if let Ok(rel) = entry.path().strip_prefix(dir) {
+					let name = rel
+						.components()
+						.map(|c| c.as_os_str().to_string_lossy())
+						.collect::<Vec<_>>()
+						.join("/");
+					names.insert(name);
+				}
+			}
+		}
+		names
+	}
+
+	/// Compiles the template for the given igata name without rendering it.
+	pub fn compile(&self, name: &str) -> Result<(), Error> {
+		self.env.get_template(&igata_file_name(name)).map(|_| ())
+	}
+
+	/// Renders the template for the given igata name with the given context.
+	pub fn render(&self, name: &str, ctx: Value) -> Result<String, Error> {
+		self.env.get_template(&igata_file_name(name))?.render(ctx)
+	}
+}
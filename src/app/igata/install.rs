@@ -0,0 +1,597 @@
+use std::{
+	collections::BTreeMap,
+	fmt::Display,
+	fs::{self, File},
+	io::Error as IOError,
+	path::{Path, PathBuf},
+	process::Command,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+
+use crate::app::{
+	fs::{copy_dir_all, io::open_file_with_overwrite_mode},
+	serde::{
+		DeError, FileType, SerError, find_path_with_file_type, get_extended_path,
+		read_deserialized_file, write_serialized_string_all,
+	},
+	suggest::{did_you_mean, near_miss_names},
+};
+use crate::msg;
+
+use super::{
+	manage::{PackEntry, list_packs},
+	pack::{PackProperties, norm_pack_name, read_pack_settings},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+/// Represents where a pack is installed from.
+pub enum PackSource {
+	/// A gzipped tar archive.
+	TarGz(PathBuf),
+	/// A zip archive.
+	Zip(PathBuf),
+	/// A pack directory.
+	Dir(PathBuf),
+	/// A git repository. A local path or a `file://` URL.
+	Git(String),
+}
+
+impl Display for PackSource {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			PackSource::TarGz(path) => write!(f, "tar.gz {}", path.display()),
+			PackSource::Zip(path) => write!(f, "zip {}", path.display()),
+			PackSource::Dir(path) => write!(f, "dir {}", path.display()),
+			PackSource::Git(url) => write!(f, "git {url}"),
+		}
+	}
+}
+
+impl PackSource {
+	/// Detects the source kind from the given path or URL.
+	/// Local paths are made absolute so the source can be pulled again from anywhere.
+	pub fn detect(src: &str) -> Option<Self> {
+		let src = src.trim();
+		if !is_allowed_git_url(src) {
+			return None;
+		}
+		if src.starts_with("file://") {
+			return Some(PackSource::Git(src.to_string()));
+		}
+		let Ok(path) = Path::new(src).canonicalize() else {
+			// ローカルにないものはリモートのリポジトリとして扱う
+			return src
+				.ends_with(".git")
+				.then(|| PackSource::Git(src.to_string()));
+		};
+		if path.is_dir() && src.ends_with(".git") {
+			return Some(PackSource::Git(path.to_string_lossy().to_string()));
+		}
+		let name = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+		if path.is_dir() {
+			if path.join(".git").exists() {
+				Some(PackSource::Git(path.to_string_lossy().to_string()))
+			} else {
+				Some(PackSource::Dir(path))
+			}
+		} else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+			Some(PackSource::TarGz(path))
+		} else if name.ends_with(".zip") {
+			Some(PackSource::Zip(path))
+		} else {
+			None
+		}
+	}
+}
+
+/// Returns false if git would read the source as an option or run a command for it.
+fn is_allowed_git_url(url: &str) -> bool {
+	!url.starts_with('-') && !url.to_ascii_lowercase().starts_with("ext::")
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Represents an installed pack recorded in the lockfile.
+pub struct LockedPack {
+	/// The source the pack was installed from.
+	pub source: PackSource,
+	/// The version written in the pack info.
+	pub version: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The commit hash, if installed from a git repository.
+	pub rev: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+/// Lockfile of the installed packs, keyed by the pack name.
+pub struct PackLock {
+	#[serde(default)]
+	/// The installed packs.
+	pub packs: BTreeMap<String, LockedPack>,
+}
+
+/// Returns the base path (without extension) of the lockfile in the igata directory.
+pub fn pack_lock_base_path(igata_dir: &Path) -> PathBuf {
+	igata_dir.join("igata_lock")
+}
+
+/// Reads the lockfile in the igata directory. Returns an empty lock if the file does not exist.
+pub fn read_pack_lock(igata_dir: &Path) -> Result<PackLock, DeError> {
+	match read_deserialized_file(&pack_lock_base_path(igata_dir)) {
+		Err(DeError::IO(e)) if e.kind() == std::io::ErrorKind::NotFound => Ok(PackLock::default()),
+		result => result,
+	}
+}
+
+/// Writes the lockfile into the igata directory, keeping the file type of the existing lockfile.
+pub fn write_pack_lock(igata_dir: &Path, lock: &PackLock) -> Result<(), SerError> {
+	let base_path = pack_lock_base_path(igata_dir);
+	let (path, file_type) = find_path_with_file_type(&base_path).unwrap_or_else(|| {
+		let file_type = FileType::default();
+		(get_extended_path(base_path, file_type), file_type)
+	});
+	let file = open_file_with_overwrite_mode(&path).map_err(SerError::IO)?;
+	write_serialized_string_all(file, lock, file_type)
+}
+
+#[derive(Debug)]
+/// Represents an error while installing or updating a pack.
+pub enum InstallError {
+	/// The source is not a supported archive, directory or git repository.
+	UnknownSource(String),
+	/// Failed to extract or copy the source.
+	Fetch(PackSource, IOError),
+	/// Failed to clone the git repository.
+	Git(String, String),
+	/// No valid `pack_info` and `pack_config` were found in the source.
+	InvalidPack(PackSource),
+	/// The pack name in the `pack_info` can not be used as a directory name.
+	InvalidName(String),
+	/// The version in the `pack_info` is not valid semver.
+	InvalidVersion(String, semver::Error),
+	/// A pack with the same name is already installed.
+	Collision(String, PathBuf),
+	/// The pack name of the source is different from the installed one.
+	NameChanged {
+		/// The name of the installed pack.
+		expected: String,
+		/// The name in the `pack_info` of the source.
+		actual: String,
+	},
+	/// The pack is not recorded in the lockfile. Near-miss pack names are suggested.
+	NotInstalled(String, Vec<String>),
+	/// Failed to move the pack into the igata directory.
+	IO(PathBuf, IOError),
+	/// Failed to read the lockfile.
+	ReadLock(DeError),
+	/// Failed to write the lockfile.
+	WriteLock(SerError),
+}
+
+impl Display for InstallError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			InstallError::UnknownSource(src) => write!(f, "{}", msg!(UnknownPackSource, src)),
+			InstallError::Fetch(src, e) => write!(f, "{}", msg!(FetchFailed, src, e)),
+			InstallError::Git(url, output) => {
+				write!(f, "{}", msg!(CloneFailed, url, output.trim()))
+			}
+			InstallError::InvalidPack(src) => write!(f, "{}", msg!(PackInfoNotFoundIn, src)),
+			InstallError::InvalidName(name) => write!(f, "{}", msg!(InvalidPackName, name)),
+			InstallError::InvalidVersion(version, e) => {
+				write!(f, "{}", msg!(InvalidPackVersion, version, e))
+			}
+			InstallError::Collision(name, dir) => {
+				write!(f, "{}", msg!(PackCollision, name, dir.display()))
+			}
+			InstallError::NameChanged { expected, actual } => {
+				write!(f, "{}", msg!(PackNameChanged, expected, actual))
+			}
+			InstallError::NotInstalled(name, suggestions) => write!(
+				f,
+				"{}",
+				msg!(PackNotInstalled, name, did_you_mean(suggestions))
+			),
+			InstallError::IO(path, e) => write!(f, "{}: {e}", path.display()),
+			InstallError::ReadLock(e) => write!(f, "{}", msg!(LockReadFailed, e)),
+			InstallError::WriteLock(e) => write!(f, "{}", msg!(LockWriteFailed, e)),
+		}
+	}
+}
+
+/// Result of an installation.
+pub struct Installed {
+	/// The installed pack.
+	pub pack: PackProperties,
+	/// The lock entry of the previously installed pack, if exists.
+	pub previous: Option<LockedPack>,
+}
+
+static INSTALL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn run_git(args: &[&str]) -> Result<String, String> {
+	match Command::new("git").args(args).output() {
+		Ok(output) if output.status.success() => {
+			Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+		}
+		Ok(output) => Err(String::from_utf8_lossy(&output.stderr).to_string()),
+		Err(e) => Err(e.to_string()),
+	}
+}
+
+/// Fetches the source into the staging directory. Returns the commit hash for git sources.
+fn fetch(source: &PackSource, staging: &Path) -> Result<Option<String>, InstallError> {
+	let fetch_err = |e| InstallError::Fetch(source.clone(), e);
+	match source {
+		PackSource::TarGz(path) => {
+			let file = File::open(path).map_err(fetch_err)?;
+			tar::Archive::new(GzDecoder::new(file))
+				.unpack(staging)
+				.map_err(fetch_err)?;
+			Ok(None)
+		}
+		PackSource::Zip(path) => {
+			let file = File::open(path).map_err(fetch_err)?;
+			zip::ZipArchive::new(file)
+				.and_then(|mut archive| archive.extract(staging))
+				.map_err(|e| fetch_err(IOError::other(e)))?;
+			Ok(None)
+		}
+		PackSource::Dir(path) => {
+			copy_dir_all(path, staging).map_err(fetch_err)?;
+			Ok(None)
+		}
+		PackSource::Git(url) => {
+			// lockfileのURLはdetectを通らないので、ここでも確かめる
+			if !is_allowed_git_url(url) {
+				return Err(InstallError::UnknownSource(url.clone()));
+			}
+			let staging_str = staging.to_string_lossy();
+			run_git(&[
+				"-c",
+				"protocol.ext.allow=never",
+				"clone",
+				"--quiet",
+				"--depth",
+				"1",
+				"--",
+				url,
+				&staging_str,
+			])
+			.map_err(|e| InstallError::Git(url.clone(), e))?;
+			let rev = run_git(&["-C", &staging_str, "rev-parse", "HEAD"]).ok();
+			let _ = fs::remove_dir_all(staging.join(".git"));
+			Ok(rev)
+		}
+	}
+}
+
+/// Finds the pack in the staging directory. Archives often wrap the pack in a single directory.
+fn find_staged_pack(staging: &Path) -> Option<PackProperties> {
+	if let Ok(pack) = read_pack_settings(staging) {
+		return Some(pack);
+	}
+	let mut entries = fs::read_dir(staging)
+		.ok()?
+		.filter_map(|e| e.ok())
+		.map(|e| e.path())
+		.collect::<Vec<_>>();
+	if entries.len() == 1 && entries[0].is_dir() {
+		read_pack_settings(&entries.remove(0)).ok()
+	} else {
+		None
+	}
+}
+
+/// Installs the pack from the source into the igata directory and records it in the lockfile.
+/// An existing pack with the same name is replaced only if `force` is `true`.
+/// If `expected_name` is given, the pack name of the source must be the same.
+pub fn install_pack(
+	source: PackSource,
+	igata_dir: &Path,
+	force: bool,
+	expected_name: Option<&str>,
+) -> Result<Installed, InstallError> {
+	let mut lock = read_pack_lock(igata_dir).map_err(InstallError::ReadLock)?;
+	fs::create_dir_all(igata_dir).map_err(|e| InstallError::IO(igata_dir.to_path_buf(), e))?;
+	// 途中で止まっても一時ファイルがigataディレクトリに残らないよう、一時ディレクトリで作業する
+	let work_dir = std::env::temp_dir().join(format!(
+		"nibi_install_{}_{}",
+		std::process::id(),
+		INSTALL_COUNTER.fetch_add(1, Ordering::Relaxed)
+	));
+	let _ = fs::remove_dir_all(&work_dir);
+	let staging = work_dir.join("staging");
+	fs::create_dir_all(&staging).map_err(|e| InstallError::IO(staging.clone(), e))?;
+
+	let result = install_staged(&source, igata_dir, &work_dir, force, expected_name);
+	let _ = fs::remove_dir_all(&work_dir);
+	let (pack, rev) = result?;
+
+	let name = pack.get_pack_name().to_string();
+	let previous = lock.packs.insert(
+		name,
+		LockedPack {
+			source,
+			version: pack.get_info().version.clone(),
+			rev,
+		},
+	);
+	write_pack_lock(igata_dir, &lock).map_err(InstallError::WriteLock)?;
+	Ok(Installed { pack, previous })
+}
+
+/// Moves the directory, copying it if it can not be renamed, e.g. across file systems.
+fn move_dir(from: &Path, to: &Path) -> Result<(), IOError> {
+	if fs::rename(from, to).is_ok() {
+		return Ok(());
+	}
+	if let Err(e) = copy_dir_all(from, to) {
+		let _ = fs::remove_dir_all(to);
+		return Err(e);
+	}
+	fs::remove_dir_all(from)
+}
+
+fn install_staged(
+	source: &PackSource,
+	igata_dir: &Path,
+	work_dir: &Path,
+	force: bool,
+	expected_name: Option<&str>,
+) -> Result<(PackProperties, Option<String>), InstallError> {
+	let staging = work_dir.join("staging");
+	let rev = fetch(source, &staging)?;
+	let staged =
+		find_staged_pack(&staging).ok_or_else(|| InstallError::InvalidPack(source.clone()))?;
+	let name = staged.get_pack_name().to_string();
+	if name.is_empty() || norm_pack_name(name.clone()) != name {
+		return Err(InstallError::InvalidName(name));
+	}
+	if let Some(Err(e)) = staged.get_info().parse_version() {
+		return Err(InstallError::InvalidVersion(
+			staged.get_info().version.clone(),
+			e,
+		));
+	}
+	if let Some(expected) = expected_name
+		&& expected != name
+	{
+		return Err(InstallError::NameChanged {
+			expected: expected.to_string(),
+			actual: name,
+		});
+	}
+
+	// 同じ名前のpackが別のディレクトリにある場合も衝突とする
+	let existing = list_packs(igata_dir)
+		.unwrap_or_default()
+		.into_iter()
+		.filter_map(|entry| match entry {
+			PackEntry::Pack(pack) if pack.get_pack_name() == name => {
+				Some(pack.get_directory().to_path_buf())
+			}
+			_ => None,
+		})
+		.collect::<Vec<_>>();
+	let dest = igata_dir.join(&name);
+	let mut to_remove = existing;
+	if dest.exists() && !to_remove.contains(&dest) {
+		to_remove.push(dest.clone());
+	}
+	if let Some(dir) = to_remove.first()
+		&& !force
+	{
+		return Err(InstallError::Collision(name, dir.clone()));
+	}
+
+	// 置き換えるpackは退避しておき、新しいpackを置けなかった場合は元に戻す
+	let mut moved = Vec::new();
+	let mut result = Ok(());
+	for (i, dir) in to_remove.into_iter().enumerate() {
+		let backup = work_dir.join(format!("old_{i}"));
+		match move_dir(&dir, &backup) {
+			Ok(()) => moved.push((dir, backup)),
+			Err(e) => {
+				result = Err(InstallError::IO(dir, e));
+				break;
+			}
+		}
+	}
+	if result.is_ok() {
+		result =
+			move_dir(staged.get_directory(), &dest).map_err(|e| InstallError::IO(dest.clone(), e));
+	}
+	if let Err(e) = result {
+		for (dir, backup) in moved.iter().rev() {
+			let _ = move_dir(backup, dir);
+		}
+		return Err(e);
+	}
+	let pack = PackProperties::new(staged.info, staged.config, dest);
+	Ok((pack, rev))
+}
+
+/// Pulls the pack again from the source recorded in the lockfile and replaces the installed pack.
+pub fn update_pack(name: &str, igata_dir: &Path) -> Result<Installed, InstallError> {
+	let lock = read_pack_lock(igata_dir).map_err(InstallError::ReadLock)?;
+	let Some(locked) = lock.packs.get(name) else {
+		return Err(InstallError::NotInstalled(
+			name.to_string(),
+			near_miss_names(name, lock.packs.keys().map(|s| s.as_str())),
+		));
+	};
+	install_pack(locked.source.clone(), igata_dir, true, Some(name))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Write;
+
+	use flate2::{Compression, write::GzEncoder};
+
+	use super::*;
+	use crate::app::testing::{TempDir, write_pack};
+
+	#[test]
+	fn test_install_pack() {
+		let root = TempDir::new("install");
+		let igata_dir = root.join("igata");
+		let src_dir = root.join("src");
+
+		// 単一ディレクトリで包まれたtar.gz
+		write_pack(&src_dir.join("theme"), "theme", "1.0.0", &[]);
+		let tar_path = root.join("theme.tar.gz");
+		let mut builder = tar::Builder::new(GzEncoder::new(
+			File::create(&tar_path).unwrap(),
+			Compression::default(),
+		));
+		builder
+			.append_dir_all("theme", src_dir.join("theme"))
+			.unwrap();
+		builder.into_inner().unwrap().finish().unwrap();
+
+		let source = PackSource::detect(&tar_path.to_string_lossy()).unwrap();
+		assert!(matches!(source, PackSource::TarGz(_)));
+		let installed = install_pack(source.clone(), &igata_dir, false, None).unwrap();
+		assert_eq!(installed.pack.get_directory(), igata_dir.join("theme"));
+		assert!(igata_dir.join("theme/post.html").is_file());
+		assert!(matches!(
+			install_pack(source, &igata_dir, false, None),
+			Err(InstallError::Collision(..))
+		));
+
+		// zipで更新し、lockfileから再取得する
+		write_pack(&src_dir.join("theme"), "theme", "1.1.0", &[]);
+		let zip_path = root.join("theme.zip");
+		let mut zip = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+		for file in ["pack_info.ron", "pack_config.ron", "post.html"] {
+			zip.start_file(file, zip::write::SimpleFileOptions::default())
+				.unwrap();
+			zip.write_all(&fs::read(src_dir.join("theme").join(file)).unwrap())
+				.unwrap();
+		}
+		zip.finish().unwrap();
+		let source = PackSource::detect(&zip_path.to_string_lossy()).unwrap();
+		let installed = install_pack(source, &igata_dir, true, None).unwrap();
+		assert_eq!(installed.previous.unwrap().version, "1.0.0");
+
+		write_pack(&src_dir.join("theme"), "theme", "1.2.0", &[]);
+		let source = PackSource::detect(&src_dir.join("theme").to_string_lossy()).unwrap();
+		assert!(matches!(source, PackSource::Dir(_)));
+		install_pack(source, &igata_dir, true, None).unwrap();
+		write_pack(&src_dir.join("theme"), "theme", "1.3.0", &[]);
+		let updated = update_pack("theme", &igata_dir).unwrap();
+		assert_eq!(updated.previous.unwrap().version, "1.2.0");
+		let lock = read_pack_lock(&igata_dir).unwrap();
+		assert_eq!(lock.packs["theme"].version, "1.3.0");
+		// 作業用のディレクトリはigataディレクトリに残らない
+		let mut names = fs::read_dir(&igata_dir)
+			.unwrap()
+			.map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+			.collect::<Vec<_>>();
+		names.sort();
+		assert_eq!(names, ["igata_lock.ron", "theme"]);
+		assert!(matches!(
+			update_pack("thme", &igata_dir),
+			Err(InstallError::NotInstalled(_, s)) if s == ["theme"]
+		));
+	}
+
+	#[test]
+	fn test_install_git_pack() {
+		let root = TempDir::new("install_git");
+		let igata_dir = root.join("igata");
+		let repo = root.join("repo");
+		let repo_str = repo.to_string_lossy().to_string();
+		let commit = |version: &str| {
+			write_pack(&repo, "theme", version, &[]);
+			run_git(&["-C", &repo_str, "add", "-A"]).unwrap();
+			run_git(&[
+				"-C",
+				&repo_str,
+				"-c",
+				"user.name=nibi",
+				"-c",
+				"user.email=nibi@example.com",
+				"-c",
+				"commit.gpgsign=false",
+				"commit",
+				"--quiet",
+				"-m",
+				version,
+			])
+			.unwrap();
+			run_git(&["-C", &repo_str, "rev-parse", "HEAD"]).unwrap()
+		};
+		fs::create_dir_all(&repo).unwrap();
+		run_git(&["-C", &repo_str, "init", "--quiet"]).unwrap();
+		let first = commit("1.0.0");
+
+		let source = PackSource::detect(&format!("file://{repo_str}")).unwrap();
+		assert!(matches!(source, PackSource::Git(_)));
+		install_pack(source, &igata_dir, false, None).unwrap();
+		assert!(igata_dir.join("theme/post.html").is_file());
+		assert!(!igata_dir.join("theme/.git").exists());
+		let lock = read_pack_lock(&igata_dir).unwrap();
+		assert_eq!(lock.packs["theme"].rev.as_deref(), Some(first.as_str()));
+
+		// 新しいコミットをlockfileのURLから取り直す
+		let second = commit("1.1.0");
+		let updated = update_pack("theme", &igata_dir).unwrap();
+		let previous = updated.previous.unwrap();
+		assert_eq!(previous.version, "1.0.0");
+		assert_eq!(previous.rev.as_deref(), Some(first.as_str()));
+		let lock = read_pack_lock(&igata_dir).unwrap();
+		assert_eq!(lock.packs["theme"].version, "1.1.0");
+		assert_eq!(lock.packs["theme"].rev.as_deref(), Some(second.as_str()));
+
+		// ローカルのパスは絶対パスにして記録する
+		fs::create_dir_all(root.join("bare.git")).unwrap();
+		assert_eq!(
+			PackSource::detect(&format!("{}/repo/../bare.git", root.display())),
+			Some(PackSource::Git(
+				root
+					.join("bare.git")
+					.canonicalize()
+					.unwrap()
+					.to_string_lossy()
+					.to_string()
+			))
+		);
+		assert_eq!(
+			PackSource::detect("https://example.com/theme.git"),
+			Some(PackSource::Git("https://example.com/theme.git".to_string()))
+		);
+	}
+
+	#[test]
+	fn test_reject_git_options() {
+		let root = TempDir::new("install_git_options");
+		let pwned = root.join("pwned");
+		let cmd = format!("--upload-pack=touch {}", pwned.display());
+		assert_eq!(PackSource::detect(&format!("{cmd} .git")), None);
+		assert_eq!(PackSource::detect("ext::sh -c touch% pwned .git"), None);
+
+		// lockfileに書かれたURLも使わない
+		let igata_dir = root.join("igata");
+		fs::create_dir_all(&igata_dir).unwrap();
+		let mut lock = PackLock::default();
+		lock.packs.insert(
+			"theme".to_string(),
+			LockedPack {
+				source: PackSource::Git(format!("{cmd} .git")),
+				version: "1.0.0".to_string(),
+				rev: None,
+			},
+		);
+		write_pack_lock(&igata_dir, &lock).unwrap();
+		assert!(matches!(
+			update_pack("theme", &igata_dir),
+			Err(InstallError::UnknownSource(_))
+		));
+		assert!(!pwned.exists());
+	}
+}
@@ -0,0 +1,253 @@
+use std::{
+	fmt::Display,
+	io::Error as IOError,
+	path::{Path, PathBuf},
+};
+
+use crate::app::{
+	fs::{copy_dir_all, get_child_dirs, io::open_file_with_overwrite_mode},
+	serde::{
+		DeError, FileType, SerError, find_path_with_file_type, get_extended_path,
+		write_serialized_string_all,
+	},
+};
+use crate::msg;
+
+use super::{
+	igata::Igata,
+	pack::{PackInfo, PackProperties, norm_pack_name, pack_info_base_path, read_pack_settings},
+	schema::SchemaViolation,
+};
+
+/// Represents a pack directory found in the igata directory.
+pub enum PackEntry {
+	/// A pack with its info and config.
+	Pack(Box<PackProperties>),
+	/// A directory without readable pack info or config, with the error of reading them.
+	Invalid(PathBuf, DeError),
+}
+
+/// Lists the pack directories in the igata directory, sorted by the directory name.
+pub fn list_packs(igata_dir: &Path) -> Result<Vec<PackEntry>, IOError> {
+	let mut dirs = get_child_dirs(igata_dir)?;
+	dirs.sort();
+	Ok(dirs
+		.into_iter()
+		.map(|dir| match read_pack_settings(&dir) {
+			Ok(pack) => PackEntry::Pack(Box::new(pack)),
+			Err(e) => PackEntry::Invalid(dir, e),
+		})
+		.collect())
+}
+
+/// Returns the one line summary of the pack info.
+pub fn pack_summary(info: &PackInfo) -> String {
+	let mut summary = info.name.clone();
+	if !info.version.is_empty() {
+		summary.push_str(&format!(" {}", info.version));
+	}
+	if !info.authors.is_empty() {
+		let authors = info
+			.authors
+			.iter()
+			.map(|a| a.name.as_str())
+			.collect::<Vec<_>>();
+		summary.push_str(&format!(" by {}", authors.join(", ")));
+	}
+	if !info.license.is_empty() {
+		summary.push_str(&format!(" [{}]", info.license));
+	}
+	summary
+}
+
+#[derive(Debug)]
+/// Represents a problem found in a pack.
+pub enum PackProblem {
+	/// Failed to compile a template.
+	Compile(String, minijinja::Error),
+	/// The source of an `additional_renders` entry does not exist.
+	MissingRenderSource(PathBuf),
+	/// The source of a `static_copy` entry does not exist.
+	MissingStaticSource(PathBuf),
+	/// The default value in the schema does not satisfy the schema.
+	InvalidDefault(SchemaViolation),
+}
+
+impl Display for PackProblem {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			PackProblem::Compile(name, e) => write!(f, "{name}: {e:#}"),
+			PackProblem::MissingRenderSource(path) => {
+				write!(f, "{}", msg!(MissingRenderSource, path.display()))
+			}
+			PackProblem::MissingStaticSource(path) => {
+				write!(f, "{}", msg!(MissingStaticSource, path.display()))
+			}
+			PackProblem::InvalidDefault(v) => write!(f, "{}", msg!(InvalidSchemaDefault, v)),
+		}
+	}
+}
+
+/// Validates the pack. Compiles every template and checks that the sources of
+/// `additional_renders` and `static_copy` exist.
+pub fn validate_pack(pack: &PackProperties) -> Vec<PackProblem> {
+	let dir = pack.get_directory();
+	let config = pack.get_config();
+	let igata = Igata::new(vec![dir.to_path_buf()]);
+	let mut problems = Vec::new();
+
+	for name in igata.template_names() {
+		if let Err(e) = igata.compile(&name) {
+			problems.push(PackProblem::Compile(name, e));
+		}
+	}
+
+	for src in config.additional_renders.iter().flat_map(|m| m.keys()) {
+		let path = dir.join(src);
+		if !path.is_file() {
+			problems.push(PackProblem::MissingRenderSource(path));
+		} else if let Err(e) = igata.compile(&src.to_string_lossy()) {
+			problems.push(PackProblem::Compile(src.to_string_lossy().to_string(), e));
+		}
+	}
+
+	for src in config.static_copy.iter().flat_map(|m| m.keys()) {
+		let path = dir.join(src);
+		if !path.exists() {
+			problems.push(PackProblem::MissingStaticSource(path));
+		}
+	}
+
+	for (key, schema) in config.schema.iter() {
+		if let Some(default) = &schema.default
+			&& let Some(v) = schema.validate(key, Some(default))
+		{
+			problems.push(PackProblem::InvalidDefault(v));
+		}
+	}
+
+	problems
+}
+
+#[derive(Debug)]
+/// Represents an error while forking a pack.
+pub enum ForkError {
+	/// The new pack name is empty after normalization.
+	InvalidName(String),
+	/// The destination directory already exists.
+	AlreadyExists(PathBuf),
+	/// Failed to copy the pack directory.
+	Copy(IOError),
+	/// Failed to write the pack info of the copied pack.
+	WriteInfo(PathBuf, SerError),
+}
+
+impl Display for ForkError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ForkError::InvalidName(name) => write!(f, "{}", msg!(InvalidPackName, name)),
+			ForkError::AlreadyExists(path) => write!(f, "{}", msg!(AlreadyExists, path.display())),
+			ForkError::Copy(e) => write!(f, "{}", msg!(PackCopyFailed, e)),
+			ForkError::WriteInfo(path, e) => write!(f, "{}: {e}", path.display()),
+		}
+	}
+}
+
+/// Copies the pack into the igata directory under the new name and rewrites the name in its pack info.
+/// The pack info is written back in the file type of the original file.
+/// Returns the directory of the new pack.
+pub fn fork_pack(
+	pack: &PackProperties,
+	igata_dir: &Path,
+	new_name: String,
+) -> Result<PathBuf, ForkError> {
+	let normed_name = norm_pack_name(new_name.clone());
+	if normed_name.is_empty() {
+		return Err(ForkError::InvalidName(new_name));
+	}
+	let new_name = normed_name;
+	let new_dir = igata_dir.join(&new_name);
+	if new_dir.exists() {
+		return Err(ForkError::AlreadyExists(new_dir));
+	}
+	copy_dir_all(pack.get_directory(), &new_dir).map_err(ForkError::Copy)?;
+
+	// 元のpack_infoと同じ形式で書き戻す
+	let base_path = pack_info_base_path(&new_dir);
+	let (info_path, file_type) = find_path_with_file_type(&base_path).unwrap_or_else(|| {
+		let file_type = FileType::default();
+		(get_extended_path(base_path, file_type), file_type)
+	});
+	let mut info = pack.get_info().clone();
+	info.name = new_name;
+	open_file_with_overwrite_mode(&info_path)
+		.map_err(|e| ForkError::WriteInfo(info_path.clone(), SerError::IO(e)))
+		.and_then(|file| {
+			write_serialized_string_all(file, &info, file_type)
+				.map_err(|e| ForkError::WriteInfo(info_path.clone(), e))
+		})?;
+	Ok(new_dir)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use super::*;
+	use crate::app::testing::{TempDir, write_pack};
+
+	#[test]
+	fn test_validate_pack() {
+		let dir = TempDir::new("manage_validate");
+		write_pack(&dir, "theme", "1.0.0", &[]);
+		// 既定のpack_configのコピー元はまだない
+		let pack = read_pack_settings(&dir).unwrap();
+		let problems = validate_pack(&pack);
+		assert_eq!(problems.len(), 3, "{problems:?}");
+		assert!(
+			matches!(&problems[0], PackProblem::MissingRenderSource(path) if path.ends_with("common.css"))
+		);
+		assert!(
+			matches!(&problems[1], PackProblem::MissingRenderSource(path) if path.ends_with("common.js"))
+		);
+		assert!(
+			matches!(&problems[2], PackProblem::MissingStaticSource(path) if path.ends_with("assets"))
+		);
+
+		fs::write(dir.join("common.css"), "").unwrap();
+		fs::write(dir.join("common.js"), "").unwrap();
+		fs::create_dir(dir.join("assets")).unwrap();
+		let problems = validate_pack(&pack);
+		assert!(problems.is_empty(), "{problems:?}");
+	}
+
+	#[test]
+	fn test_fork_pack() {
+		let dir = TempDir::new("manage_fork");
+		write_pack(&dir.join("theme"), "theme", "1.0.0", &[]);
+		let pack = read_pack_settings(&dir.join("theme")).unwrap();
+
+		let new_dir = fork_pack(&pack, &dir, "my theme".to_string()).unwrap();
+		assert_eq!(new_dir, dir.join("mytheme"));
+		assert!(new_dir.join("post.html").is_file());
+		let forked = read_pack_settings(&new_dir).unwrap();
+		assert_eq!(forked.get_info().name, "mytheme");
+		assert_eq!(forked.get_info().version, "1.0.0");
+		assert_eq!(
+			read_pack_settings(&dir.join("theme"))
+				.unwrap()
+				.get_info()
+				.name,
+			"theme"
+		);
+
+		assert!(matches!(
+			fork_pack(&pack, &dir, "mytheme".to_string()),
+			Err(ForkError::AlreadyExists(_))
+		));
+		assert!(matches!(
+			fork_pack(&pack, &dir, " / ".to_string()),
+			Err(ForkError::InvalidName(_))
+		));
+	}
+}
@@ -0,0 +1,123 @@
+use std::{
+	collections::BTreeMap,
+	fs,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
+
+use crate::app::{
+	fs::io::open_file_with_read_mode,
+	serde::{DeError, FileType, read_deserialized_value},
+};
+
+/// The directory of the message files in an igata pack.
+/// Each file is named after the language code (e.g. `i18n/ja.ron`) and maps message keys to strings.
+pub const MESSAGES_DIR: &str = "i18n";
+
+/// The messages of a language, keyed by the message key.
+pub type Messages = BTreeMap<String, String>;
+
+fn read_messages(path: &Path, file_type: FileType) -> Result<Messages, DeError> {
+	let file = open_file_with_read_mode(path).map_err(DeError::IO)?;
+	read_deserialized_value(file, file_type)
+}
+
+/// Loads the message files of the given pack directories, keyed by the language code.
+/// The message of the first pack which has the key wins, as the igata files do.
+/// Files whose extension is not a supported file type are ignored.
+pub fn load_messages(
+	pack_dirs: &[PathBuf],
+) -> (BTreeMap<String, Messages>, Vec<(PathBuf, DeError)>) {
+	let mut all: BTreeMap<String, Messages> = BTreeMap::new();
+	let mut errors = Vec::new();
+	for dir in pack_dirs.iter() {
+		let Ok(entries) = fs::read_dir(dir.join(MESSAGES_DIR)) else {
+			continue;
+		};
+		let mut paths = entries
+			.filter_map(|e| e.ok())
+			.map(|e| e.path())
+			.filter(|path| path.is_file())
+			.collect::<Vec<_>>();
+		paths.sort();
+		for path in paths {
+			let (Some(lang), Some(file_type)) = (
+				path.file_stem().map(|s| s.to_string_lossy().to_string()),
+				path
+					.extension()
+					.and_then(|ext| FileType::from_str(&ext.to_string_lossy()).ok()),
+			) else {
+				continue;
+			};
+			match read_messages(&path, file_type) {
+				Ok(messages) => {
+					let entry = all.entry(lang).or_default();
+					for (key, message) in messages {
+						entry.entry(key).or_insert(message);
+					}
+				}
+				Err(e) => errors.push((path, e)),
+			}
+		}
+	}
+	(all, errors)
+}
+
+/// Returns the messages of the given language, falling back to the messages of the default language.
+pub fn messages_for(all: &BTreeMap<String, Messages>, lang: &str, default_lang: &str) -> Messages {
+	let mut messages = all.get(lang).cloned().unwrap_or_default();
+	if lang != default_lang
+		&& let Some(defaults) = all.get(default_lang)
+	{
+		for (key, message) in defaults {
+			messages
+				.entry(key.clone())
+				.or_insert_with(|| message.clone());
+		}
+	}
+	messages
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::testing::TempDir;
+
+	#[test]
+	fn test_load_messages() {
+		let dir = TempDir::new("messages");
+		let first = dir.join("first");
+		let second = dir.join("second");
+		fs::create_dir_all(first.join(MESSAGES_DIR)).unwrap();
+		fs::create_dir_all(second.join(MESSAGES_DIR)).unwrap();
+		fs::write(
+			first.join(MESSAGES_DIR).join("en.ron"),
+			r#"{"read_more": "Read more"}"#,
+		)
+		.unwrap();
+		fs::write(
+			second.join(MESSAGES_DIR).join("en.json"),
+			r#"{"read_more": "More", "search": "Search"}"#,
+		)
+		.unwrap();
+		fs::write(
+			second.join(MESSAGES_DIR).join("ja.toml"),
+			"read_more = \"続きを読む\"\nhome = \"ホーム\"\n",
+		)
+		.unwrap();
+		fs::write(second.join(MESSAGES_DIR).join("README.md"), "ignored").unwrap();
+
+		let (all, errors) = load_messages(&[first, second]);
+		assert!(errors.is_empty());
+		assert_eq!(all.len(), 2);
+		assert_eq!(all["en"]["read_more"], "Read more");
+		assert_eq!(all["en"]["search"], "Search");
+
+		let ja = messages_for(&all, "ja", "en");
+		assert_eq!(ja["read_more"], "続きを読む");
+		assert_eq!(ja["search"], "Search");
+		let fr = messages_for(&all, "fr", "en");
+		assert_eq!(fr["read_more"], "Read more");
+		assert!(!fr.contains_key("home"));
+	}
+}
@@ -4,16 +4,25 @@ use std::{
 	path::{Path, PathBuf},
 };
 
+use combu::crate_version;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 
 use crate::app::{
 	fs::{
 		get_child_dirs,
-		io::{open_file_with_overwrite_mode, open_file_with_read_mode},
+		io::open_file_with_overwrite_mode,
 		path::{append_ext, to_path_map},
 	},
-	serde::{FileType, read_deserialized_value, write_serialized_string_all},
+	serde::{
+		DeResult, FileType, get_extended_path, read_deserialized_file, write_serialized_string_all,
+	},
+	suggest::{did_you_mean, near_miss_names},
+	value::{ValueMap, merge_values},
 };
+use crate::msg;
+
+use super::schema::{Schema, schema_defaults};
 
 #[derive(Default, Deserialize, Serialize, Clone)]
 /// Represents an author of a pack.
@@ -35,10 +44,19 @@ pub struct PackInfo {
 	pub description: String,
 	/// The license of the pack.
 	pub license: String,
-	/// The version of the pack.
+	/// The version of the pack. Parsed as semver if not empty.
 	pub version: String,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	/// The version requirement of nibi, e.g. `>=0.2`.
+	pub nibi_version: String,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	/// The packs this pack depends on, with the version requirements, e.g. `{"base": "^1.2"}`.
+	pub depends: BTreeMap<String, String>,
 }
 
+/// The version of newly created packs.
+pub const INITIAL_PACK_VERSION: &str = "0.1.0";
+
 impl PackInfo {
 	/// Creates a new `PackInfo` with the given name.
 	pub fn new<T: Into<String>>(name: T) -> Self {
@@ -48,8 +66,25 @@ impl PackInfo {
 			description: String::new(),
 			license: String::new(),
 			version: String::new(),
+			nibi_version: String::new(),
+			depends: BTreeMap::new(),
 		}
 	}
+	/// Parses the version as semver. Returns `None` if the version is empty.
+	pub fn parse_version(&self) -> Option<Result<Version, semver::Error>> {
+		let version = self.version.trim();
+		(!version.is_empty()).then(|| Version::parse(version))
+	}
+}
+
+/// Parses the version requirement. An empty requirement matches any version.
+pub fn parse_version_req(req: &str) -> Result<VersionReq, semver::Error> {
+	let req = req.trim();
+	if req.is_empty() {
+		Ok(VersionReq::STAR)
+	} else {
+		VersionReq::parse(req)
+	}
 }
 
 impl Default for PackInfo {
@@ -66,7 +101,10 @@ pub struct PackConfig {
 	/// Static files to copy into the pack.
 	pub static_copy: Option<BTreeMap<PathBuf, PathBuf>>,
 	/// Values to replace in the pack.
-	pub values: BTreeMap<String, String>,
+	pub values: ValueMap,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	/// Schema of the values which the pack accepts from recipes.
+	pub schema: Schema,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -85,6 +123,12 @@ impl PackConfig {
 	pub fn new() -> Self {
 		Self::default()
 	}
+	/// Returns the values of the pack merged over the defaults declared in the schema.
+	pub fn get_default_values(&self) -> ValueMap {
+		let mut values = schema_defaults(&self.schema);
+		merge_values(&mut values, self.values.clone());
+		values
+	}
 }
 
 impl PackProperties {
@@ -112,6 +156,66 @@ impl PackProperties {
 	pub fn get_pack_name(&self) -> &str {
 		&self.info.name
 	}
+	/// Returns the human readable description of the pack, including the documented values.
+	pub fn describe(&self) -> String {
+		let info = &self.info;
+		let mut lines = vec![msg!(PackDescName, info.name)];
+		if !info.version.is_empty() {
+			lines.push(msg!(PackDescVersion, info.version));
+		}
+		if !info.description.is_empty() {
+			lines.push(msg!(PackDescDescription, info.description));
+		}
+		if !info.license.is_empty() {
+			lines.push(msg!(PackDescLicense, info.license));
+		}
+		if !info.nibi_version.is_empty() {
+			lines.push(msg!(PackDescNibiVersion, info.nibi_version));
+		}
+		for (dependency, req) in info.depends.iter() {
+			lines.push(msg!(
+				PackDescDepends,
+				format!("{dependency} {req}").trim_end()
+			));
+		}
+		for author in info.authors.iter() {
+			let contacts = author
+				.contact
+				.iter()
+				.map(|(k, v)| format!("{k}: {v}"))
+				.collect::<Vec<_>>();
+			if contacts.is_empty() {
+				lines.push(msg!(PackDescAuthor, author.name));
+			} else {
+				lines.push(msg!(
+					PackDescAuthor,
+					format!("{} ({})", author.name, contacts.join(", "))
+				));
+			}
+		}
+		lines.push(msg!(PackDescDirectory, self.directory.display()));
+
+		let config = &self.config;
+		if config.schema.is_empty() {
+			lines.push(msg!(NoDocumentedValues));
+		} else {
+			lines.push(msg!(PackDescValues));
+			for (key, schema) in config.schema.iter() {
+				lines.extend(schema.describe(key).into_iter().map(|l| format!("  {l}")));
+			}
+		}
+		let undocumented = config
+			.values
+			.iter()
+			.filter(|(key, _)| !config.schema.contains_key(*key))
+			.map(|(key, value)| format!("  {key} = {value}"))
+			.collect::<Vec<_>>();
+		if !undocumented.is_empty() {
+			lines.push(msg!(UndocumentedValues));
+			lines.extend(undocumented);
+		}
+		lines.join("\n")
+	}
 }
 
 fn default_additional_renders() -> Option<BTreeMap<PathBuf, PathBuf>> {
@@ -138,7 +242,8 @@ impl Default for PackConfig {
 		Self {
 			additional_renders: default_additional_renders(),
 			static_copy: default_static_copy(),
-			values: BTreeMap::new(),
+			values: ValueMap::new(),
+			schema: Schema::new(),
 		}
 	}
 }
@@ -168,17 +273,12 @@ pub fn create_new_pack(igata_dir_path: &Path, pack_name: String) {
 	let normed_pack_name = norm_pack_name(pack_name);
 	let set_dir_path = igata_dir_path.join(&normed_pack_name);
 
-	println!("begin {normed_pack_name} creation: {normed_pack_name}の作成を開始します");
+	println!("{}", msg!(BeginCreation, normed_pack_name));
 
 	match fs::create_dir(&set_dir_path) {
-		Ok(()) => println!(
-			"create new set directory {0}: 新しいセット用ディレクトリを作成しました {0}",
-			set_dir_path.display()
-		),
+		Ok(()) => println!("{}", msg!(PackDirCreated, set_dir_path.display())),
 		Err(e) => {
-			println!(
-				"failed to create new set directory: セット用ディレクトリの作成に失敗しました - {e}"
-			);
+			println!("{}", msg!(PackDirCreateFailed, e));
 			return;
 		}
 	}
@@ -193,6 +293,9 @@ pub fn create_new_pack(igata_dir_path: &Path, pack_name: String) {
 		let _ = fs::File::create(file_path);
 	}
 
+	// static_copyの既定のコピー元
+	let _ = fs::create_dir(set_dir_path.join("assets"));
+
 	let default_js_css = vec!["common.css", "common.js"];
 
 	for file_name in default_js_css {
@@ -200,54 +303,356 @@ pub fn create_new_pack(igata_dir_path: &Path, pack_name: String) {
 		let _ = fs::File::create(file_path);
 	}
 
-	if let Ok(writer) = open_file_with_overwrite_mode(&set_dir_path.join("pack_info.ron")) {
-		let _ = write_serialized_string_all(writer, &PackInfo::new(&normed_pack_name), FileType::Ron);
+	let file_type = FileType::default();
+	if let Ok(writer) = open_file_with_overwrite_mode(&get_extended_path(
+		pack_info_base_path(&set_dir_path),
+		file_type,
+	)) {
+		let mut info = PackInfo::new(&normed_pack_name);
+		info.version = INITIAL_PACK_VERSION.to_string();
+		let _ = write_serialized_string_all(writer, &info, file_type);
 	}
 
-	if let Ok(writer) = open_file_with_overwrite_mode(&set_dir_path.join("pack_config.ron")) {
-		let _ = write_serialized_string_all(writer, &PackConfig::default(), FileType::Ron);
+	if let Ok(writer) = open_file_with_overwrite_mode(&get_extended_path(
+		pack_config_base_path(&set_dir_path),
+		file_type,
+	)) {
+		let _ = write_serialized_string_all(writer, &PackConfig::default(), file_type);
 	}
 
-	println!("finished {normed_pack_name} creation: {normed_pack_name}の作成を完了しました");
+	println!("{}", msg!(FinishCreation, normed_pack_name));
 }
-/// Reads the pack info from the given pack directory path, if the pack info file exists.
-pub fn read_pack_info(pack_dir: &Path) -> Option<PackInfo> {
-	let info_path = pack_dir.join("pack_info.ron");
-	match open_file_with_read_mode(&info_path) {
-		Ok(file) => read_deserialized_value(file, FileType::Ron).ok(),
-		Err(_) => None,
-	}
+/// Returns the base path (without extension) of the pack info file in the given pack directory path.
+pub fn pack_info_base_path(pack_dir: &Path) -> PathBuf {
+	pack_dir.join("pack_info")
 }
 
-/// Reads the pack config from the given pack directory path, if the pack config file exists.
-pub fn read_pack_config(pack_dir: &Path) -> Option<PackConfig> {
-	let config_path = pack_dir.join("pack_config.ron");
-	match open_file_with_read_mode(&config_path) {
-		Ok(file) => read_deserialized_value(file, FileType::Ron).ok(),
-		Err(_) => None,
-	}
+/// Returns the base path (without extension) of the pack config file in the given pack directory path.
+pub fn pack_config_base_path(pack_dir: &Path) -> PathBuf {
+	pack_dir.join("pack_config")
 }
-/// Reads the pack settings (info and config) from the given pack directory path, if both files exist.
-pub fn read_pack_settings(pack_dir: &Path) -> Option<PackProperties> {
+
+/// Reads the pack info from the given pack directory path.
+/// The pack info file can be written in any supported file type.
+pub fn read_pack_info(pack_dir: &Path) -> DeResult<PackInfo> {
+	read_deserialized_file(&pack_info_base_path(pack_dir))
+}
+
+/// Reads the pack config from the given pack directory path.
+/// The pack config file can be written in any supported file type.
+pub fn read_pack_config(pack_dir: &Path) -> DeResult<PackConfig> {
+	read_deserialized_file(&pack_config_base_path(pack_dir))
+}
+/// Reads the pack settings (info and config) from the given pack directory path.
+/// Fails if either file is missing or malformed.
+pub fn read_pack_settings(pack_dir: &Path) -> DeResult<PackProperties> {
 	let info = read_pack_info(pack_dir)?;
 	let config = read_pack_config(pack_dir)?;
-	Some(PackProperties::new(info, config, pack_dir.to_path_buf()))
+	Ok(PackProperties::new(info, config, pack_dir.to_path_buf()))
+}
+
+#[derive(Debug, Clone)]
+/// Represents a problem found while resolving the packs specified in a recipe.
+pub enum PackError {
+	/// No pack with the name was found. Near-miss pack names are suggested.
+	NotFound(String, Vec<String>),
+	/// The directory with the requested name could not be read as a pack, and no other pack has the name.
+	Unreadable {
+		/// The pack directory.
+		directory: PathBuf,
+		/// The error message of reading `pack_info` or `pack_config`.
+		error: String,
+	},
+	/// The directory with the requested name holds a pack whose `pack_info` name is different.
+	NameMismatch {
+		/// The requested pack name.
+		requested: String,
+		/// The pack directory.
+		directory: PathBuf,
+		/// The name written in the `pack_info` of the directory.
+		actual: String,
+	},
+	/// The version in the `pack_info` is not valid semver.
+	InvalidVersion {
+		/// The pack name.
+		pack: String,
+		/// The version written in the `pack_info`.
+		version: String,
+		/// The parse error message.
+		error: String,
+	},
+	/// A version requirement in the `pack_info` is not valid.
+	InvalidVersionReq {
+		/// The pack name.
+		pack: String,
+		/// The requirement target, `nibi` or a dependency name.
+		target: String,
+		/// The requirement written in the `pack_info`.
+		req: String,
+		/// The parse error message.
+		error: String,
+	},
+	/// The running nibi does not satisfy the `nibi_version` of the pack.
+	NibiVersion {
+		/// The pack name.
+		pack: String,
+		/// The required version.
+		required: String,
+		/// The version of the running nibi.
+		actual: String,
+	},
+	/// A dependency of the pack was not found. Near-miss pack names are suggested.
+	DependencyNotFound {
+		/// The pack name.
+		pack: String,
+		/// The dependency name.
+		dependency: String,
+		/// The near-miss pack names.
+		suggestions: Vec<String>,
+	},
+	/// The version of a dependency does not satisfy the requirement of the pack.
+	DependencyVersion {
+		/// The pack name.
+		pack: String,
+		/// The dependency name.
+		dependency: String,
+		/// The required version.
+		required: String,
+		/// The version of the dependency.
+		actual: String,
+	},
 }
 
-/// Gets the packs from the given pack names and Igata directory path.
+impl std::fmt::Display for PackError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			PackError::NotFound(name, suggestions) => {
+				write!(f, "{}", msg!(PackNotFound, name, did_you_mean(suggestions)))
+			}
+			PackError::Unreadable { directory, error } => {
+				write!(f, "{}", msg!(PackUnreadable, directory.display(), error))
+			}
+			PackError::NameMismatch {
+				requested,
+				directory,
+				actual,
+			} => write!(
+				f,
+				"{}",
+				msg!(PackNameMismatch, directory.display(), actual, requested)
+			),
+			PackError::InvalidVersion {
+				pack,
+				version,
+				error,
+			} => write!(f, "{}", msg!(PackInvalidVersion, pack, version, error)),
+			PackError::InvalidVersionReq {
+				pack,
+				target,
+				req,
+				error,
+			} => write!(
+				f,
+				"{}",
+				msg!(PackInvalidVersionReq, target, pack, req, error)
+			),
+			PackError::NibiVersion {
+				pack,
+				required,
+				actual,
+			} => write!(f, "{}", msg!(PackNibiVersion, pack, required, actual)),
+			PackError::DependencyNotFound {
+				pack,
+				dependency,
+				suggestions,
+			} => write!(
+				f,
+				"{}",
+				msg!(
+					PackDependencyNotFound,
+					dependency,
+					pack,
+					did_you_mean(suggestions)
+				)
+			),
+			PackError::DependencyVersion {
+				pack,
+				dependency,
+				required,
+				actual,
+			} => write!(
+				f,
+				"{}",
+				msg!(PackDependencyVersion, pack, dependency, required, actual)
+			),
+		}
+	}
+}
+
+/// Gets the packs from the given pack names and Igata directory path, with the packs they depend on.
 /// No duplicate checking is performed.
+/// Returns the found packs and the problems for the packs which could not be resolved,
+/// including invalid versions and unsatisfied version requirements.
+pub fn get_packs_from_names(
+	pack_names: &[String],
+	igata_packs_dir: &Path,
+) -> (BTreeMap<String, PackProperties>, Vec<PackError>) {
+	let (mut packs, mut errors) = load_packs_from_names(pack_names, igata_packs_dir);
+	let mut requested: BTreeSet<String> = pack_names.iter().cloned().collect();
+
+	// 依存packを見つからなくなるまで読み込む
+	loop {
+		let mut missing: BTreeMap<String, Vec<String>> = BTreeMap::new();
+		for pack in packs.values() {
+			for dependency in pack.info.depends.keys() {
+				if !requested.contains(dependency) {
+					missing
+						.entry(dependency.clone())
+						.or_default()
+						.push(pack.info.name.clone());
+				}
+			}
+		}
+		if missing.is_empty() {
+			break;
+		}
+		let names = missing.keys().cloned().collect::<Vec<_>>();
+		requested.extend(names.iter().cloned());
+		let (found, dep_errors) = load_packs_from_names(&names, igata_packs_dir);
+		packs.extend(found);
+		for e in dep_errors {
+			match e {
+				PackError::NotFound(dependency, suggestions) => {
+					for pack in missing.get(&dependency).into_iter().flatten() {
+						errors.push(PackError::DependencyNotFound {
+							pack: pack.clone(),
+							dependency: dependency.clone(),
+							suggestions: suggestions.clone(),
+						});
+					}
+				}
+				e => errors.push(e),
+			}
+		}
+	}
+
+	errors.extend(check_pack_versions(&packs));
+	(packs, errors)
+}
+
+/// Checks the versions, the `nibi_version` requirements and the dependency requirements of the packs.
+pub fn check_pack_versions(packs: &BTreeMap<String, PackProperties>) -> Vec<PackError> {
+	let nibi_version = Version::parse(crate_version!()).ok();
+	let mut errors = Vec::new();
+	for pack in packs.values() {
+		let info = &pack.info;
+		if let Some(Err(e)) = info.parse_version() {
+			errors.push(PackError::InvalidVersion {
+				pack: info.name.clone(),
+				version: info.version.clone(),
+				error: e.to_string(),
+			});
+		}
+		match parse_version_req(&info.nibi_version) {
+			Ok(req) => {
+				if let Some(nibi_version) = &nibi_version
+					&& !req.matches(nibi_version)
+				{
+					errors.push(PackError::NibiVersion {
+						pack: info.name.clone(),
+						required: info.nibi_version.clone(),
+						actual: nibi_version.to_string(),
+					});
+				}
+			}
+			Err(e) => errors.push(PackError::InvalidVersionReq {
+				pack: info.name.clone(),
+				target: "nibi".to_string(),
+				req: info.nibi_version.clone(),
+				error: e.to_string(),
+			}),
+		}
+		for (dependency, req_str) in info.depends.iter() {
+			let req = match parse_version_req(req_str) {
+				Ok(req) => req,
+				Err(e) => {
+					errors.push(PackError::InvalidVersionReq {
+						pack: info.name.clone(),
+						target: dependency.clone(),
+						req: req_str.clone(),
+						error: e.to_string(),
+					});
+					continue;
+				}
+			};
+			// 依存packが見つからない場合は読み込み時にエラーにしている
+			let Some(dep) = packs.get(dependency) else {
+				continue;
+			};
+			let satisfied = match dep.info.parse_version() {
+				Some(Ok(version)) => req.matches(&version),
+				// バージョンが書かれていない場合は要求がなければ良しとする
+				None => req == VersionReq::STAR,
+				Some(Err(_)) => false,
+			};
+			if !satisfied {
+				errors.push(PackError::DependencyVersion {
+					pack: info.name.clone(),
+					dependency: dependency.clone(),
+					required: req_str.clone(),
+					actual: if dep.info.version.is_empty() {
+						"unversioned".to_string()
+					} else {
+						dep.info.version.clone()
+					},
+				});
+			}
+		}
+	}
+	errors
+}
+
+/// Returns the pack names in the order of priority.
+/// Each pack is followed by the packs it depends on, so a pack overrides the templates of its dependencies.
+pub fn dependency_ordered_names(
+	pack_names: &[String],
+	packs: &BTreeMap<String, PackProperties>,
+) -> Vec<String> {
+	fn visit(name: &str, packs: &BTreeMap<String, PackProperties>, ordered: &mut Vec<String>) {
+		if ordered.iter().any(|n| n == name) {
+			return;
+		}
+		ordered.push(name.to_string());
+		if let Some(pack) = packs.get(name) {
+			for dependency in pack.info.depends.keys() {
+				visit(dependency, packs, ordered);
+			}
+		}
+	}
+	let mut ordered = Vec::new();
+	for name in pack_names {
+		visit(name, packs, &mut ordered);
+	}
+	ordered
+}
+
+/// Loads the packs from the given pack names and Igata directory path.
 /// 指定されたpack名リストにあるpackを読み込む
 /// pack名の重複チェックは行わない、重複削除してある場合がおそらく一番効率がいい
-pub fn get_packs_from_names(
+fn load_packs_from_names(
 	pack_names: &[String],
 	igata_packs_dir: &Path,
-) -> BTreeMap<String, PackProperties> {
-	// 探す途中で読みだしたpackのデータをキャッシュ
+) -> (BTreeMap<String, PackProperties>, Vec<PackError>) {
+	// 探す途中で読みだしたpackのデータをキャッシュ(pack_infoに書かれた名前をキーにする)
 	let mut pack_name_cache: BTreeMap<String, PackProperties> = BTreeMap::new();
 	let mut readed_dir_names: BTreeSet<String> = BTreeSet::new();
 	// レシピで指定されたpackのデータを格納
 	let mut packs = BTreeMap::<String, PackProperties>::new();
 	let mut rest_child_dir_map: Option<BTreeMap<String, PathBuf>> = None;
+	// ディレクトリ名とそこにあったpackの名前
+	let mut dir_pack_names: BTreeMap<String, String> = BTreeMap::new();
+	// pack名のディレクトリがあるのに読めなかった場合のエラー
+	let mut read_errors: BTreeMap<String, String> = BTreeMap::new();
 
 	// 鋳型パックのディレクトリ
 	for pack_name in pack_names {
@@ -257,48 +662,171 @@ pub fn get_packs_from_names(
 		}
 		// pack名が付いたフォルダを確認する
 		let pack_name_dir = igata_packs_dir.join(pack_name);
-		if let Some(pack_properties) = read_pack_settings(&pack_name_dir) {
-			let r_pack_name = pack_properties.get_pack_name();
-			pack_name_cache.insert(pack_name.clone(), pack_properties.clone());
-			readed_dir_names.insert(pack_name.to_owned());
-			if pack_name == r_pack_name {
-				packs.insert(pack_name.clone(), pack_properties);
-			}
-		} else {
-			// 見つからない／エラーが起きた場合、他のディレクトリを漁る
-			// ここで初めて子ディレクトリの一覧を取得して代入
-			if rest_child_dir_map.is_none() {
-				match get_child_dirs(igata_packs_dir) {
-					Ok(entries) => rest_child_dir_map = Some(to_path_map(entries)),
-					Err(_) => {
-						// ここまで来てエラーであればcontinue
+		if !readed_dir_names.contains(pack_name) {
+			match read_pack_settings(&pack_name_dir) {
+				Ok(pack_properties) => {
+					let r_pack_name = pack_properties.get_pack_name().to_owned();
+					pack_name_cache.insert(r_pack_name.clone(), pack_properties.clone());
+					readed_dir_names.insert(pack_name.to_owned());
+					dir_pack_names.insert(pack_name.to_owned(), r_pack_name.clone());
+					if pack_name == &r_pack_name {
+						packs.insert(pack_name.clone(), pack_properties);
 						continue;
 					}
 				}
+				Err(e) if pack_name_dir.is_dir() => {
+					read_errors.insert(pack_name.to_owned(), e.to_string());
+				}
+				Err(_) => {}
 			}
-			// 構造的にここにNoneはこない
-			let child_map = rest_child_dir_map.as_mut().unwrap();
-			let keys = child_map.keys().cloned().collect::<Vec<_>>();
-			for key in keys {
-				if readed_dir_names.contains(&key) {
-					child_map.remove(&key);
+		}
+		// 見つからない／エラーが起きた場合、他のディレクトリを漁る
+		// ここで初めて子ディレクトリの一覧を取得して代入
+		if rest_child_dir_map.is_none() {
+			match get_child_dirs(igata_packs_dir) {
+				Ok(entries) => rest_child_dir_map = Some(to_path_map(entries)),
+				Err(_) => {
+					// ここまで来てエラーであればcontinue
 					continue;
 				}
-				// unwrapできるはずなので、できない場合はpanic直行でよい
-				let path = child_map.remove(&key).unwrap();
-				if let Some(pack_properties) = read_pack_settings(&path) {
+			}
+		}
+		// 構造的にここにNoneはこない
+		let child_map = rest_child_dir_map.as_mut().unwrap();
+		let keys = child_map.keys().cloned().collect::<Vec<_>>();
+		for key in keys {
+			// unwrapできるはずなので、できない場合はpanic直行でよい
+			let path = child_map.remove(&key).unwrap();
+			if readed_dir_names.contains(&key) {
+				continue;
+			}
+			match read_pack_settings(&path) {
+				Ok(pack_properties) => {
 					let r_pack_name = pack_properties.get_pack_name();
-					pack_name_cache.insert(r_pack_name.to_owned(), pack_properties.clone());
-					if pack_name == r_pack_name {
-						packs.insert(pack_name.clone(), pack_properties);
-					}
+					dir_pack_names.insert(key.clone(), r_pack_name.to_owned());
+					pack_name_cache
+						.entry(r_pack_name.to_owned())
+						.or_insert(pack_properties);
+				}
+				Err(e) => {
+					read_errors.insert(key.clone(), e.to_string());
 				}
-				// パック設定があってもなくても読んだのでchild_mapから削除し、読んだ一覧に入れる
-				child_map.remove(&key);
-				readed_dir_names.insert(key);
 			}
+			// パック設定があってもなくても読んだので読んだ一覧に入れる
+			readed_dir_names.insert(key);
+		}
+		if let Some(pack_properties) = pack_name_cache.get(pack_name) {
+			packs.insert(pack_name.clone(), pack_properties.clone());
 		}
 	}
 
-	packs
+	let mut errors = Vec::new();
+	for pack_name in pack_names {
+		if packs.contains_key(pack_name)
+			|| errors.iter().any(|e| match e {
+				PackError::NotFound(name, _) => name == pack_name,
+				PackError::NameMismatch { requested, .. } => requested == pack_name,
+				PackError::Unreadable { directory, .. } => directory.ends_with(pack_name),
+				_ => false,
+			}) {
+			continue;
+		}
+		match (dir_pack_names.get(pack_name), read_errors.get(pack_name)) {
+			(Some(actual), _) => errors.push(PackError::NameMismatch {
+				requested: pack_name.clone(),
+				directory: igata_packs_dir.join(pack_name),
+				actual: actual.clone(),
+			}),
+			(None, Some(error)) => errors.push(PackError::Unreadable {
+				directory: igata_packs_dir.join(pack_name),
+				error: error.clone(),
+			}),
+			(None, None) => {
+				let candidates = pack_name_cache
+					.keys()
+					.map(|s| s.as_str())
+					.chain(readed_dir_names.iter().map(|s| s.as_str()));
+				errors.push(PackError::NotFound(
+					pack_name.clone(),
+					near_miss_names(pack_name, candidates),
+				));
+			}
+		}
+	}
+
+	(packs, errors)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::testing::{TempDir, write_pack};
+
+	#[test]
+	fn test_get_packs_with_dependencies() {
+		let dir = TempDir::new("pack");
+		write_pack(
+			&dir.join("child"),
+			"child",
+			"1.0.0",
+			&[("base", "^1.2"), ("parts", "")],
+		);
+		write_pack(&dir.join("base"), "base", "1.3.0", &[("parts", "")]);
+		write_pack(&dir.join("parts"), "parts", "", &[]);
+
+		let names = vec!["child".to_string()];
+		let (packs, errors) = get_packs_from_names(&names, &dir);
+		assert!(errors.is_empty(), "{:?}", errors);
+		assert_eq!(
+			dependency_ordered_names(&names, &packs),
+			["child", "base", "parts"]
+		);
+
+		write_pack(&dir.join("base"), "base", "2.0.0", &[("prats", "")]);
+		write_pack(&dir.join("old"), "old", "1.0", &[]);
+		let names = vec!["child".to_string(), "old".to_string()];
+		let (_, errors) = get_packs_from_names(&names, &dir);
+		let messages = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+		assert_eq!(messages.len(), 3, "{messages:?}");
+		assert!(
+			messages
+				.contains(&"pack prats required by base not found (did you mean: parts?)".to_string())
+		);
+		assert!(messages.contains(&"pack child requires base ^1.2, but base is 2.0.0".to_string()));
+		assert!(
+			messages
+				.iter()
+				.any(|m| m.starts_with("invalid version of pack old"))
+		);
+	}
+
+	#[test]
+	fn test_pack_name_problems() {
+		let dir = TempDir::new("pack_names");
+		write_pack(&dir.join("theme"), "other", "1.0.0", &[]);
+		write_pack(&dir.join("broken"), "broken", "1.0.0", &[]);
+		fs::write(dir.join("broken/pack_info.ron"), "(name: ").unwrap();
+
+		let names = vec![
+			"theme".to_string(),
+			"broken".to_string(),
+			"missing".to_string(),
+		];
+		let (packs, errors) = get_packs_from_names(&names, &dir);
+		assert!(packs.is_empty());
+		assert_eq!(errors.len(), 3, "{errors:?}");
+		assert!(matches!(
+			&errors[0],
+			PackError::NameMismatch { requested, directory, actual }
+				if requested == "theme" && directory == &dir.join("theme") && actual == "other"
+		));
+		// 壊れたpack_infoは見つからないとせず、読めなかった理由を返す
+		assert!(matches!(
+			&errors[1],
+			PackError::Unreadable { directory, error }
+				if directory == &dir.join("broken") && !error.is_empty()
+		));
+		assert!(matches!(&errors[2], PackError::NotFound(name, _) if name == "missing"));
+		assert!(read_pack_info(&dir.join("broken")).is_err());
+	}
 }
@@ -0,0 +1,240 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::value::{RecipeValue, ValueMap};
+use crate::msg;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+/// Represents the type of a pack value.
+/// Values written in XML are strings, so only `any`, `string`, `list` and `map` can be set in XML.
+pub enum ValueType {
+	#[default]
+	/// Any type of value.
+	Any,
+	/// A boolean value.
+	Bool,
+	/// An integer value.
+	Int,
+	/// A floating point value. Integers are also accepted.
+	Float,
+	/// A string value.
+	String,
+	/// A list of values.
+	List,
+	/// A map of values.
+	Map,
+}
+
+impl ValueType {
+	/// Returns `true` if the value is of this type.
+	pub fn accepts(&self, value: &RecipeValue) -> bool {
+		matches!(
+			(self, value),
+			(ValueType::Any, _)
+				| (ValueType::Bool, RecipeValue::Bool(_))
+				| (ValueType::Int, RecipeValue::Int(_))
+				| (
+					ValueType::Float,
+					RecipeValue::Float(_) | RecipeValue::Int(_)
+				) | (ValueType::String, RecipeValue::String(_))
+				| (ValueType::List, RecipeValue::List(_))
+				| (ValueType::Map, RecipeValue::Map(_))
+		)
+	}
+}
+
+fn is_false(b: &bool) -> bool {
+	!b
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+/// Represents the schema of a value which a pack accepts from recipes.
+pub struct ValueSchema {
+	#[serde(rename = "type", default)]
+	/// The type of the value.
+	pub value_type: ValueType,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The default value used when the recipe does not set the value.
+	pub default: Option<RecipeValue>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	/// The allowed values. Any value of the type is allowed if empty.
+	pub allowed: Vec<RecipeValue>,
+	#[serde(default, skip_serializing_if = "is_false")]
+	/// `true` if the value must be set.
+	pub required: bool,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	/// The description of the value.
+	pub description: String,
+}
+
+impl ValueSchema {
+	/// Validates the given value against this schema.
+	pub fn validate(&self, key: &str, value: Option<&RecipeValue>) -> Option<SchemaViolation> {
+		let value = match value {
+			None | Some(RecipeValue::Null) => {
+				return (self.required && self.default.is_none())
+					.then(|| SchemaViolation::Missing(key.to_string()));
+			}
+			Some(value) => value,
+		};
+		if !self.value_type.accepts(value) {
+			return Some(SchemaViolation::TypeMismatch {
+				key: key.to_string(),
+				expected: self.value_type,
+				actual: value.type_name(),
+			});
+		}
+		if !self.allowed.is_empty() && !self.allowed.contains(value) {
+			return Some(SchemaViolation::NotAllowed {
+				key: key.to_string(),
+				value: value.clone(),
+				allowed: self.allowed.clone(),
+			});
+		}
+		None
+	}
+
+	/// Returns the documentation lines of the value with the given name.
+	pub fn describe(&self, key: &str) -> Vec<String> {
+		let mut head = format!("{key}: {}", self.value_type);
+		if self.required {
+			head.push_str(&msg!(RequiredValue));
+		}
+		if let Some(default) = &self.default {
+			head.push_str(&format!(" = {default}"));
+		}
+		let mut lines = vec![head];
+		if !self.allowed.is_empty() {
+			lines.push(msg!(
+				SchemaDescAllowed,
+				self
+					.allowed
+					.iter()
+					.map(|v| v.to_string())
+					.collect::<Vec<_>>()
+					.join(", ")
+			));
+		}
+		if !self.description.is_empty() {
+			lines.extend(self.description.lines().map(|l| format!("  {l}")));
+		}
+		lines
+	}
+}
+
+/// Value schemas of a pack, keyed by the value name.
+pub type Schema = BTreeMap<String, ValueSchema>;
+
+/// Returns the default values declared in the schema.
+pub fn schema_defaults(schema: &Schema) -> ValueMap {
+	schema
+		.iter()
+		.filter_map(|(key, s)| s.default.clone().map(|d| (key.clone(), d)))
+		.collect()
+}
+
+#[derive(Debug, Clone)]
+/// Represents a value which does not satisfy the schema.
+pub enum SchemaViolation {
+	/// A required value is not set.
+	Missing(String),
+	/// The value type does not match the schema.
+	TypeMismatch {
+		/// The value name.
+		key: String,
+		/// The type declared in the schema.
+		expected: ValueType,
+		/// The type of the actual value.
+		actual: &'static str,
+	},
+	/// The value is not one of the allowed values.
+	NotAllowed {
+		/// The value name.
+		key: String,
+		/// The actual value.
+		value: RecipeValue,
+		/// The allowed values.
+		allowed: Vec<RecipeValue>,
+	},
+}
+
+impl Display for SchemaViolation {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			SchemaViolation::Missing(key) => write!(f, "{}", msg!(ValueRequired, key)),
+			SchemaViolation::TypeMismatch {
+				key,
+				expected,
+				actual,
+			} => write!(f, "{}", msg!(ValueTypeMismatch, key, expected, actual)),
+			SchemaViolation::NotAllowed {
+				key,
+				value,
+				allowed,
+			} => write!(
+				f,
+				"{}",
+				msg!(
+					ValueNotAllowed,
+					key,
+					allowed
+						.iter()
+						.map(|v| v.to_string())
+						.collect::<Vec<_>>()
+						.join(", "),
+					value
+				)
+			),
+		}
+	}
+}
+
+/// Validates the values against the schema. Values which are not in the schema are not checked.
+pub fn validate_values(schema: &Schema, values: &ValueMap) -> Vec<SchemaViolation> {
+	schema
+		.iter()
+		.filter_map(|(key, s)| s.validate(key, values.get(key)))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::app::serde::{FileType, get_deselialized_value};
+
+	use super::*;
+
+	#[test]
+	fn test_validate_values() {
+		let schema: Schema = get_deselialized_value(
+			r#"{
+				"per_page": {"type": "int", "default": 10},
+				"theme": {"type": "string", "allowed": ["light", "dark"]},
+				"ratio": {"type": "float"},
+				"author": {"type": "string", "required": true, "description": "site author"}
+			}"#,
+			FileType::Json,
+		)
+		.unwrap();
+		assert_eq!(
+			schema_defaults(&schema).get("per_page"),
+			Some(&RecipeValue::Int(10))
+		);
+
+		let values: ValueMap = get_deselialized_value(
+			r#"{"per_page": "10", "theme": "blue", "ratio": 1}"#,
+			FileType::Json,
+		)
+		.unwrap();
+		let violations = validate_values(&schema, &values);
+		assert_eq!(violations.len(), 3);
+		assert!(matches!(&violations[0], SchemaViolation::Missing(key) if key == "author"));
+		assert!(matches!(
+			&violations[1],
+			SchemaViolation::TypeMismatch { key, expected: ValueType::Int, actual: "string" } if key == "per_page"
+		));
+		assert!(matches!(&violations[2], SchemaViolation::NotAllowed { key, .. } if key == "theme"));
+	}
+}
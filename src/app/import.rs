@@ -0,0 +1,327 @@
+use std::{
+	collections::BTreeMap,
+	fmt::Display,
+	fs,
+	io::Error as IOError,
+	path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use super::{
+	build::collect_ingot_paths,
+	config::Config,
+	fs::io::{open_file_with_overwrite_mode, write_str},
+	ingot::{Ingot, ingot::RKeyRaw, writer::ingot_to_string},
+	serde::{
+		FileType, SerError, find_path_with_file_type, get_extended_path, get_serialized_string,
+		read_deserialized_file,
+	},
+};
+use crate::msg;
+
+/// hugo module
+pub mod hugo;
+/// ingots module
+pub mod ingots;
+/// jekyll module
+pub mod jekyll;
+/// `markdown_site` module
+pub mod markdown_site;
+/// wordpress module
+pub mod wordpress;
+
+#[derive(Debug)]
+/// Represents an error while importing contents from other systems.
+pub enum ImportError {
+	/// Failed to read the source file.
+	Read(PathBuf, IOError),
+	/// The source is not a valid document. The position is the byte offset in the source.
+	Parse(u64, String),
+	/// Failed to serialize the list file.
+	Serialize(PathBuf, SerError),
+	/// The files already exist. They are replaced only when forced.
+	Exists(Vec<PathBuf>),
+	/// Failed to write the file.
+	Write(PathBuf, IOError),
+}
+
+impl Display for ImportError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ImportError::Read(path, e) => write!(f, "{}", msg!(ImportReadFailed, path.display(), e)),
+			ImportError::Parse(pos, detail) => write!(f, "{}", msg!(InvalidDocument, pos, detail)),
+			ImportError::Serialize(path, e) => {
+				write!(f, "{}", msg!(SerializeFileFailed, path.display(), e))
+			}
+			ImportError::Exists(paths) => {
+				write!(f, "{}", msg!(FilesExist, paths.len()))?;
+				for path in paths.iter() {
+					write!(f, "\n\t{}", path.display())?;
+				}
+				Ok(())
+			}
+			ImportError::Write(path, e) => write!(f, "{}", msg!(WriteFailed, path.display(), e)),
+		}
+	}
+}
+
+#[derive(Debug)]
+/// Represents the destination directories of an import.
+pub struct ImportDest {
+	/// The zairyo directory, for ingots and list files.
+	pub zairyo_dir: PathBuf,
+	/// The resources directory, for static assets such as images.
+	pub resources_dir: PathBuf,
+}
+
+impl ImportDest {
+	/// Returns the destination directories of the project.
+	pub fn from_config(config: &Config, proj_path: &Path) -> Self {
+		let dir_conf = config.get_dir_conf();
+		Self {
+			zairyo_dir: dir_conf.get_zairyo_path(proj_path),
+			resources_dir: dir_conf.get_resources_path(proj_path),
+		}
+	}
+}
+
+#[derive(Debug, Default)]
+/// Represents the files to be written by an import.
+/// The paths of files are relative to the zairyo directory, and the paths of resources are relative to the resources directory.
+pub struct ImportFiles {
+	files: Vec<(PathBuf, String)>,
+	resources: Vec<(PathBuf, PathBuf)>,
+	extended: Vec<PathBuf>,
+	/// The items which were not imported, with the reasons.
+	pub skipped: Vec<String>,
+	/// The files which were rejected as invalid, with the reasons.
+	pub rejected: Vec<(PathBuf, String)>,
+}
+
+impl ImportFiles {
+	/// Adds the file with the contents as is.
+	pub fn push_file(&mut self, rel_path: PathBuf, body: String) {
+		self.files.push((rel_path, body));
+	}
+	/// Adds the resource file copied from the source path.
+	pub fn push_resource(&mut self, src_path: PathBuf, rel_path: PathBuf) {
+		self.resources.push((src_path, rel_path));
+	}
+	/// Records the rejected file.
+	pub fn reject(&mut self, path: PathBuf, reason: String) {
+		self.rejected.push((path, reason));
+	}
+	/// Adds the ingot file.
+	pub fn push_ingot(&mut self, rel_path: PathBuf, ingot: &Ingot) {
+		self.files.push((rel_path, ingot_to_string(ingot)));
+	}
+	/// Adds the list file serialized in the given file type.
+	/// The extension is appended to the base path.
+	pub fn push_list<T: Serialize>(
+		&mut self,
+		rel_base_path: &Path,
+		value: &T,
+		file_type: FileType,
+	) -> Result<(), ImportError> {
+		let path = get_extended_path(rel_base_path, file_type);
+		let body = get_serialized_string(value, file_type)
+			.map_err(|e| ImportError::Serialize(path.clone(), e))?;
+		self.files.push((path, body));
+		Ok(())
+	}
+	/// Adds the list file which extends the existing list in the zairyo directory with the items.
+	/// The existing file is replaced in its own file type and is not regarded as a collision.
+	/// If the list does not exist, the items are added as a new list file in the given file type.
+	pub fn push_extended_list<T: Serialize + for<'de> Deserialize<'de>>(
+		&mut self,
+		zairyo_dir: &Path,
+		rel_base_path: &Path,
+		items: Vec<T>,
+		file_type: FileType,
+	) -> Result<(), ImportError> {
+		if items.is_empty() {
+			return Ok(());
+		}
+		let existing =
+			find_path_with_file_type(&zairyo_dir.join(rel_base_path)).and_then(|(path, file_type)| {
+				let list: Vec<T> = read_deserialized_file(&path).ok()?;
+				Some((path, file_type, list))
+			});
+		match existing {
+			Some((path, existing_type, mut list)) => {
+				list.extend(items);
+				let rel_path = path.strip_prefix(zairyo_dir).unwrap_or(&path).to_path_buf();
+				let body = get_serialized_string(&list, existing_type)
+					.map_err(|e| ImportError::Serialize(path.clone(), e))?;
+				self.files.push((rel_path.clone(), body));
+				self.extended.push(rel_path);
+				Ok(())
+			}
+			// 読めない既存のリストは置き換えの対象として扱う
+			None => self.push_list(rel_base_path, &items, file_type),
+		}
+	}
+	/// Returns the paths which would be replaced by writing into the destination.
+	/// A list file is also regarded as existing if it exists with another file type extension.
+	pub fn collisions(&self, dest: &ImportDest) -> Vec<PathBuf> {
+		let files = self.files.iter().filter_map(|(path, _)| {
+			if self.extended.contains(path) {
+				return None;
+			}
+			let path = dest.zairyo_dir.join(path);
+			if path.exists() {
+				return Some(path);
+			}
+			if path.extension().is_some_and(|ext| ext != "ingot") {
+				return find_path_with_file_type(&path.with_extension("")).map(|(p, _)| p);
+			}
+			None
+		});
+		let resources = self
+			.resources
+			.iter()
+			.map(|(_, path)| dest.resources_dir.join(path))
+			.filter(|path| path.exists());
+		files.chain(resources).collect()
+	}
+	/// Writes the files into the destination.
+	/// Nothing is written if some files already exist and `force` is false.
+	pub fn write(self, dest: &ImportDest, force: bool) -> Result<ImportReport, ImportError> {
+		if !force {
+			let collisions = self.collisions(dest);
+			if !collisions.is_empty() {
+				return Err(ImportError::Exists(collisions));
+			}
+		}
+		let mut report = ImportReport {
+			skipped: self.skipped,
+			rejected: self.rejected,
+			..Default::default()
+		};
+		for (rel_path, body) in self.files {
+			let path = dest.zairyo_dir.join(&rel_path);
+			create_parent_dir(&path)?;
+			open_file_with_overwrite_mode(&path)
+				.and_then(|file| write_str(file, &body))
+				.map_err(|e| ImportError::Write(path.clone(), e))?;
+			if rel_path.extension().is_some_and(|ext| ext == "ingot") {
+				report.ingots.push(path);
+			} else {
+				report.files.push(path);
+			}
+		}
+		for (src_path, rel_path) in self.resources {
+			let path = dest.resources_dir.join(&rel_path);
+			create_parent_dir(&path)?;
+			fs::copy(&src_path, &path).map_err(|e| ImportError::Write(path.clone(), e))?;
+			report.resources.push(path);
+		}
+		Ok(report)
+	}
+}
+
+fn create_parent_dir(path: &Path) -> Result<(), ImportError> {
+	match path.parent() {
+		Some(parent) => {
+			fs::create_dir_all(parent).map_err(|e| ImportError::Write(parent.to_path_buf(), e))
+		}
+		None => Ok(()),
+	}
+}
+
+#[derive(Debug, Default)]
+/// Represents the result of an import.
+pub struct ImportReport {
+	/// The written ingot files.
+	pub ingots: Vec<PathBuf>,
+	/// The written list files.
+	pub files: Vec<PathBuf>,
+	/// The copied resource files.
+	pub resources: Vec<PathBuf>,
+	/// The items which were not imported, with the reasons.
+	pub skipped: Vec<String>,
+	/// The files which were rejected as invalid, with the reasons.
+	pub rejected: Vec<(PathBuf, String)>,
+}
+
+/// Returns the IDs of the ingots already in the zairyo directory, with their paths.
+pub fn existing_ingot_ids(zairyo_dir: &Path) -> BTreeMap<usize, PathBuf> {
+	collect_ingot_paths(zairyo_dir)
+		.into_iter()
+		.filter_map(|path| {
+			let ingot = Ingot::read(fs::File::open(&path).ok()?).ok()?;
+			Some((ingot.id, path))
+		})
+		.collect()
+}
+
+/// Returns the files under the directory, sorted by file name.
+/// Nothing is returned if the directory does not exist.
+pub fn walk_files(dir: &Path) -> impl Iterator<Item = PathBuf> {
+	WalkDir::new(dir)
+		.sort_by_file_name()
+		.into_iter()
+		.filter_map(|e| e.ok())
+		.filter(|e| e.file_type().is_file())
+		.map(|e| e.into_path())
+}
+
+/// Returns the key to refer the term from ingots.
+/// The ID is used when the path name can not be distinguished from an ID.
+pub fn term_key(id: usize, path_name: &str) -> RKeyRaw {
+	match RKeyRaw::from(path_name.to_string()) {
+		RKeyRaw::String(s) if !s.is_empty() => RKeyRaw::String(s),
+		_ => RKeyRaw::Usize(id),
+	}
+}
+
+/// Collapses the runs of blank lines outside of fenced code blocks into one blank line,
+/// because two or more blank lines start the back matter of ingots.
+pub fn collapse_blank_lines(content: &str) -> String {
+	let mut result = String::with_capacity(content.len());
+	let mut blank = 0;
+	let mut fence: Option<&str> = None;
+	for line in content.trim().lines() {
+		let trimmed = line.trim_start();
+		match fence {
+			Some(f) if trimmed.starts_with(f) => fence = None,
+			Some(_) => {}
+			None => {
+				if trimmed.starts_with("```") {
+					fence = Some("```");
+				} else if trimmed.starts_with("~~~") {
+					fence = Some("~~~");
+				}
+			}
+		}
+		if fence.is_none() && line.trim().is_empty() {
+			blank += 1;
+			if blank > 1 {
+				continue;
+			}
+			result.push('\n');
+		} else {
+			blank = 0;
+			result.push_str(line.trim_end());
+			result.push('\n');
+		}
+	}
+	result
+}
+
+/// Returns the string usable as a file name, keeping non-ASCII characters.
+pub fn file_name_safe(s: &str) -> String {
+	s.chars()
+		.map(|c| {
+			if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+				c
+			} else {
+				'-'
+			}
+		})
+		.collect::<String>()
+		.trim_matches(['-', '.'])
+		.to_string()
+}
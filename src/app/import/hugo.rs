@@ -0,0 +1,185 @@
+use std::path::Path;
+
+use super::{
+	ImportDest, ImportError, ImportFiles, ImportReport,
+	markdown_site::{ContentFile, SiteConverter},
+	walk_files,
+};
+use crate::app::{fs::io::read_all, ingot::ingot::To, serde::FileType};
+use crate::msg;
+
+/// The extensions of the content files of Hugo.
+pub const HUGO_CONTENT_EXTS: [&str; 3] = ["md", "markdown", "html"];
+
+fn is_content_file(path: &Path) -> bool {
+	path.extension().is_some_and(|ext| {
+		HUGO_CONTENT_EXTS.contains(&ext.to_string_lossy().to_ascii_lowercase().as_str())
+	})
+}
+
+/// Converts the Hugo site into the files to be written.
+///
+/// Content files under `content/` are converted into ingots under `hugo/`.
+/// Files directly under `content/` become pages and files in sections become posts,
+/// and the path name follows the default permalink of Hugo, such as `posts/hello`.
+/// `_index` of the site root becomes the top page, and other list pages are skipped.
+/// Files in `static/` and the resources of page bundles are copied into the resources directory.
+pub fn convert_hugo(
+	src_dir: &Path,
+	zairyo_dir: &Path,
+	file_type: FileType,
+) -> Result<ImportFiles, ImportError> {
+	let content_dir = src_dir.join("content");
+	if !content_dir.is_dir() {
+		return Err(ImportError::Read(
+			content_dir,
+			std::io::Error::new(std::io::ErrorKind::NotFound, msg!(NotHugoSite)),
+		));
+	}
+	let mut converter = SiteConverter::new(zairyo_dir);
+
+	for path in walk_files(&content_dir) {
+		let rel_path = path
+			.strip_prefix(&content_dir)
+			.unwrap_or(&path)
+			.to_path_buf();
+		let stem = path
+			.file_stem()
+			.map(|s| s.to_string_lossy().to_string())
+			.unwrap_or_default();
+		let dir = rel_path.parent().unwrap_or(Path::new("")).to_path_buf();
+		if !is_content_file(&path) {
+			// ページバンドルのリソースはページと同じ位置に置く
+			converter.files_mut().push_resource(path, rel_path);
+			continue;
+		}
+		let (to, default_pname) = match stem.as_str() {
+			"_index" if dir.as_os_str().is_empty() => (To::Top, String::new()),
+			"_index" => {
+				converter
+					.files_mut()
+					.skipped
+					.push(msg!(ListPage, path.display()));
+				continue;
+			}
+			// ページバンドルはディレクトリ名を使う
+			"index" => (To::Post, dir.to_string_lossy().replace('\\', "/")),
+			_ => {
+				let pname = dir.join(&stem).to_string_lossy().replace('\\', "/");
+				if dir.as_os_str().is_empty() {
+					(To::Page, pname)
+				} else {
+					(To::Post, pname)
+				}
+			}
+		};
+		let src = match read_all(&path) {
+			Ok(src) => src,
+			Err(e) => {
+				converter.files_mut().reject(path, e.to_string());
+				continue;
+			}
+		};
+		let file = ContentFile {
+			rel_path: Path::new("hugo").join(rel_path.with_extension("ingot")),
+			path,
+			to,
+			default_pname,
+			default_date: None,
+			draft: false,
+		};
+		converter.convert(file, &src);
+	}
+
+	let static_dir = src_dir.join("static");
+	for path in walk_files(&static_dir) {
+		let rel_path = path
+			.strip_prefix(&static_dir)
+			.unwrap_or(&path)
+			.to_path_buf();
+		converter.files_mut().push_resource(path, rel_path);
+	}
+	converter.finish(file_type)
+}
+
+/// Imports the Hugo site into the destination.
+/// Nothing is written if some files already exist and `force` is false.
+pub fn import_hugo(
+	src_dir: &Path,
+	dest: &ImportDest,
+	file_type: FileType,
+	force: bool,
+) -> Result<ImportReport, ImportError> {
+	convert_hugo(src_dir, &dest.zairyo_dir, file_type)?.write(dest, force)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::ingot::Ingot;
+	use crate::app::testing::TempDir;
+
+	#[test]
+	fn test_import_hugo() {
+		let dir = TempDir::new("hugo");
+		let src = dir.join("src");
+		for d in ["content/posts/trip", "static/css"] {
+			std::fs::create_dir_all(src.join(d)).unwrap();
+		}
+		std::fs::write(
+			src.join("content/_index.md"),
+			"+++\ntitle = \"Home\"\n+++\nwelcome\n",
+		)
+		.unwrap();
+		std::fs::write(
+			src.join("content/posts/_index.md"),
+			"+++\ntitle = \"Posts\"\n+++\n",
+		)
+		.unwrap();
+		std::fs::write(
+			src.join("content/posts/hello.md"),
+			"---\ntitle: Hello\ndate: 2020-01-02\n---\nhello\n",
+		)
+		.unwrap();
+		std::fs::write(
+			src.join("content/posts/trip/index.md"),
+			"---\ntitle: Trip\n---\n![photo](photo.jpg)\n",
+		)
+		.unwrap();
+		std::fs::write(src.join("content/posts/trip/photo.jpg"), [0u8, 1, 2]).unwrap();
+		std::fs::write(
+			src.join("content/about.md"),
+			"---\ntitle: About\n---\nabout\n",
+		)
+		.unwrap();
+		std::fs::write(src.join("static/css/site.css"), "body {}").unwrap();
+
+		let dest = ImportDest {
+			zairyo_dir: dir.join("zairyo"),
+			resources_dir: dir.join("resources"),
+		};
+		let report = import_hugo(&src, &dest, FileType::Ron, false).unwrap();
+		assert_eq!(report.ingots.len(), 4);
+		assert_eq!(report.skipped.len(), 1);
+		assert!(report.skipped[0].contains("_index.md"));
+		assert_eq!(report.resources.len(), 2);
+		assert!(dest.resources_dir.join("posts/trip/photo.jpg").is_file());
+		assert!(dest.resources_dir.join("css/site.css").is_file());
+
+		let read =
+			|p: &str| Ingot::read(std::fs::File::open(dest.zairyo_dir.join(p)).unwrap()).unwrap();
+		let top = read("hugo/_index.ingot");
+		assert!(matches!(top.to, To::Top));
+		assert_eq!(top.title, "Home");
+		assert!(!dest.zairyo_dir.join("hugo/posts/_index.ingot").exists());
+		let hello = read("hugo/posts/hello.ingot");
+		assert!(matches!(hello.to, To::Post));
+		assert_eq!(hello.pname, "posts/hello");
+		let trip = read("hugo/posts/trip/index.ingot");
+		assert!(matches!(trip.to, To::Post));
+		assert_eq!(trip.pname, "posts/trip");
+		let about = read("hugo/about.ingot");
+		assert!(matches!(about.to, To::Page));
+		assert_eq!(about.pname, "about");
+	}
+}
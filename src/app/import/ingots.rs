@@ -0,0 +1,218 @@
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use super::{ImportDest, ImportError, ImportFiles, ImportReport, existing_ingot_ids};
+use crate::app::{
+	author::{Author, authors_file_base_path},
+	category::{Category, categories_file_base_path},
+	fs::io::read_all,
+	ingot::parser::{IngotParser, MATTER_KEYS},
+	serde::{DeResult, find_path_with_file_type, read_deserialized_file},
+	tag::{Tag, tags_file_base_path},
+};
+use crate::msg;
+
+/// The extensions of the files converted into ingots, besides `.ingot`.
+pub const CONVERTIBLE_EXTS: [&str; 3] = ["md", "markdown", "txt"];
+
+/// Returns `true` if the first line of the source is a `key: value` line of the front matter with a known key.
+/// Lines such as `Note: ...` are the content.
+fn starts_with_matter(src: &str) -> bool {
+	let line = src.trim_start().lines().next().unwrap_or_default();
+	line
+		.split_once(':')
+		.is_some_and(|(key, _)| MATTER_KEYS.contains(&key.trim_end()))
+}
+
+/// Adds the list file in the source directory as is, if it exists and is valid.
+fn push_list_file<T: for<'de> serde::Deserialize<'de>>(
+	files: &mut ImportFiles,
+	src_dir: &Path,
+	base_path: fn(&Path) -> PathBuf,
+) {
+	let Some((path, _)) = find_path_with_file_type(&base_path(src_dir)) else {
+		return;
+	};
+	let result: DeResult<T> = read_deserialized_file(&path);
+	match result {
+		Ok(_) => match read_all(&path) {
+			Ok(body) => {
+				let rel_path = path.strip_prefix(src_dir).unwrap_or(&path);
+				files.push_file(rel_path.to_path_buf(), body);
+			}
+			Err(e) => files.reject(path, e.to_string()),
+		},
+		Err(e) => files.reject(path, e.to_string()),
+	}
+}
+
+/// Collects the ingots in the directory into the files to be written into the zairyo directory.
+///
+/// `.ingot` files are copied as they are, and `.md`, `.markdown` and `.txt` files are converted into `.ingot` files.
+/// Every file is validated with `IngotParser`, and the files without a title or with a duplicate ID are rejected.
+/// Files without an ID are converted with a new ID following the existing ones.
+/// The tags, categories and authors list files in the directory are copied if they are valid.
+pub fn convert_ingots_dir(src_dir: &Path, zairyo_dir: &Path) -> ImportFiles {
+	let mut files = ImportFiles::default();
+	let mut ids = existing_ingot_ids(zairyo_dir);
+	let mut without_id = Vec::new();
+
+	let paths = WalkDir::new(src_dir)
+		.sort_by_file_name()
+		.into_iter()
+		.filter_map(|e| e.ok())
+		.filter(|e| e.file_type().is_file())
+		.map(|e| e.into_path());
+	for path in paths {
+		let ext = path
+			.extension()
+			.map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+			.unwrap_or_default();
+		if ext != "ingot" && !CONVERTIBLE_EXTS.contains(&ext.as_str()) {
+			continue;
+		}
+		let rel_path = path.strip_prefix(src_dir).unwrap_or(&path).to_path_buf();
+		let body = match read_all(&path) {
+			Ok(body) => body,
+			Err(e) => {
+				files.reject(path, e.to_string());
+				continue;
+			}
+		};
+		if body.trim().is_empty() {
+			files.reject(path, msg!(FileEmpty));
+			continue;
+		}
+		// フロントマターのないMarkdownは、先頭行がフロントマターとして読まれないよう空のフロントマターを補う
+		let body = if ext != "ingot" && !starts_with_matter(&body) {
+			format!("\n\n{body}")
+		} else {
+			body
+		};
+		let mut ingot = match IngotParser::parse_str(&body) {
+			Ok(ingot) => ingot,
+			Err(e) => {
+				files.reject(path, e.to_string());
+				continue;
+			}
+		};
+		if ext != "ingot"
+			&& let Some(title) = ingot.title.strip_prefix("# ")
+		{
+			// Markdownの見出しをタイトルとして使う
+			ingot.title = title.trim().to_string();
+		}
+		if ingot.title.trim().is_empty() {
+			files.reject(path, msg!(TitleNotFound));
+			continue;
+		}
+		if ingot.id == 0 {
+			// IDのない生地は、ID付きの生地をすべて確認してから採番する
+			without_id.push((rel_path, ingot));
+			continue;
+		}
+		let dest_rel_path = rel_path.with_extension("ingot");
+		// 同じ生地の再インポートは上書きとして扱う
+		if let Some(other) = ids
+			.get(&ingot.id)
+			.filter(|other| **other != zairyo_dir.join(&dest_rel_path))
+		{
+			let reason = msg!(IdAlreadyUsed, ingot.id, other.display());
+			files.reject(path, reason);
+			continue;
+		}
+		ids.insert(ingot.id, path.clone());
+		if ext == "ingot" {
+			files.push_file(dest_rel_path, body);
+		} else {
+			files.push_ingot(dest_rel_path, &ingot);
+		}
+	}
+
+	let next_id = ids.keys().next_back().copied().unwrap_or_default() + 1;
+	for (id, (rel_path, mut ingot)) in (next_id..).zip(without_id) {
+		ingot.id = id;
+		files.push_ingot(rel_path.with_extension("ingot"), &ingot);
+	}
+
+	push_list_file::<Vec<Tag>>(&mut files, src_dir, tags_file_base_path);
+	push_list_file::<Vec<Category>>(&mut files, src_dir, categories_file_base_path);
+	push_list_file::<Vec<Author>>(&mut files, src_dir, authors_file_base_path);
+	files
+}
+
+/// Imports the ingots in the directory into the zairyo directory.
+/// Nothing is written if some files already exist and `force` is false.
+pub fn import_ingots_dir(
+	src_dir: &Path,
+	dest: &ImportDest,
+	force: bool,
+) -> Result<ImportReport, ImportError> {
+	if !src_dir.is_dir() {
+		return Err(ImportError::Read(
+			src_dir.to_path_buf(),
+			std::io::Error::new(std::io::ErrorKind::NotADirectory, "not a directory"),
+		));
+	}
+	convert_ingots_dir(src_dir, &dest.zairyo_dir).write(dest, force)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::ingot::Ingot;
+	use crate::app::testing::TempDir;
+
+	#[test]
+	fn test_convert_ingots_dir() {
+		let dir = TempDir::new("import_ingots");
+		let src = dir.join("src");
+		let zairyo = dir.join("zairyo");
+		std::fs::create_dir_all(src.join("sub")).unwrap();
+		std::fs::create_dir_all(&zairyo).unwrap();
+		std::fs::write(zairyo.join("old.ingot"), "id: 3\n\nOld\n\nold\n").unwrap();
+		std::fs::write(src.join("a.ingot"), "id: 5\ntags: [rust]\n\nA\n\nbody\n").unwrap();
+		std::fs::write(src.join("dup.ingot"), "id: 3\n\nDup\n\nbody\n").unwrap();
+		std::fs::write(src.join("empty.ingot"), "\n\n").unwrap();
+		std::fs::write(src.join("sub/b.md"), "# B\n\nbody of b\n").unwrap();
+		std::fs::write(src.join("c.txt"), "status: publish\n\nC\n\nbody of c\n").unwrap();
+		std::fs::write(src.join("sub/note.md"), "Note: first line\n\nbody of d\n").unwrap();
+		std::fs::write(src.join("skip.html"), "<p></p>").unwrap();
+		std::fs::write(
+			src.join("tags.ron"),
+			"[(id: 1, name: \"Rust\", path_name: \"rust\", description: \"\")]",
+		)
+		.unwrap();
+		std::fs::write(src.join("categories.json"), "{").unwrap();
+
+		let dest = ImportDest {
+			zairyo_dir: zairyo.clone(),
+			resources_dir: dir.join("resources"),
+		};
+		let report = import_ingots_dir(&src, &dest, false).unwrap();
+		assert_eq!(report.ingots.len(), 4);
+		assert_eq!(report.files, [zairyo.join("tags.ron")]);
+		assert_eq!(report.rejected.len(), 3);
+		assert_eq!(
+			std::fs::read_to_string(zairyo.join("a.ingot")).unwrap(),
+			"id: 5\ntags: [rust]\n\nA\n\nbody\n"
+		);
+		let b = Ingot::read(std::fs::File::open(zairyo.join("sub/b.ingot")).unwrap()).unwrap();
+		assert_eq!(b.id, 7);
+		assert_eq!(b.title, "B");
+		assert_eq!(b.content, "body of b\n");
+		let c = Ingot::read(std::fs::File::open(zairyo.join("c.ingot")).unwrap()).unwrap();
+		assert_eq!(c.id, 6);
+		assert_eq!(c.title, "C");
+		// 既知のキーでない先頭行は本文として読む
+		let d = Ingot::read(std::fs::File::open(zairyo.join("sub/note.ingot")).unwrap()).unwrap();
+		assert_eq!(d.title, "Note: first line");
+		assert_eq!(d.content, "body of d\n");
+
+		assert!(matches!(
+			import_ingots_dir(&src, &dest, false),
+			Err(ImportError::Exists(_))
+		));
+	}
+}
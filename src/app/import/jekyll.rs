@@ -0,0 +1,225 @@
+use std::path::{Component, Path};
+
+use jiff::{Timestamp, civil::Date, tz::TimeZone};
+
+use super::{
+	ImportDest, ImportError, ImportFiles, ImportReport,
+	markdown_site::{ContentFile, SiteConverter, split_front_matter},
+	walk_files,
+};
+use crate::app::{fs::io::read_all, ingot::ingot::To, serde::FileType};
+use crate::msg;
+
+/// The extensions of the pages of Jekyll.
+pub const JEKYLL_PAGE_EXTS: [&str; 3] = ["md", "markdown", "html"];
+
+/// The names which Jekyll excludes from the site by default.
+pub const JEKYLL_EXCLUDES: [&str; 5] = [
+	"Gemfile",
+	"Gemfile.lock",
+	"node_modules",
+	"vendor",
+	"package.json",
+];
+
+/// Splits the file stem of a post, such as `2020-01-02-hello`, into the date and the slug.
+fn split_post_name(stem: &str) -> (Option<Timestamp>, &str) {
+	if let Some(date) = stem.get(..10)
+		&& let Ok(date) = date.parse::<Date>()
+		&& let Some(slug) = stem.get(10..).and_then(|s| s.strip_prefix('-'))
+	{
+		let ts = date.to_zoned(TimeZone::UTC).ok().map(|z| z.timestamp());
+		return (ts, slug);
+	}
+	(None, stem)
+}
+
+/// Returns `true` if the path relative to the site root is excluded from the site.
+fn is_excluded(rel_path: &Path) -> bool {
+	rel_path.components().any(|c| match c {
+		Component::Normal(name) => {
+			let name = name.to_string_lossy();
+			name.starts_with('_') || name.starts_with('.') || JEKYLL_EXCLUDES.contains(&name.as_ref())
+		}
+		_ => false,
+	})
+}
+
+fn is_page_ext(path: &Path) -> bool {
+	path.extension().is_some_and(|ext| {
+		JEKYLL_PAGE_EXTS.contains(&ext.to_string_lossy().to_ascii_lowercase().as_str())
+	})
+}
+
+/// Converts the Jekyll site into the files to be written.
+///
+/// Posts in `_posts/` and `_drafts/` are converted into posts under `jekyll/posts/`,
+/// taking the date and the slug from the file names such as `2020-01-02-hello.md`.
+/// Markdown and HTML files with front matter elsewhere become pages, and `index` of the site root becomes the top page.
+/// Other files, except the ones in directories starting with `_` or `.`, are copied into the resources directory.
+pub fn convert_jekyll(
+	src_dir: &Path,
+	zairyo_dir: &Path,
+	file_type: FileType,
+) -> Result<ImportFiles, ImportError> {
+	if !src_dir.join("_posts").is_dir() && !src_dir.join("_config.yml").is_file() {
+		return Err(ImportError::Read(
+			src_dir.to_path_buf(),
+			std::io::Error::new(std::io::ErrorKind::NotFound, msg!(NotJekyllSite)),
+		));
+	}
+	let mut converter = SiteConverter::new(zairyo_dir);
+
+	for (dir_name, draft) in [("_posts", false), ("_drafts", true)] {
+		let posts_dir = src_dir.join(dir_name);
+		for path in walk_files(&posts_dir).filter(|p| is_page_ext(p)) {
+			let stem = path
+				.file_stem()
+				.map(|s| s.to_string_lossy().to_string())
+				.unwrap_or_default();
+			let (date, slug) = split_post_name(&stem);
+			let src = match read_all(&path) {
+				Ok(src) => src,
+				Err(e) => {
+					converter.files_mut().reject(path, e.to_string());
+					continue;
+				}
+			};
+			let file_name = format!("{stem}.ingot");
+			let file = ContentFile {
+				rel_path: Path::new("jekyll").join("posts").join(file_name),
+				to: To::Post,
+				default_pname: slug.to_string(),
+				default_date: date,
+				draft,
+				path,
+			};
+			converter.convert(file, &src);
+		}
+	}
+
+	for path in walk_files(src_dir) {
+		let rel_path = path.strip_prefix(src_dir).unwrap_or(&path).to_path_buf();
+		if is_excluded(&rel_path) {
+			continue;
+		}
+		if is_page_ext(&path) {
+			let src = match read_all(&path) {
+				Ok(src) => src,
+				Err(e) => {
+					converter.files_mut().reject(path, e.to_string());
+					continue;
+				}
+			};
+			// フロントマターのあるファイルだけがページとして処理される
+			if split_front_matter(&src).is_some() {
+				let pname = rel_path
+					.with_extension("")
+					.to_string_lossy()
+					.replace('\\', "/");
+				let to = if pname == "index" { To::Top } else { To::Page };
+				let file = ContentFile {
+					rel_path: Path::new("jekyll").join(rel_path.with_extension("ingot")),
+					to,
+					default_pname: pname.trim_end_matches("/index").to_string(),
+					default_date: None,
+					draft: false,
+					path,
+				};
+				converter.convert(file, &src);
+				continue;
+			}
+		}
+		converter.files_mut().push_resource(path, rel_path);
+	}
+	converter.finish(file_type)
+}
+
+/// Imports the Jekyll site into the destination.
+/// Nothing is written if some files already exist and `force` is false.
+pub fn import_jekyll(
+	src_dir: &Path,
+	dest: &ImportDest,
+	file_type: FileType,
+	force: bool,
+) -> Result<ImportReport, ImportError> {
+	convert_jekyll(src_dir, &dest.zairyo_dir, file_type)?.write(dest, force)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::testing::TempDir;
+	use crate::app::{
+		ingot::{Ingot, ingot::Status},
+		tag::get_tags_from_dir_path,
+	};
+
+	#[test]
+	fn test_import_jekyll() {
+		let dir = TempDir::new("jekyll");
+		let src = dir.join("src");
+		for d in ["_posts", "_drafts", "_layouts", "assets"] {
+			std::fs::create_dir_all(src.join(d)).unwrap();
+		}
+		std::fs::write(src.join("_config.yml"), "title: x\n").unwrap();
+		std::fs::write(
+			src.join("_posts/2020-01-02-hello.md"),
+			"---\ntitle: Hello\ntags: rust nibi\ncategories: [Dev]\n---\nbody\n\n\n\n```\na\n\n\nb\n```\n",
+		)
+		.unwrap();
+		std::fs::write(
+			src.join("_drafts/wip.md"),
+			"---\ntitle: WIP\ntags: [Rust]\n---\nwip\n",
+		)
+		.unwrap();
+		std::fs::write(src.join("_layouts/default.html"), "{{ content }}").unwrap();
+		std::fs::write(
+			src.join("about.md"),
+			"---\npermalink: /about-us/\n---\nabout\n",
+		)
+		.unwrap();
+		std::fs::write(src.join("README.md"), "readme\n").unwrap();
+		std::fs::write(src.join("assets/a.png"), [0u8, 1, 2]).unwrap();
+		std::fs::write(src.join("Gemfile"), "").unwrap();
+
+		let dest = ImportDest {
+			zairyo_dir: dir.join("zairyo"),
+			resources_dir: dir.join("resources"),
+		};
+		std::fs::create_dir_all(&dest.zairyo_dir).unwrap();
+		std::fs::write(
+			dest.zairyo_dir.join("tags.ron"),
+			"[(id: 5, name: \"Rust\", path_name: \"rust\", description: \"\")]",
+		)
+		.unwrap();
+		let report = import_jekyll(&src, &dest, FileType::Ron, false).unwrap();
+		assert_eq!(report.ingots.len(), 3);
+		assert_eq!(report.resources.len(), 2);
+		assert!(dest.resources_dir.join("assets/a.png").is_file());
+
+		let read =
+			|p: &str| Ingot::read(std::fs::File::open(dest.zairyo_dir.join(p)).unwrap()).unwrap();
+		let hello = read("jekyll/posts/2020-01-02-hello.ingot");
+		assert_eq!(hello.id, 1);
+		assert_eq!(hello.pname, "hello");
+		assert_eq!(hello.published.to_string(), "2020-01-02T00:00:00Z");
+		assert!(matches!(hello.status, Status::Publish));
+		assert_eq!(hello.content, "body\n\n```\na\n\n\nb\n```");
+		let wip = read("jekyll/posts/wip.ingot");
+		assert!(matches!(wip.status, Status::Draft));
+		let about = read("jekyll/about.ingot");
+		assert!(matches!(about.to, To::Page));
+		assert_eq!(about.pname, "about-us");
+		assert_eq!(about.title, "about");
+
+		let tags = get_tags_from_dir_path(&dest.zairyo_dir).unwrap();
+		assert_eq!(
+			tags
+				.iter()
+				.map(|t| (t.id, t.path_name.as_str()))
+				.collect::<Vec<_>>(),
+			[(5, "rust"), (6, "nibi")]
+		);
+	}
+}
@@ -0,0 +1,465 @@
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+};
+
+use jiff::{Timestamp, civil::Date, civil::DateTime, fmt::strtime, tz::TimeZone};
+use serde_json::{Map, Value as JsonValue};
+
+use super::{ImportError, ImportFiles, collapse_blank_lines, existing_ingot_ids, term_key};
+use crate::app::{
+	category::{Category, categories_file_base_path, get_categories_from_dir_path},
+	ingot::{
+		Ingot,
+		ingot::{RKeyList, Status, To},
+	},
+	serde::FileType,
+	tag::{Tag, get_tags_from_dir_path, tags_file_base_path},
+};
+use crate::msg;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Represents the format of the front matter.
+pub enum MatterFormat {
+	/// YAML, between `---` lines.
+	Yaml,
+	/// TOML, between `+++` lines.
+	Toml,
+}
+
+/// Splits the front matter from the source.
+/// Returns the format, the front matter and the rest, or `None` if the source has no front matter.
+pub fn split_front_matter(src: &str) -> Option<(MatterFormat, &str, &str)> {
+	let src = src.strip_prefix('\u{feff}').unwrap_or(src);
+	let (format, delimiter) = if src.starts_with("---") {
+		(MatterFormat::Yaml, "---")
+	} else if src.starts_with("+++") {
+		(MatterFormat::Toml, "+++")
+	} else {
+		return None;
+	};
+	let (first, rest) = src.split_once('\n')?;
+	if first.trim_end() != delimiter {
+		return None;
+	}
+	let mut pos = 0;
+	for line in rest.split_inclusive('\n') {
+		let trimmed = line.trim_end();
+		if trimmed == delimiter || (format == MatterFormat::Yaml && trimmed == "...") {
+			return Some((format, &rest[..pos], &rest[pos + line.len()..]));
+		}
+		pos += line.len();
+	}
+	None
+}
+
+fn toml_to_json(value: toml::Value) -> JsonValue {
+	match value {
+		toml::Value::String(s) => JsonValue::String(s),
+		toml::Value::Integer(i) => JsonValue::from(i),
+		toml::Value::Float(f) => JsonValue::from(f),
+		toml::Value::Boolean(b) => JsonValue::Bool(b),
+		// 日時はYAMLと同じく文字列として扱う
+		toml::Value::Datetime(dt) => JsonValue::String(dt.to_string()),
+		toml::Value::Array(a) => JsonValue::Array(a.into_iter().map(toml_to_json).collect()),
+		toml::Value::Table(t) => {
+			JsonValue::Object(t.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect())
+		}
+	}
+}
+
+/// Parses the front matter into a map.
+pub fn parse_front_matter(
+	format: MatterFormat,
+	matter: &str,
+) -> Result<Map<String, JsonValue>, String> {
+	let value = match format {
+		MatterFormat::Yaml => {
+			serde_yaml_ng::from_str::<JsonValue>(matter).map_err(|e| e.to_string())?
+		}
+		MatterFormat::Toml => toml::from_str::<toml::Table>(matter)
+			.map(|t| toml_to_json(toml::Value::Table(t)))
+			.map_err(|e| e.to_string())?,
+	};
+	match value {
+		JsonValue::Object(map) => Ok(map),
+		JsonValue::Null => Ok(Map::new()),
+		_ => Err(msg!(FrontMatterNotMap)),
+	}
+}
+
+/// Parses the date in the front matter, such as `2020-01-02`, `2020-01-02T03:04:05+09:00` or `2020-01-02 03:04:05 +0900`.
+/// Dates without an offset are regarded as UTC.
+pub fn parse_matter_date(s: &str) -> Option<Timestamp> {
+	let s = s.trim();
+	if let Ok(ts) = s.parse::<Timestamp>() {
+		return Some(ts);
+	}
+	for format in [
+		"%Y-%m-%d %H:%M:%S %z",
+		"%Y-%m-%d %H:%M:%S %:z",
+		"%Y-%m-%d %H:%M %z",
+	] {
+		if let Ok(ts) = strtime::parse(format, s).and_then(|t| t.to_timestamp()) {
+			return Some(ts);
+		}
+	}
+	s.parse::<DateTime>()
+		.or_else(|_| {
+			s.parse::<Date>()
+				.map(|d| d.to_datetime(jiff::civil::Time::midnight()))
+		})
+		.ok()
+		.and_then(|dt| dt.to_zoned(TimeZone::UTC).ok())
+		.map(|z| z.timestamp())
+}
+
+fn string_value(value: &JsonValue) -> Option<String> {
+	match value {
+		JsonValue::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+		JsonValue::Number(n) => Some(n.to_string()),
+		_ => None,
+	}
+}
+
+/// Returns the list of the strings. A string is split by commas, or by spaces if it has no commas.
+fn string_list(value: &JsonValue) -> Vec<String> {
+	match value {
+		JsonValue::Array(a) => a.iter().filter_map(string_value).collect(),
+		JsonValue::String(s) => {
+			let items: Vec<&str> = if s.contains(',') {
+				s.split(',').collect()
+			} else {
+				s.split_whitespace().collect()
+			};
+			items
+				.into_iter()
+				.map(str::trim)
+				.filter(|s| !s.is_empty())
+				.map(str::to_string)
+				.collect()
+		}
+		_ => Vec::new(),
+	}
+}
+
+#[derive(Debug, Default)]
+/// Represents the front matter fields which are mapped onto the ingot.
+pub struct SiteMatter {
+	/// `title`
+	pub title: Option<String>,
+	/// `draft: true`, or `published: false` of Jekyll.
+	pub draft: bool,
+	/// `date`, or `publishDate` of Hugo.
+	pub date: Option<Timestamp>,
+	/// `lastmod`, or `last_modified_at` of Jekyll.
+	pub lastmod: Option<Timestamp>,
+	/// `slug`
+	pub slug: Option<String>,
+	/// `url` of Hugo, or `permalink` of Jekyll.
+	pub url: Option<String>,
+	/// `tags`
+	pub tags: Vec<String>,
+	/// `categories`
+	pub categories: Vec<String>,
+	/// `description`, `summary` or `excerpt`.
+	pub excerpt: Option<String>,
+}
+
+impl SiteMatter {
+	/// Returns the fields from the front matter map.
+	pub fn from_map(map: &Map<String, JsonValue>) -> Self {
+		let first = |keys: &[&str]| keys.iter().find_map(|k| map.get(*k));
+		let date = |keys: &[&str]| {
+			keys
+				.iter()
+				.filter_map(|k| map.get(*k).and_then(string_value))
+				.find_map(|s| parse_matter_date(&s))
+		};
+		let draft = matches!(map.get("draft"), Some(JsonValue::Bool(true)))
+			|| matches!(map.get("published"), Some(JsonValue::Bool(false)));
+		Self {
+			title: first(&["title"]).and_then(string_value),
+			draft,
+			date: date(&["date", "publishDate", "publishdate", "pubdate"]),
+			lastmod: date(&["lastmod", "last_modified_at", "modified", "updated"]),
+			slug: first(&["slug"]).and_then(string_value),
+			url: first(&["url", "permalink"]).and_then(string_value),
+			tags: first(&["tags", "tag"]).map(string_list).unwrap_or_default(),
+			categories: first(&["categories", "category"])
+				.map(string_list)
+				.unwrap_or_default(),
+			excerpt: first(&["description", "summary", "excerpt"]).and_then(string_value),
+		}
+	}
+}
+
+/// Returns the path name for the term name, in lower case with spaces replaced by hyphens.
+pub fn slugify(name: &str) -> String {
+	let mut slug = String::with_capacity(name.len());
+	for c in name.trim().chars() {
+		if c.is_alphanumeric() || c == '_' {
+			slug.extend(c.to_lowercase());
+		} else if !slug.ends_with('-') {
+			slug.push('-');
+		}
+	}
+	slug.trim_matches('-').to_string()
+}
+
+#[derive(Debug, Default)]
+/// Collects the terms in the order of their first appearance, and assigns IDs following the known terms.
+/// Terms with the same path name are merged, and the first name is used.
+pub struct TermCollector {
+	known: Vec<(usize, String)>,
+	terms: Vec<(String, String)>,
+}
+
+impl TermCollector {
+	/// Creates a new collector with the known terms, given as pairs of the ID and the path name.
+	/// Terms with the same path names as the known ones refer them instead of being added.
+	pub fn with_known(known: Vec<(usize, String)>) -> Self {
+		Self {
+			known,
+			terms: Vec::new(),
+		}
+	}
+	fn first_id(&self) -> usize {
+		self
+			.known
+			.iter()
+			.map(|(id, _)| *id)
+			.max()
+			.unwrap_or_default()
+			+ 1
+	}
+	/// Adds the term and returns its ID and path name.
+	pub fn add(&mut self, name: &str) -> (usize, String) {
+		let path_name = match slugify(name) {
+			s if s.is_empty() => name.trim().to_string(),
+			s => s,
+		};
+		if let Some((id, _)) = self.known.iter().find(|(_, p)| *p == path_name) {
+			return (*id, path_name);
+		}
+		let pos = match self.terms.iter().position(|(p, _)| *p == path_name) {
+			Some(pos) => pos,
+			None => {
+				self
+					.terms
+					.push((path_name.clone(), name.trim().to_string()));
+				self.terms.len() - 1
+			}
+		};
+		(self.first_id() + pos, path_name)
+	}
+	/// Returns the list of the keys to refer the terms from ingots.
+	pub fn keys(&mut self, names: &[String]) -> RKeyList {
+		let keys = names
+			.iter()
+			.map(|name| {
+				let (id, path_name) = self.add(name);
+				term_key(id, &path_name)
+			})
+			.collect();
+		RKeyList::Raw(keys)
+	}
+	fn new_terms(&self) -> impl Iterator<Item = (usize, &String, &String)> {
+		let first_id = self.first_id();
+		self
+			.terms
+			.iter()
+			.enumerate()
+			.map(move |(i, (path_name, name))| (first_id + i, path_name, name))
+	}
+	/// Returns the collected terms, except the known ones, as tags.
+	pub fn tags(&self) -> Vec<Tag> {
+		self
+			.new_terms()
+			.map(|(id, path_name, name)| Tag::new(id, path_name.clone(), name.clone(), String::new()))
+			.collect()
+	}
+	/// Returns the collected terms, except the known ones, as root categories.
+	pub fn categories(&self) -> Vec<Category> {
+		self
+			.new_terms()
+			.map(|(id, path_name, name)| {
+				Category::new(id, path_name.clone(), name.clone(), String::new())
+			})
+			.collect()
+	}
+}
+
+/// Represents a content file found in the source site.
+#[derive(Debug)]
+pub struct ContentFile {
+	/// The source path.
+	pub path: PathBuf,
+	/// The destination path of the ingot relative to the zairyo directory, with the `.ingot` extension.
+	pub rel_path: PathBuf,
+	/// The build type of the ingot.
+	pub to: To,
+	/// The path name used when the front matter has neither `slug` nor `url`.
+	pub default_pname: String,
+	/// The date used when the front matter has no date, such as the date in Jekyll post file names.
+	pub default_date: Option<Timestamp>,
+	/// Whether the file is a draft regardless of the front matter.
+	pub draft: bool,
+}
+
+/// Converts the content files of Markdown based static site generators into ingots, tags and categories.
+///
+/// IDs are assigned to ingots in the given order, following the ingots in the zairyo directory.
+/// Tags and categories with the same path names as the existing ones refer them, and new ones are appended to the lists.
+/// `slug` replaces the last segment of the default path name, and `url` replaces the whole path name.
+pub struct SiteConverter {
+	zairyo_dir: PathBuf,
+	files: ImportFiles,
+	tags: TermCollector,
+	categories: TermCollector,
+	next_id: usize,
+}
+
+impl SiteConverter {
+	/// Creates a new converter into the zairyo directory.
+	pub fn new(zairyo_dir: &Path) -> Self {
+		let tags = get_tags_from_dir_path(zairyo_dir)
+			.unwrap_or_default()
+			.into_iter()
+			.map(|tag| (tag.id, tag.path_name))
+			.collect();
+		let categories = get_categories_from_dir_path(zairyo_dir)
+			.unwrap_or_default()
+			.iter()
+			.flat_map(|category| category.get_descendants())
+			.map(|category| (category.id, category.path_name.clone()))
+			.collect();
+		Self {
+			zairyo_dir: zairyo_dir.to_path_buf(),
+			files: ImportFiles::default(),
+			tags: TermCollector::with_known(tags),
+			categories: TermCollector::with_known(categories),
+			next_id: first_new_id(&existing_ingot_ids(zairyo_dir)),
+		}
+	}
+	/// Returns the files to be written, for adding resources and rejected files.
+	pub fn files_mut(&mut self) -> &mut ImportFiles {
+		&mut self.files
+	}
+	/// Converts the content file into an ingot.
+	/// Returns `false` and records the file as rejected if the front matter is invalid.
+	pub fn convert(&mut self, file: ContentFile, src: &str) -> bool {
+		let (map, body) = match split_front_matter(src) {
+			Some((format, matter, body)) => match parse_front_matter(format, matter) {
+				Ok(map) => (map, body),
+				Err(e) => {
+					self.files.reject(file.path, e);
+					return false;
+				}
+			},
+			None => (Map::new(), src),
+		};
+		let matter = SiteMatter::from_map(&map);
+
+		let mut ingot = Ingot::new(self.next_id);
+		self.next_id += 1;
+		ingot.status = if matter.draft || file.draft {
+			Status::Draft
+		} else {
+			Status::Publish
+		};
+		ingot.pname = match (&matter.url, &matter.slug) {
+			(Some(url), _) => url.trim_matches('/').trim_end_matches(".html").to_string(),
+			(None, Some(slug)) => match file.default_pname.rsplit_once('/') {
+				Some((dir, _)) => format!("{dir}/{}", slug.trim_matches('/')),
+				None => slug.trim_matches('/').to_string(),
+			},
+			(None, None) => file.default_pname.clone(),
+		};
+		ingot.to = file.to;
+		if let Some(date) = matter.date.or(file.default_date) {
+			ingot.published = date;
+		}
+		if let Some(lastmod) = matter.lastmod {
+			ingot.updated = lastmod;
+		}
+		ingot.tags = self.tags.keys(&matter.tags);
+		ingot.categories = self.categories.keys(&matter.categories);
+		ingot.excerpt = matter.excerpt.unwrap_or_default();
+		ingot.title = matter.title.unwrap_or_else(|| {
+			file
+				.path
+				.file_stem()
+				.map(|s| s.to_string_lossy().to_string())
+				.unwrap_or_default()
+		});
+		ingot.content = collapse_blank_lines(body);
+		self.files.push_ingot(file.rel_path, &ingot);
+		true
+	}
+	/// Returns the files to be written, with the tags and categories list files extended by the new terms.
+	pub fn finish(mut self, file_type: FileType) -> Result<ImportFiles, ImportError> {
+		let root = Path::new("");
+		self.files.push_extended_list(
+			&self.zairyo_dir,
+			&tags_file_base_path(root),
+			self.tags.tags(),
+			file_type,
+		)?;
+		self.files.push_extended_list(
+			&self.zairyo_dir,
+			&categories_file_base_path(root),
+			self.categories.categories(),
+			file_type,
+		)?;
+		Ok(self.files)
+	}
+}
+
+/// Returns the first ID for new ingots, following the IDs in the zairyo directory.
+pub fn first_new_id(existing: &BTreeMap<usize, PathBuf>) -> usize {
+	existing.keys().next_back().copied().unwrap_or_default() + 1
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_front_matter() {
+		let (format, matter, body) =
+			split_front_matter("---\ntitle: A\ntags: [x, Y Z]\ndraft: true\n---\nbody\n").unwrap();
+		assert_eq!(format, MatterFormat::Yaml);
+		assert_eq!(body, "body\n");
+		let m = SiteMatter::from_map(&parse_front_matter(format, matter).unwrap());
+		assert_eq!(m.title.as_deref(), Some("A"));
+		assert_eq!(m.tags, ["x", "Y Z"]);
+		assert!(m.draft);
+
+		let (format, matter, _) = split_front_matter(
+			"+++\ndate = 2020-01-02T03:04:05+09:00\nlastmod = 2021-01-01\ncategories = \"a b\"\n+++\n",
+		)
+		.unwrap();
+		let m = SiteMatter::from_map(&parse_front_matter(format, matter).unwrap());
+		assert_eq!(m.date.unwrap().to_string(), "2020-01-01T18:04:05Z");
+		assert_eq!(m.lastmod.unwrap().to_string(), "2021-01-01T00:00:00Z");
+		assert_eq!(m.categories, ["a", "b"]);
+
+		assert_eq!(
+			parse_matter_date("2020-01-02 03:04:05 +0900")
+				.unwrap()
+				.to_string(),
+			"2020-01-01T18:04:05Z"
+		);
+		assert!(split_front_matter("--- not matter\n").is_none());
+
+		let mut terms = TermCollector::with_known(vec![(3, "diary".to_string())]);
+		assert_eq!(terms.add("Static Site").0, 4);
+		assert_eq!(terms.add("static-site").0, 4);
+		assert_eq!(terms.add("Diary").0, 3);
+		assert_eq!(terms.add("日記").0, 5);
+		let tags = terms.tags();
+		assert_eq!(tags.len(), 2);
+		assert_eq!(tags[0].path_name, "static-site");
+	}
+}
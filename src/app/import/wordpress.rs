@@ -0,0 +1,801 @@
+use std::{
+	collections::BTreeMap,
+	fmt::Display,
+	path::{Path, PathBuf},
+};
+
+use combu::Vector;
+use jiff::{Timestamp, civil::DateTime, tz::TimeZone};
+use quick_xml::{
+	Reader,
+	escape::{resolve_predefined_entity, unescape},
+	events::{BytesStart, Event},
+};
+use serde::Serialize;
+
+use super::{
+	ImportDest, ImportError, ImportFiles, ImportReport, collapse_blank_lines, file_name_safe,
+	term_key,
+};
+use crate::app::{
+	author::{Author, authors_file_base_path},
+	build::render::url_of,
+	category::{Category, categories_file_base_path, insert_descendant_to_category_list},
+	fs::io::read_all,
+	ingot::{
+		Ingot,
+		ingot::{RKeyList, RKeyRaw, Status, To},
+	},
+	markdown::percent_decode,
+	serde::FileType,
+	tag::{Tag, tags_file_base_path},
+};
+use crate::msg;
+
+/// The directory name for the `WordPress` specific list files in the zairyo directory.
+pub const WORDPRESS_DIR: &str = "wordpress";
+
+#[derive(Debug, Default)]
+/// Represents an author in the WXR.
+pub struct WxrAuthor {
+	/// `wp:author_id`
+	pub id: usize,
+	/// `wp:author_login`
+	pub login: String,
+	/// `wp:author_email`
+	pub email: String,
+	/// `wp:author_display_name`
+	pub display_name: String,
+}
+
+#[derive(Debug, Default)]
+/// Represents a category or a tag term in the WXR.
+pub struct WxrTerm {
+	/// `wp:term_id`
+	pub id: usize,
+	/// `wp:category_nicename` or `wp:tag_slug`. It may be percent-encoded.
+	pub slug: String,
+	/// `wp:category_parent`, the slug of the parent category.
+	pub parent: String,
+	/// `wp:cat_name` or `wp:tag_name`
+	pub name: String,
+	/// `wp:category_description` or `wp:tag_description`
+	pub description: String,
+}
+
+#[derive(Debug, Default)]
+/// Represents a term which an item belongs to.
+pub struct WxrItemTerm {
+	/// The `domain` attribute, `category` or `post_tag`.
+	pub domain: String,
+	/// The `nicename` attribute.
+	pub slug: String,
+	/// The display name of the term.
+	pub name: String,
+}
+
+#[derive(Debug, Default)]
+/// Represents an item (post, page, attachment and so on) in the WXR.
+pub struct WxrItem {
+	/// `title`
+	pub title: String,
+	/// `link`, the original URL.
+	pub link: String,
+	/// `dc:creator`, the login name of the author.
+	pub creator: String,
+	/// `content:encoded`
+	pub content: String,
+	/// `excerpt:encoded`
+	pub excerpt: String,
+	/// `wp:post_id`
+	pub post_id: usize,
+	/// `wp:post_date`, in the site local time.
+	pub post_date: String,
+	/// `wp:post_date_gmt`
+	pub post_date_gmt: String,
+	/// `wp:post_modified_gmt`
+	pub post_modified_gmt: String,
+	/// `wp:comment_status`
+	pub comment_status: String,
+	/// `wp:post_name`, the slug. It may be percent-encoded.
+	pub post_name: String,
+	/// `wp:status`
+	pub status: String,
+	/// `wp:post_parent`
+	pub post_parent: usize,
+	/// `wp:post_type`
+	pub post_type: String,
+	/// `wp:attachment_url`
+	pub attachment_url: String,
+	/// The categories and tags of the item.
+	pub terms: Vec<WxrItemTerm>,
+	/// `wp:postmeta` pairs of `wp:meta_key` and `wp:meta_value`.
+	pub meta: Vec<(String, String)>,
+}
+
+impl WxrItem {
+	/// Returns the value of the post meta with the given key.
+	pub fn meta_value(&self, key: &str) -> Option<&str> {
+		self
+			.meta
+			.iter()
+			.find_map(|(k, v)| (k == key).then_some(v.as_str()))
+	}
+}
+
+#[derive(Debug, Default)]
+/// Represents the contents of a `WordPress` eXtended RSS (WXR) export file.
+pub struct Wxr {
+	/// The site URL, `wp:base_blog_url` or the `link` of the channel.
+	pub base_url: String,
+	/// The authors.
+	pub authors: Vec<WxrAuthor>,
+	/// The categories.
+	pub categories: Vec<WxrTerm>,
+	/// The tags.
+	pub tags: Vec<WxrTerm>,
+	/// The items.
+	pub items: Vec<WxrItem>,
+}
+
+fn parse_error(reader: &Reader<&[u8]>, e: impl Display) -> ImportError {
+	ImportError::Parse(reader.buffer_position(), e.to_string())
+}
+
+fn attr_value(reader: &Reader<&[u8]>, e: &BytesStart, name: &str) -> Result<String, ImportError> {
+	match e.try_get_attribute(name) {
+		Ok(Some(attr)) => attr
+			.unescape_value()
+			.map(|v| v.into_owned())
+			.map_err(|e| parse_error(reader, e)),
+		Ok(None) => Ok(String::new()),
+		Err(e) => Err(parse_error(reader, e)),
+	}
+}
+
+/// Returns the term name. `WordPress` stores the names of terms HTML-escaped.
+fn term_name(value: &str) -> String {
+	let value = value.trim();
+	unescape(value).map_or_else(|_| value.to_string(), |v| v.into_owned())
+}
+
+fn parse_id(value: &str) -> usize {
+	value.trim().parse().unwrap_or_default()
+}
+
+/// Parses the WXR document.
+pub fn parse_wxr(src: &str) -> Result<Wxr, ImportError> {
+	let mut reader = Reader::from_str(src);
+	let mut wxr = Wxr::default();
+	let mut found_channel = false;
+	let mut text = String::new();
+	let mut item: Option<WxrItem> = None;
+	let mut author: Option<WxrAuthor> = None;
+	let mut term: Option<WxrTerm> = None;
+	let mut item_term: Option<WxrItemTerm> = None;
+	let mut meta: Option<(String, String)> = None;
+
+	loop {
+		let event = reader
+			.read_event()
+			.map_err(|e| ImportError::Parse(reader.error_position(), e.to_string()))?;
+		match event {
+			Event::Start(e) => {
+				text.clear();
+				match e.name().as_ref() {
+					b"channel" => found_channel = true,
+					b"item" => item = Some(WxrItem::default()),
+					b"wp:author" if item.is_none() => author = Some(WxrAuthor::default()),
+					b"wp:category" | b"wp:tag" if item.is_none() => term = Some(WxrTerm::default()),
+					b"wp:postmeta" => meta = Some(Default::default()),
+					b"category" if item.is_some() => {
+						item_term = Some(WxrItemTerm {
+							domain: attr_value(&reader, &e, "domain")?,
+							slug: attr_value(&reader, &e, "nicename")?,
+							name: String::new(),
+						})
+					}
+					_ => (),
+				}
+			}
+			Event::Empty(_) => text.clear(),
+			Event::Text(e) => text.push_str(&e.xml_content().map_err(|e| parse_error(&reader, e))?),
+			Event::CData(e) => text.push_str(&e.xml_content().map_err(|e| parse_error(&reader, e))?),
+			Event::GeneralRef(e) => {
+				if let Some(c) = e.resolve_char_ref().map_err(|e| parse_error(&reader, e))? {
+					text.push(c);
+				} else {
+					let name = e.decode().map_err(|e| parse_error(&reader, e))?;
+					match resolve_predefined_entity(&name) {
+						Some(s) => text.push_str(s),
+						// HTMLの実体参照などは解決せずにそのまま残す
+						None => {
+							text.push('&');
+							text.push_str(&name);
+							text.push(';');
+						}
+					}
+				}
+			}
+			Event::End(e) => {
+				let value = std::mem::take(&mut text);
+				let name = e.name();
+				let name = name.as_ref();
+				if let Some((key, val)) = &mut meta {
+					match name {
+						b"wp:meta_key" => *key = value,
+						b"wp:meta_value" => *val = value,
+						b"wp:postmeta" => {
+							if let (Some(item), Some(pair)) = (&mut item, meta.take()) {
+								item.meta.push(pair);
+							}
+						}
+						_ => (),
+					}
+				} else if let Some(it) = &mut item_term {
+					if name == b"category" {
+						it.name = term_name(&value);
+						if let (Some(item), Some(it)) = (&mut item, item_term.take()) {
+							item.terms.push(it);
+						}
+					}
+				} else if let Some(i) = &mut item {
+					match name {
+						b"title" => i.title = value,
+						b"link" => i.link = value.trim().to_string(),
+						b"dc:creator" => i.creator = value.trim().to_string(),
+						b"content:encoded" => i.content = value,
+						b"excerpt:encoded" => i.excerpt = value,
+						b"wp:post_id" => i.post_id = parse_id(&value),
+						b"wp:post_date" => i.post_date = value.trim().to_string(),
+						b"wp:post_date_gmt" => i.post_date_gmt = value.trim().to_string(),
+						b"wp:post_modified_gmt" => i.post_modified_gmt = value.trim().to_string(),
+						b"wp:comment_status" => i.comment_status = value.trim().to_string(),
+						b"wp:post_name" => i.post_name = value.trim().to_string(),
+						b"wp:status" => i.status = value.trim().to_string(),
+						b"wp:post_parent" => i.post_parent = parse_id(&value),
+						b"wp:post_type" => i.post_type = value.trim().to_string(),
+						b"wp:attachment_url" => i.attachment_url = value.trim().to_string(),
+						b"item" => wxr.items.extend(item.take()),
+						_ => (),
+					}
+				} else if let Some(a) = &mut author {
+					match name {
+						b"wp:author_id" => a.id = parse_id(&value),
+						b"wp:author_login" => a.login = value.trim().to_string(),
+						b"wp:author_email" => a.email = value.trim().to_string(),
+						b"wp:author_display_name" => a.display_name = value.trim().to_string(),
+						b"wp:author" => wxr.authors.extend(author.take()),
+						_ => (),
+					}
+				} else if let Some(t) = &mut term {
+					match name {
+						b"wp:term_id" => t.id = parse_id(&value),
+						b"wp:category_nicename" | b"wp:tag_slug" => t.slug = value.trim().to_string(),
+						b"wp:category_parent" => t.parent = value.trim().to_string(),
+						b"wp:cat_name" | b"wp:tag_name" => t.name = term_name(&value),
+						b"wp:category_description" | b"wp:tag_description" => {
+							t.description = value.trim().to_string()
+						}
+						b"wp:category" => wxr.categories.extend(term.take()),
+						b"wp:tag" => wxr.tags.extend(term.take()),
+						_ => (),
+					}
+				} else {
+					match name {
+						b"wp:base_blog_url" => wxr.base_url = value.trim().to_string(),
+						b"link" if wxr.base_url.is_empty() => wxr.base_url = value.trim().to_string(),
+						_ => (),
+					}
+				}
+			}
+			Event::Eof => break,
+			_ => (),
+		}
+	}
+	if !found_channel {
+		return Err(ImportError::Parse(0, msg!(NotWxr)));
+	}
+	Ok(wxr)
+}
+
+#[derive(Debug, Serialize)]
+/// Represents an attachment (media file) in the `WordPress` site.
+pub struct Attachment {
+	/// The original post ID of the attachment.
+	pub id: usize,
+	/// The post ID which the attachment belongs to. 0 means none.
+	pub parent: usize,
+	/// The title of the attachment.
+	pub title: String,
+	/// The original URL of the file.
+	pub url: String,
+	/// The path of the file relative to the uploads directory.
+	pub file: String,
+}
+
+#[derive(Debug, Serialize)]
+/// Represents a redirect from the original URL to the URL in the nibi site.
+pub struct Redirect {
+	/// The original post ID.
+	pub id: usize,
+	/// The original URL path.
+	pub from: String,
+	/// The URL path in the nibi site.
+	pub to: String,
+}
+
+/// Returns the path of the URL, without the scheme and the host.
+fn url_path(url: &str) -> String {
+	let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+	match rest.find('/') {
+		Some(pos) if url.contains("://") => rest[pos..].to_string(),
+		None if url.contains("://") => "/".to_string(),
+		_ => rest.to_string(),
+	}
+}
+
+/// Parses the WXR date, such as `2020-01-02 03:04:05`.
+/// The local time of the site is regarded as UTC, because the time zone is not recorded in the item.
+fn parse_wxr_date(gmt: &str, local: &str) -> Option<Timestamp> {
+	[gmt, local]
+		.into_iter()
+		.filter(|s| !s.is_empty() && !s.starts_with("0000"))
+		.find_map(|s| {
+			s.parse::<DateTime>()
+				.ok()
+				.and_then(|dt| dt.to_zoned(TimeZone::UTC).ok())
+				.map(|z| z.timestamp())
+		})
+}
+
+/// Removes the block editor comments such as `<!-- wp:paragraph -->`.
+/// Runs of blank lines are collapsed, because two or more blank lines start the back matter of ingots.
+fn clean_content(content: &str) -> String {
+	let mut out = String::with_capacity(content.len());
+	let mut rest = content;
+	while let Some(start) = rest.find("<!--") {
+		let comment = &rest[start + 4..];
+		let Some(end) = comment.find("-->") else {
+			break;
+		};
+		let body = comment[..end].trim_start();
+		if body.starts_with("wp:") || body.starts_with("/wp:") {
+			out.push_str(&rest[..start]);
+		} else {
+			out.push_str(&rest[..start + 4 + end + 3]);
+		}
+		rest = &comment[end + 3..];
+	}
+	out.push_str(rest);
+
+	collapse_blank_lines(&out)
+}
+
+/// Returns the categories with the parent hierarchy, and the keys of them by their original slugs.
+fn convert_categories(
+	wxr: &Wxr,
+	skipped: &mut Vec<String>,
+) -> (Vec<Category>, BTreeMap<String, RKeyRaw>) {
+	let mut terms = wxr
+		.categories
+		.iter()
+		.map(|t| {
+			(
+				t.id,
+				t.slug.clone(),
+				t.parent.clone(),
+				t.name.clone(),
+				t.description.clone(),
+			)
+		})
+		.collect::<Vec<_>>();
+	// 一覧にはないが投稿で使われているカテゴリーを補う
+	let mut next_id = terms.iter().map(|t| t.0).max().unwrap_or_default() + 1;
+	for it in wxr.items.iter().flat_map(|i| i.terms.iter()) {
+		if it.domain == "category" && !terms.iter().any(|t| t.1 == it.slug) {
+			terms.push((
+				next_id,
+				it.slug.clone(),
+				String::new(),
+				it.name.clone(),
+				String::new(),
+			));
+			next_id += 1;
+		}
+	}
+	let ids = terms
+		.iter()
+		.map(|t| (t.1.clone(), t.0))
+		.collect::<BTreeMap<_, _>>();
+	let keys = terms
+		.iter()
+		.map(|t| (t.1.clone(), term_key(t.0, &percent_decode(&t.1))))
+		.collect();
+
+	let mut roots: Vec<Category> = Vec::new();
+	let mut pending = Vec::new();
+	for (id, slug, parent, name, description) in terms {
+		let parent_id = if parent.is_empty() {
+			None
+		} else {
+			match ids.get(&parent) {
+				Some(parent_id) => Some(*parent_id),
+				None => {
+					skipped.push(msg!(ParentCategoryNotFound, parent, slug));
+					None
+				}
+			}
+		};
+		let category =
+			Category::new_with_parent(id, percent_decode(&slug), name, description, parent_id);
+		if parent_id.is_none() {
+			roots.push(category);
+		} else {
+			pending.push(category);
+		}
+	}
+	// 親が子より後に現れる場合もあるため、挿入できなくなるまで繰り返す
+	loop {
+		let count = pending.len();
+		pending = pending
+			.into_iter()
+			.filter_map(|c| insert_descendant_to_category_list(&mut roots, c))
+			.collect();
+		if pending.is_empty() || pending.len() == count {
+			break;
+		}
+	}
+	for mut category in pending {
+		skipped.push(msg!(CyclicCategory, category.path_name));
+		category.parent_id = None;
+		category.children = Vector(None);
+		roots.push(category);
+	}
+	(roots, keys)
+}
+
+/// Returns the tags, and the keys of them by their original slugs.
+fn convert_tags(wxr: &Wxr) -> (Vec<Tag>, BTreeMap<String, RKeyRaw>) {
+	let mut tags = wxr
+		.tags
+		.iter()
+		.map(|t| {
+			Tag::new(
+				t.id,
+				percent_decode(&t.slug),
+				t.name.clone(),
+				t.description.clone(),
+			)
+		})
+		.collect::<Vec<_>>();
+	let mut next_id = tags.iter().map(|t| t.id).max().unwrap_or_default() + 1;
+	let mut slugs = wxr.tags.iter().map(|t| t.slug.clone()).collect::<Vec<_>>();
+	for it in wxr.items.iter().flat_map(|i| i.terms.iter()) {
+		if it.domain == "post_tag" && !slugs.contains(&it.slug) {
+			tags.push(Tag::new(
+				next_id,
+				percent_decode(&it.slug),
+				it.name.clone(),
+				String::new(),
+			));
+			slugs.push(it.slug.clone());
+			next_id += 1;
+		}
+	}
+	let keys = slugs
+		.into_iter()
+		.zip(tags.iter())
+		.map(|(slug, tag)| (slug, term_key(tag.id, &tag.path_name)))
+		.collect();
+	(tags, keys)
+}
+
+/// Returns the path name of the page, joined with the path names of the ancestors.
+fn page_path_name(items: &BTreeMap<usize, &WxrItem>, item: &WxrItem) -> String {
+	let mut names = vec![percent_decode(&item.post_name)];
+	let mut parent = item.post_parent;
+	// 親の循環に備えて深さを制限する
+	while parent != 0 && names.len() < 32 {
+		let Some(p) = items.get(&parent).filter(|p| p.post_type == "page") else {
+			break;
+		};
+		names.push(percent_decode(&p.post_name));
+		parent = p.post_parent;
+	}
+	names.reverse();
+	names.retain(|n| !n.is_empty());
+	names.join("/")
+}
+
+/// Converts the WXR into the files to be written into the zairyo directory.
+///
+/// Posts and pages are converted into ingots under `posts/` and `pages/`, keeping their original IDs and slugs.
+/// Tags, categories and authors are written into the list files,
+/// and the attachments and the redirects from the original URLs are written under `wordpress/`.
+pub fn convert_wxr(wxr: &Wxr, file_type: FileType) -> Result<ImportFiles, ImportError> {
+	let mut files = ImportFiles::default();
+	let (categories, category_keys) = convert_categories(wxr, &mut files.skipped);
+	let (tags, tag_keys) = convert_tags(wxr);
+	let authors = wxr
+		.authors
+		.iter()
+		.map(|a| {
+			Author::new(
+				a.id,
+				a.login.clone(),
+				a.display_name.clone(),
+				a.email.clone(),
+			)
+		})
+		.collect::<Vec<_>>();
+	let items_by_id = wxr
+		.items
+		.iter()
+		.map(|i| (i.post_id, i))
+		.collect::<BTreeMap<_, _>>();
+
+	let mut attachments = Vec::new();
+	let mut redirects = Vec::new();
+	for item in wxr.items.iter() {
+		let (to, dir) = match item.post_type.as_str() {
+			"post" => (To::Post, "posts"),
+			"page" => (To::Page, "pages"),
+			"attachment" => {
+				attachments.push(Attachment {
+					id: item.post_id,
+					parent: item.post_parent,
+					title: item.title.clone(),
+					url: item.attachment_url.clone(),
+					file: item
+						.meta_value("_wp_attached_file")
+						.unwrap_or_default()
+						.to_string(),
+				});
+				continue;
+			}
+			post_type => {
+				files
+					.skipped
+					.push(msg!(UnsupportedPostType, item.post_id, post_type));
+				continue;
+			}
+		};
+		let status = match item.status.as_str() {
+			"publish" => Status::Publish,
+			"private" => Status::Private,
+			"draft" | "pending" | "future" | "auto-draft" => Status::Draft,
+			status => {
+				files
+					.skipped
+					.push(msg!(SkippedByStatus, item.post_id, status));
+				continue;
+			}
+		};
+		if item.post_id == 0 {
+			files.skipped.push(msg!(PostIdNotFound, item.title));
+			continue;
+		}
+
+		let mut ingot = Ingot::new(item.post_id);
+		ingot.status = status;
+		ingot.pname = match to {
+			To::Page => page_path_name(&items_by_id, item),
+			_ => percent_decode(&item.post_name),
+		};
+		ingot.to = to;
+		ingot.author = wxr
+			.authors
+			.iter()
+			.find(|a| a.login == item.creator)
+			.map(|a| a.id)
+			.unwrap_or_default();
+		ingot.comment_status = item.comment_status.parse().unwrap_or_default();
+		if let Some(published) = parse_wxr_date(&item.post_date_gmt, &item.post_date) {
+			ingot.published = published;
+		}
+		if let Some(updated) = parse_wxr_date(&item.post_modified_gmt, "") {
+			ingot.updated = updated;
+		}
+		let (item_tags, item_categories): (Vec<_>, Vec<_>) =
+			item.terms.iter().partition(|t| t.domain == "post_tag");
+		ingot.tags = RKeyList::Raw(
+			item_tags
+				.iter()
+				.filter_map(|t| tag_keys.get(&t.slug).cloned())
+				.collect(),
+		);
+		ingot.categories = RKeyList::Raw(
+			item_categories
+				.iter()
+				.filter(|t| t.domain == "category")
+				.filter_map(|t| category_keys.get(&t.slug).cloned())
+				.collect(),
+		);
+		ingot.excerpt = item.excerpt.trim().to_string();
+		ingot.title = if item.title.trim().is_empty() {
+			format!("Untitled {}", item.post_id)
+		} else {
+			item.title.trim().to_string()
+		};
+		ingot.content = clean_content(&item.content);
+
+		let from = url_path(&item.link);
+		let to = url_of(&ingot);
+		if !item.link.is_empty() && from != to {
+			redirects.push(Redirect {
+				id: item.post_id,
+				from,
+				to,
+			});
+		}
+
+		let slug = file_name_safe(&percent_decode(&item.post_name));
+		let file_name = if slug.is_empty() {
+			format!("{}.ingot", item.post_id)
+		} else {
+			format!("{}-{slug}.ingot", item.post_id)
+		};
+		files.push_ingot(Path::new(dir).join(file_name), &ingot);
+	}
+
+	let root = PathBuf::new();
+	files.push_list(&tags_file_base_path(&root), &tags, file_type)?;
+	files.push_list(&categories_file_base_path(&root), &categories, file_type)?;
+	files.push_list(&authors_file_base_path(&root), &authors, file_type)?;
+	let wordpress_dir = root.join(WORDPRESS_DIR);
+	files.push_list(&wordpress_dir.join("attachments"), &attachments, file_type)?;
+	files.push_list(&wordpress_dir.join("redirects"), &redirects, file_type)?;
+	Ok(files)
+}
+
+/// Imports the WXR file into the destination.
+/// Nothing is written if some files already exist and `force` is false.
+pub fn import_wordpress(
+	wxr_path: &Path,
+	dest: &ImportDest,
+	file_type: FileType,
+	force: bool,
+) -> Result<ImportReport, ImportError> {
+	let src = read_all(wxr_path).map_err(|e| ImportError::Read(wxr_path.to_path_buf(), e))?;
+	let wxr = parse_wxr(&src)?;
+	convert_wxr(&wxr, file_type)?.write(dest, force)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::testing::TempDir;
+	use crate::app::{category::get_categories_from_dir_path, tag::get_tags_from_dir_path};
+
+	const WXR: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<rss version="2.0" xmlns:excerpt="http://wordpress.org/export/1.2/excerpt/" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:wp="http://wordpress.org/export/1.2/">
+<channel>
+	<title>Example</title>
+	<link>https://example.com</link>
+	<wp:base_blog_url>https://example.com</wp:base_blog_url>
+	<wp:author><wp:author_id>3</wp:author_id><wp:author_login><![CDATA[alice]]></wp:author_login><wp:author_email><![CDATA[alice@example.com]]></wp:author_email><wp:author_display_name><![CDATA[Alice]]></wp:author_display_name></wp:author>
+	<wp:category><wp:term_id>5</wp:term_id><wp:category_nicename><![CDATA[child]]></wp:category_nicename><wp:category_parent><![CDATA[parent]]></wp:category_parent><wp:cat_name><![CDATA[Child]]></wp:cat_name></wp:category>
+	<wp:category><wp:term_id>4</wp:term_id><wp:category_nicename><![CDATA[parent]]></wp:category_nicename><wp:category_parent><![CDATA[]]></wp:category_parent><wp:cat_name><![CDATA[Parent &amp; Co]]></wp:cat_name></wp:category>
+	<wp:tag><wp:term_id>7</wp:term_id><wp:tag_slug><![CDATA[%e6%97%a5%e8%a8%98]]></wp:tag_slug><wp:tag_name><![CDATA[日記]]></wp:tag_name></wp:tag>
+	<item>
+		<title>Hello &amp; welcome</title>
+		<link>https://example.com/2020/01/hello/</link>
+		<dc:creator><![CDATA[alice]]></dc:creator>
+		<content:encoded><![CDATA[<!-- wp:paragraph -->
+<p>First</p>
+<!-- /wp:paragraph -->
+
+
+
+<!-- keep -->
+<p>Second</p>]]></content:encoded>
+		<excerpt:encoded><![CDATA[]]></excerpt:encoded>
+		<wp:post_id>10</wp:post_id>
+		<wp:post_date><![CDATA[2020-01-02 12:04:05]]></wp:post_date>
+		<wp:post_date_gmt><![CDATA[2020-01-02 03:04:05]]></wp:post_date_gmt>
+		<wp:comment_status><![CDATA[open]]></wp:comment_status>
+		<wp:post_name><![CDATA[hello]]></wp:post_name>
+		<wp:status><![CDATA[publish]]></wp:status>
+		<wp:post_parent>0</wp:post_parent>
+		<wp:post_type><![CDATA[post]]></wp:post_type>
+		<category domain="category" nicename="child"><![CDATA[Child]]></category>
+		<category domain="post_tag" nicename="%e6%97%a5%e8%a8%98"><![CDATA[日記]]></category>
+	</item>
+	<item>
+		<title>About</title>
+		<link>https://example.com/about/</link>
+		<wp:post_id>20</wp:post_id>
+		<wp:post_name><![CDATA[about]]></wp:post_name>
+		<wp:status><![CDATA[publish]]></wp:status>
+		<wp:post_type><![CDATA[page]]></wp:post_type>
+	</item>
+	<item>
+		<title>Team</title>
+		<link>https://example.com/about/team/</link>
+		<wp:post_id>21</wp:post_id>
+		<wp:post_name><![CDATA[team]]></wp:post_name>
+		<wp:status><![CDATA[draft]]></wp:status>
+		<wp:post_parent>20</wp:post_parent>
+		<wp:post_type><![CDATA[page]]></wp:post_type>
+	</item>
+	<item>
+		<title>photo</title>
+		<wp:post_id>30</wp:post_id>
+		<wp:post_parent>10</wp:post_parent>
+		<wp:post_type><![CDATA[attachment]]></wp:post_type>
+		<wp:attachment_url><![CDATA[https://example.com/wp-content/uploads/2020/01/photo.jpg]]></wp:attachment_url>
+		<wp:postmeta><wp:meta_key><![CDATA[_wp_attached_file]]></wp:meta_key><wp:meta_value><![CDATA[2020/01/photo.jpg]]></wp:meta_value></wp:postmeta>
+	</item>
+	<item>
+		<title>Menu</title>
+		<wp:post_id>40</wp:post_id>
+		<wp:post_type><![CDATA[nav_menu_item]]></wp:post_type>
+	</item>
+</channel>
+</rss>"#;
+
+	#[test]
+	fn test_import_wordpress() {
+		let wxr = parse_wxr(WXR).unwrap();
+		assert_eq!(wxr.base_url, "https://example.com");
+		assert_eq!(wxr.authors.len(), 1);
+		assert_eq!(wxr.categories[1].name, "Parent & Co");
+		assert_eq!(wxr.items.len(), 5);
+		assert_eq!(wxr.items[0].title, "Hello & welcome");
+		assert_eq!(
+			wxr.items[3].meta_value("_wp_attached_file"),
+			Some("2020/01/photo.jpg")
+		);
+
+		let dir = TempDir::new("wordpress");
+		let dest = ImportDest {
+			zairyo_dir: dir.to_path_buf(),
+			resources_dir: dir.join("resources"),
+		};
+		let files = convert_wxr(&wxr, FileType::Ron).unwrap();
+		let report = files.write(&dest, false).unwrap();
+		assert_eq!(report.ingots.len(), 3);
+		assert_eq!(report.skipped.len(), 1);
+
+		let post =
+			Ingot::read(std::fs::File::open(dir.join("posts/10-hello.ingot")).unwrap()).unwrap();
+		assert_eq!(post.id, 10);
+		assert_eq!(post.pname, "hello");
+		assert_eq!(post.author, 3);
+		assert!(matches!(post.status, Status::Publish));
+		assert_eq!(post.published.to_string(), "2020-01-02T03:04:05Z");
+		assert_eq!(post.title, "Hello & welcome");
+		assert_eq!(
+			post.content,
+			"<p>First</p>\n\n<!-- keep -->\n<p>Second</p>\n"
+		);
+		assert!(
+			matches!(&post.tags, RKeyList::Raw(raw) if matches!(&raw[..], [RKeyRaw::String(s)] if s == "日記"))
+		);
+
+		let team =
+			Ingot::read(std::fs::File::open(dir.join("pages/21-team.ingot")).unwrap()).unwrap();
+		assert_eq!(team.pname, "about/team");
+		assert!(matches!(team.to, To::Page));
+		assert!(matches!(team.status, Status::Draft));
+
+		let categories = get_categories_from_dir_path(&dir).unwrap();
+		assert_eq!(categories.len(), 1);
+		assert_eq!(categories[0].path_name, "parent");
+		assert!(categories[0].search_id(5).is_some());
+		let tags = get_tags_from_dir_path(&dir).unwrap();
+		assert_eq!(tags[0].path_name, "日記");
+
+		let redirects = crate::app::fs::io::read_all(&dir.join("wordpress/redirects.ron")).unwrap();
+		assert!(redirects.contains("/2020/01/hello/"));
+		assert!(!redirects.contains("\"/about/\""));
+
+		assert!(matches!(
+			convert_wxr(&wxr, FileType::Ron)
+				.unwrap()
+				.write(&dest, false),
+			Err(ImportError::Exists(_))
+		));
+	}
+}
@@ -4,11 +4,17 @@ pub mod ingot;
 pub use ingot::Ingot;
 /// error module
 pub mod error;
+/// excerpt module
+pub mod excerpt;
 /// parser module
 pub mod parser;
+/// `str_tokenizer` module
+pub mod str_tokenizer;
 /// token module
 pub mod token;
 /// `token_node` module
 pub mod token_node;
 /// tokenizer module
 pub mod tokenizer;
+/// writer module
+pub mod writer;
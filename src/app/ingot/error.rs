@@ -1,3 +1,5 @@
+use crate::msg;
+
 #[derive(Debug)]
 /// Represents an error that can occur during ingot parsing.
 pub enum ParseError {
@@ -12,8 +14,8 @@ pub enum ParseError {
 impl std::fmt::Display for ParseError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
-			ParseError::Invalid => write!(f, "ParseError: format is invalid"),
-			ParseError::Empty => write!(f, "ParseError: data is empty"),
+			ParseError::Invalid => write!(f, "{}", msg!(ParseInvalid)),
+			ParseError::Empty => write!(f, "{}", msg!(ParseEmpty)),
 			ParseError::IO(err) => write!(f, "IO: {err}"),
 		}
 	}
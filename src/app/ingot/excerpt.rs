@@ -0,0 +1,131 @@
+use crate::app::{
+	config::ExcerptConf,
+	markdown::{html_to_text, markdown_to_html},
+};
+
+/// The mark appended to a summary when it is truncated.
+pub const ELLIPSIS: &str = "…";
+
+/// Returns the byte position of the first separator outside the fenced code blocks.
+fn find_separator(content: &str, separator: &str) -> Option<usize> {
+	let mut fence: Option<&str> = None;
+	let mut offset = 0;
+	for line in content.split_inclusive('\n') {
+		let trimmed = line.trim_start();
+		match fence {
+			Some(f) if trimmed.starts_with(f) => fence = None,
+			Some(_) => {}
+			None => {
+				if trimmed.starts_with("```") {
+					fence = Some("```");
+				} else if trimmed.starts_with("~~~") {
+					fence = Some("~~~");
+				} else if let Some(pos) = line.find(separator) {
+					return Some(offset + pos);
+				}
+			}
+		}
+		offset += line.len();
+	}
+	None
+}
+
+/// Splits the content at the first separator outside the fenced code blocks.
+/// Returns the part before the separator and the content with the separator removed.
+pub fn split_at_separator(content: &str, separator: &str) -> Option<(String, String)> {
+	if separator.is_empty() {
+		return None;
+	}
+	let pos = find_separator(content, separator)?;
+	let before = &content[..pos];
+	let after = &content[pos + separator.len()..];
+	Some((before.to_string(), format!("{before}{after}")))
+}
+
+/// Truncates the text to the given number of characters.
+/// The text is cut at a character boundary, so multibyte characters are never split.
+pub fn truncate_chars(text: &str, max_chars: usize) -> String {
+	match text.char_indices().nth(max_chars) {
+		Some((byte_pos, _)) => {
+			let mut result = text[..byte_pos].trim_end().to_string();
+			result.push_str(ELLIPSIS);
+			result
+		}
+		None => text.to_string(),
+	}
+}
+
+/// Generates a plain-text summary of the given HTML.
+pub fn summarize_html(html: &str, max_chars: usize) -> String {
+	truncate_chars(&html_to_text(html), max_chars)
+}
+
+/// Determines the excerpt of an ingot.
+/// Returns the excerpt and the content after removing the separator, if it existed.
+///
+/// 優先順位: フロントマターのexcerpt > 区切りより前の本文 > 本文から自動生成した要約
+pub fn resolve_excerpt(
+	excerpt: &str,
+	content: &str,
+	conf: &ExcerptConf,
+) -> (String, Option<String>) {
+	match split_at_separator(content, conf.get_separator()) {
+		Some((before, content)) => {
+			if excerpt.trim().is_empty() {
+				(html_to_text(&markdown_to_html(&before)), Some(content))
+			} else {
+				(excerpt.trim().to_string(), Some(content))
+			}
+		}
+		None => {
+			if excerpt.trim().is_empty() {
+				(
+					summarize_html(&markdown_to_html(content), conf.get_summary_length()),
+					None,
+				)
+			} else {
+				(excerpt.trim().to_string(), None)
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_truncate_multibyte() {
+		let text = "吾輩は猫である。名前はまだ無い。";
+		assert_eq!(truncate_chars(text, 8), "吾輩は猫である。…");
+		assert_eq!(truncate_chars(text, 100), text);
+		assert_eq!(truncate_chars("", 3), "");
+	}
+
+	#[test]
+	fn test_resolve_excerpt_separator() {
+		let conf = ExcerptConf::default();
+		let (excerpt, content) = resolve_excerpt("", "**はじめに**です。\n<!--more-->\n続き", &conf);
+		assert_eq!(excerpt, "はじめにです。");
+		assert_eq!(content, Some("**はじめに**です。\n\n続き".to_string()));
+
+		// コードブロック内の区切りでは分割しない
+		let body = "例:\n\n```html\n<!--more-->\n```\n\n~~~\n<!--more-->\n~~~\n";
+		assert_eq!(split_at_separator(body, "<!--more-->"), None);
+		let (before, content) =
+			split_at_separator(&format!("{body}<!--more-->\n続き"), "<!--more-->").unwrap();
+		assert_eq!(before, body);
+		assert_eq!(content, format!("{body}\n続き"));
+	}
+
+	#[test]
+	fn test_resolve_excerpt_front_matter_and_summary() {
+		let conf = ExcerptConf::new("<!--more-->", 5);
+		let (excerpt, content) = resolve_excerpt(" 手書きの抜粋 ", "本文", &conf);
+		assert_eq!(excerpt, "手書きの抜粋");
+		assert_eq!(content, None);
+
+		let (excerpt, _) = resolve_excerpt("", "# 見出し\n\n日本語の本文です。", &conf);
+		assert_eq!(excerpt, "見出し 日…");
+	}
+}
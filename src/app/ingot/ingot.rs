@@ -1,12 +1,19 @@
 use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
 
 use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
 
-use crate::app::{category::Category, tag::Tag};
+use crate::app::{
+	category::Category,
+	config::{ExcerptConf, I18nConf},
+	tag::Tag,
+};
+use crate::msg;
 
-use super::{error::ParseError, parser::IngotParser};
+use super::{error::ParseError, excerpt::resolve_excerpt, parser::IngotParser};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 /// Ingot struct. Represents an site page contents data.
 pub struct Ingot {
 	/// The ingot ID.
@@ -37,9 +44,16 @@ pub struct Ingot {
 	pub categories: RKeyList,
 	/// The ingot build type(e.g. post, page, article, top, as-is, custom).
 	pub to: To,
+	/// The language code (e.g. ja, en). Empty means the default language of the site.
+	pub lang: String,
+	/// The key linking the variants of the same ingot in other languages.
+	pub translation_key: String,
+	#[serde(skip)]
+	/// The permalink prefix resolved from the language. Set when the ingot is loaded.
+	pub url_prefix: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// Enum for a relational and raw key value.
 pub enum RKeyRaw {
 	/// A string value.
@@ -48,6 +62,93 @@ pub enum RKeyRaw {
 	Usize(usize),
 }
 
+impl RKeyRaw {
+	/// Returns `true` if the key is an empty string.
+	pub fn is_empty(&self) -> bool {
+		matches!(self, RKeyRaw::String(s) if s.is_empty())
+	}
+}
+
+impl std::fmt::Display for RKeyRaw {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			RKeyRaw::String(s) => write!(f, "{s}"),
+			RKeyRaw::Usize(id) => write!(f, "{id}"),
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+/// Enum for a raw key which could not be collated to a category or a tag.
+pub enum UnresolvedKey {
+	/// A category key.
+	Category(RKeyRaw),
+	/// A tag key.
+	Tag(RKeyRaw),
+}
+
+impl std::fmt::Display for UnresolvedKey {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			UnresolvedKey::Category(key) => write!(f, "{}", msg!(CategoryNotFound, key)),
+			UnresolvedKey::Tag(key) => write!(f, "{}", msg!(TagNotFound, key)),
+		}
+	}
+}
+
+impl Serialize for RKeyRaw {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		match self {
+			RKeyRaw::String(s) => serializer.serialize_str(s),
+			RKeyRaw::Usize(id) => serializer.serialize_u64(*id as u64),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for RKeyRaw {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		deserializer.deserialize_any(RKeyRawVisitor)
+	}
+}
+
+struct RKeyRawVisitor;
+
+impl<'de> serde::de::Visitor<'de> for RKeyRawVisitor {
+	type Value = RKeyRaw;
+
+	fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+		formatter.write_str("id number or name string")
+	}
+
+	fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+	where
+		E: serde::de::Error,
+	{
+		usize::try_from(v)
+			.map(RKeyRaw::Usize)
+			.map_err(|_| E::custom(format!("id out of range: {v}")))
+	}
+
+	fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+	where
+		E: serde::de::Error,
+	{
+		usize::try_from(v)
+			.map(RKeyRaw::Usize)
+			.map_err(|_| E::custom(format!("id out of range: {v}")))
+	}
+
+	fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+		Ok(RKeyRaw::from(v.to_owned()))
+	}
+}
+
 impl Default for RKeyRaw {
 	fn default() -> Self {
 		Self::String(String::default())
@@ -63,6 +164,28 @@ pub enum RKeyList {
 	CollatedId(Vec<usize>),
 }
 
+impl Serialize for RKeyList {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		match self {
+			RKeyList::Raw(raw) => raw.serialize(serializer),
+			RKeyList::CollatedId(ids) => ids.serialize(serializer),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for RKeyList {
+	/// Deserializes a list of keys as raw keys. Collated IDs are read back as raw ID keys, which collate to the same IDs.
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		Vec::<RKeyRaw>::deserialize(deserializer).map(RKeyList::Raw)
+	}
+}
+
 impl From<String> for RKeyRaw {
 	fn from(value: String) -> Self {
 		let trimed = value.trim().trim_matches(['"', '\'']);
@@ -99,7 +222,8 @@ impl From<Vec<String>> for RKeyList {
 	}
 }
 
-#[derive(Debug, Default, strum::Display)]
+#[derive(Debug, Default, strum::Display, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 /// Enum for the page(ingot) status.
 pub enum Status {
 	#[default]
@@ -155,7 +279,41 @@ impl From<&str> for To {
 	}
 }
 
-#[derive(Debug, Default, strum::Display)]
+impl Serialize for To {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		serializer.serialize_str(self.name())
+	}
+}
+
+impl<'de> Deserialize<'de> for To {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		let name = String::deserialize(deserializer)?;
+		Ok(To::from(name.as_str()))
+	}
+}
+
+impl To {
+	/// Returns the name of the build type, used as the key of the igata table.
+	pub fn name(&self) -> &str {
+		match self {
+			To::Post => "post",
+			To::Page => "page",
+			To::Article => "article",
+			To::Top => "index",
+			To::AsIs => "asis",
+			To::Custom(s) => s,
+		}
+	}
+}
+
+#[derive(Debug, Default, strum::Display, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 /// Enum for the pags's comment status.
 pub enum CommentStatus {
 	/// Open
@@ -193,38 +351,64 @@ impl Ingot {
 			tags: RKeyList::default(),
 			categories: RKeyList::default(),
 			to: To::default(),
+			lang: String::default(),
+			translation_key: String::default(),
+			url_prefix: String::default(),
 		}
 	}
 	/// Reads and parses an `Ingot` from a reader.
 	pub fn read<R: std::io::Read>(reader: R) -> Result<Ingot, ParseError> {
 		IngotParser::parse(reader)
 	}
+	/// Fills the excerpt of the `Ingot`.
+	/// The excerpt in the front matter is used if exists, otherwise the part before the separator,
+	/// otherwise a plain-text summary generated from the content.
+	pub fn fill_excerpt(&mut self, conf: &ExcerptConf) {
+		let (excerpt, content) = resolve_excerpt(&self.excerpt, &self.content, conf);
+		self.excerpt = excerpt;
+		if let Some(content) = content {
+			self.content = content;
+		}
+	}
+	/// Fills the language of the `Ingot` with the default language if not declared,
+	/// and resolves the permalink prefix of the language.
+	pub fn fill_lang(&mut self, conf: &I18nConf) {
+		if self.lang.trim().is_empty() {
+			self.lang = conf.get_default_lang().to_string();
+		}
+		self.url_prefix = conf.prefix_of(&self.lang).to_string();
+	}
 	/// Collates the IDs of the categories and tags in the `Ingot`.
+	/// Returns the keys which could not be collated.
 	pub fn collate_ids(
 		&mut self,
 		categories_index_map: &BTreeMap<usize, &Category>,
 		tags_index_map: &BTreeMap<usize, &Tag>,
-	) {
+	) -> Vec<UnresolvedKey> {
+		let mut unresolved = Vec::new();
 		if let RKeyList::Raw(raw) = &self.categories {
 			self.categories = RKeyList::CollatedId(
 				raw.iter()
-					.filter_map(|r| match r {
-						RKeyRaw::Usize(id) => {
-							if categories_index_map.contains_key(id) {
-								Some(*id)
-							} else {
-								None
-							}
+					.filter_map(|r| {
+						if r.is_empty() {
+							return None;
 						}
-						RKeyRaw::String(name) => {
-							categories_index_map.iter().find_map(|(id, category)| {
-								if &category.name == name || &category.path_name == name {
-									Some(*id)
-								} else {
-									None
-								}
-							})
+						let id = match r {
+							RKeyRaw::Usize(id) => categories_index_map.contains_key(id).then_some(*id),
+							RKeyRaw::String(name) => {
+								categories_index_map.iter().find_map(|(id, category)| {
+									if &category.name == name || &category.path_name == name {
+										Some(*id)
+									} else {
+										None
+									}
+								})
+							}
+						};
+						if id.is_none() {
+							unresolved.push(UnresolvedKey::Category(r.clone()));
 						}
+						id
 					})
 					.collect(),
 			);
@@ -232,24 +416,28 @@ impl Ingot {
 		if let RKeyList::Raw(raw) = &self.tags {
 			self.tags = RKeyList::CollatedId(
 				raw.iter()
-					.filter_map(|r| match r {
-						RKeyRaw::Usize(id) => {
-							if tags_index_map.contains_key(id) {
-								Some(*id)
-							} else {
-								None
-							}
+					.filter_map(|r| {
+						if r.is_empty() {
+							return None;
 						}
-						RKeyRaw::String(name) => tags_index_map.iter().find_map(|(id, tag)| {
-							if &tag.name == name || &tag.path_name == name {
-								Some(*id)
-							} else {
-								None
-							}
-						}),
+						let id = match r {
+							RKeyRaw::Usize(id) => tags_index_map.contains_key(id).then_some(*id),
+							RKeyRaw::String(name) => tags_index_map.iter().find_map(|(id, tag)| {
+								if &tag.name == name || &tag.path_name == name {
+									Some(*id)
+								} else {
+									None
+								}
+							}),
+						};
+						if id.is_none() {
+							unresolved.push(UnresolvedKey::Tag(r.clone()));
+						}
+						id
 					})
 					.collect(),
 			)
 		}
+		unresolved
 	}
 }
@@ -10,19 +10,47 @@ use crate::app::{
 use super::{
 	Ingot,
 	error::ParseError,
+	str_tokenizer::StrTokenizer,
 	token::{BlockToken, Bracket, BracketRole, CommentMark, Quote, RawToken, RawTokenData, Sep},
 	token_node::TokenNode,
-	tokenizer::IngotTokenizer,
 };
 
 #[derive(Debug)]
 /// Parses an `Ingot` from a reader.
 pub struct IngotParser {}
 
-fn split_chars(mut chars: Vec<char>, pos: usize) -> (Vec<char>, Vec<char>) {
-	let after = chars.split_off(pos);
-	(chars, after)
-}
+/// The keys of the front matter and the back matter read by [`IngotParser::set_from_key_value`].
+pub const MATTER_KEYS: [&str; 29] = [
+	"tags",
+	"tag",
+	"categories",
+	"category",
+	"type",
+	"to",
+	"status",
+	"updated",
+	"modified",
+	"created",
+	"published",
+	"ingot_id",
+	"id",
+	"author",
+	"author_id",
+	"comment_status",
+	"comments",
+	"excerpt",
+	"summary",
+	"lang",
+	"language",
+	"translation_key",
+	"translation",
+	"path_url_name",
+	"path_name",
+	"url_path_name",
+	"post_url_name",
+	"page_url_name",
+	"pname",
+];
 
 enum NewLineType {
 	Cr,
@@ -40,48 +68,37 @@ impl NewLineType {
 	}
 }
 
-fn seek_next_nl(chars: &[char]) -> Option<(usize, NewLineType)> {
-	let mut pos = 0;
-	while let Some(c) = chars.get(pos) {
-		match c {
-			'\n' => return Some((pos, NewLineType::Lf)),
-			'\r' => {
-				if let Some('\n') = chars.get(pos + 1) {
-					return Some((pos, NewLineType::Crlf));
-				}
-				return Some((pos, NewLineType::Cr));
-			}
-			_ => pos += 1,
-		}
+fn seek_next_nl(s: &str) -> Option<(usize, NewLineType)> {
+	let pos = s.find(['\n', '\r'])?;
+	match &s.as_bytes()[pos..] {
+		[b'\r', b'\n', ..] => Some((pos, NewLineType::Crlf)),
+		[b'\r', ..] => Some((pos, NewLineType::Cr)),
+		_ => Some((pos, NewLineType::Lf)),
 	}
-	None
 }
 
-fn is_empty_chars(chars: &[char]) -> bool {
-	chars.is_empty() || chars.iter().all(|c| c.is_whitespace())
+fn is_empty_str(s: &str) -> bool {
+	s.trim().is_empty()
 }
 
 impl IngotParser {
-	/// Splits the back matter from the given characters.
-	pub fn split_back_matter(chars: Vec<char>) -> (Vec<char>, Vec<char>) {
-		let mut pos = chars.len() - 1;
+	/// Splits the back matter from the given string.
+	/// The back matter is the block after two or more blank lines at the end of the string.
+	pub fn split_back_matter(src: &str) -> (&str, &str) {
 		let mut nl_count: usize = 0;
-		while let Some(c) = chars.get(pos) {
+		let mut after_lf = false;
+		for (pos, c) in src.char_indices().rev() {
 			match c {
-				'\n' => {
-					if let Some('\r') = chars.get(pos - 1) {
-						pos -= 1;
-					}
-					// 改行が前に2個以上ある
-					if nl_count > 1 {
-						return split_chars(chars, pos);
-					}
-					nl_count += 1;
+				'\r' if after_lf => {
+					// CRLFのCRは直後のLFと合わせて1つの改行として数える
 				}
-				'\r' => {
+				'\n' | '\r' => {
 					// 改行が前に2個以上ある
 					if nl_count > 1 {
-						return split_chars(chars, pos);
+						if c == '\n' && src[..pos].ends_with('\r') {
+							return src.split_at(pos - 1);
+						}
+						return src.split_at(pos);
 					}
 					nl_count += 1;
 				}
@@ -90,11 +107,12 @@ impl IngotParser {
 					nl_count = 0;
 				}
 			}
-			pos -= 1;
+			after_lf = c == '\n';
 		}
-		(chars, Vec::new())
+		(src, "")
 	}
 	/// Sets the value of the given key from the token node.
+	/// The keys are listed in [`MATTER_KEYS`], and the other keys are ignored.
 	pub fn set_from_key_value(result: &mut Ingot, key: String, value: Option<TokenNode>) {
 		if let Some(v) = value {
 			let token = v.token;
@@ -137,6 +155,30 @@ impl IngotParser {
 					Ok(val) => result.id = val,
 					Err(_e) => (),
 				},
+				"author" | "author_id" => {
+					match token.get_string_value_or_empty().trim().parse::<usize>() {
+						Ok(val) => result.author = val,
+						Err(_e) => (),
+					}
+				}
+				"comment_status" | "comments" => {
+					let val = token.get_string_value_or_empty();
+					result.comment_status = val.as_str().trim().parse().unwrap_or_default();
+				}
+				"excerpt" | "summary" => {
+					let val = token.get_string_value_or_empty();
+					if !val.trim().is_empty() {
+						result.excerpt = val.trim().to_string();
+					}
+				}
+				"lang" | "language" => {
+					let val = token.get_string_value_or_empty();
+					result.lang = val.trim().to_string();
+				}
+				"translation_key" | "translation" => {
+					let val = token.get_string_value_or_empty();
+					result.translation_key = val.trim().to_string();
+				}
 				"path_url_name" | "path_name" | "url_path_name" | "post_url_name" | "page_url_name"
 				| "pname" => {
 					let val = token.get_string_value_or_empty();
@@ -151,9 +193,12 @@ impl IngotParser {
 	/// Parses the buffer from given reader into an `Ingot`.
 	pub fn parse<R: Read>(reader: R) -> Result<Ingot, ParseError> {
 		let buffer = read_all_from_reader(reader).map_err(ParseError::IO)?;
-
+		IngotParser::parse_str(&buffer)
+	}
+	/// Parses the given string into an `Ingot`.
+	pub fn parse_str(src: &str) -> Result<Ingot, ParseError> {
 		let mut result = Ingot::default();
-		let mut tokenizer = IngotTokenizer::new(buffer.chars().collect());
+		let mut tokenizer = StrTokenizer::new(src);
 
 		// フロントマターを分離する
 		let mut front_matter_tokens: Vec<RawTokenData> = Vec::new();
@@ -176,7 +221,7 @@ impl IngotParser {
 			}
 		}
 
-		let (_cpos, buffer) = tokenizer.get_rest_all();
+		let (rest_pos, rest) = tokenizer.get_rest_all();
 
 		let mut parser = IngotMatterTokenParser::new(front_matter_tokens);
 
@@ -186,19 +231,13 @@ impl IngotParser {
 			}
 		}
 
-		let (mut content, back_matter) = IngotParser::split_back_matter(buffer);
+		let (mut content, back_matter) = IngotParser::split_back_matter(rest);
 
-		tokenizer = IngotTokenizer::new(back_matter);
-
-		let mut back_matter_tokens: Vec<RawTokenData> = Vec::new();
-
-		loop {
-			let (pos, token) = tokenizer.next_raw_token();
-			match token {
-				RawToken::Eos => break,
-				_ => back_matter_tokens.push((pos, token)),
-			}
-		}
+		// 位置は元の文字列でのバイトオフセットにそろえる
+		let back_matter_pos = rest_pos + content.len();
+		let back_matter_tokens: Vec<RawTokenData> = StrTokenizer::new(back_matter)
+			.map(|(pos, token)| (back_matter_pos + pos, token))
+			.collect();
 
 		parser = IngotMatterTokenParser::new(back_matter_tokens);
 
@@ -211,40 +250,40 @@ impl IngotParser {
 		// parse content
 		// 最初の中身があり、後ろが空行である行がタイトル
 		loop {
-			match seek_next_nl(&content) {
+			match seek_next_nl(content) {
 				Some((pos, nl)) => {
 					let line = &content[0..pos];
-					if is_empty_chars(line) {
+					if is_empty_str(line) {
 						// 空行はスキップ
-						content.drain(0..(pos + nl.len()));
+						content = &content[(pos + nl.len())..];
 					} else {
-						let cand_title_line: String = content.drain(0..pos).collect();
-						content.drain(0..nl.len());
+						let cand_title_line = line;
+						let after_title = &content[(pos + nl.len())..];
 						// 次の行が空行かチェック
-						match seek_next_nl(&content) {
+						match seek_next_nl(after_title) {
 							Some((pos, nl)) => {
-								let line = &content[0..pos];
-								if is_empty_chars(line) {
+								let line = &after_title[0..pos];
+								if is_empty_str(line) {
 									// 空行ならtitleとcontentをセット
-									result.title = cand_title_line;
-									result.content = content.split_off(pos + nl.len()).iter().collect();
+									result.title = cand_title_line.to_string();
+									result.content = after_title[(pos + nl.len())..].to_string();
 								} else {
 									result.title = String::new();
-									result.content = content.iter().collect::<String>();
+									result.content = content.to_string();
 								}
 								break;
 							}
 							_ => {
-								result.title = cand_title_line;
-								result.content = content.iter().collect::<String>();
+								result.title = cand_title_line.to_string();
+								result.content = after_title.to_string();
 								break;
 							}
 						}
 					}
 				}
 				_ => {
-					result.title = content.iter().collect::<String>();
-					result.content = content.iter().collect::<String>();
+					result.title = content.to_string();
+					result.content = content.to_string();
 					break;
 				}
 			}
@@ -254,24 +293,24 @@ impl IngotParser {
 	}
 }
 /// Parses the raw tokens into an Ingot matter block.
-pub struct IngotMatterTokenParser {
+pub struct IngotMatterTokenParser<'a> {
 	/// The raw tokens to parse.
-	pub raw_tokens: Vec<RawTokenData>,
+	pub raw_tokens: Vec<RawTokenData<'a>>,
 	/// The current position in the raw tokens.
 	pub pos: usize,
 }
 
-impl IngotMatterTokenParser {
+impl<'a> IngotMatterTokenParser<'a> {
 	/// Creates a new `IngotMatterTokenParser` for the given raw tokens.
-	pub fn new(raw_tokens: Vec<RawTokenData>) -> IngotMatterTokenParser {
+	pub fn new(raw_tokens: Vec<RawTokenData<'a>>) -> IngotMatterTokenParser<'a> {
 		IngotMatterTokenParser { raw_tokens, pos: 0 }
 	}
 	/// Peeks at the next token without advancing the position.
-	pub fn peek_next_token(&self) -> Option<&RawTokenData> {
+	pub fn peek_next_token(&self) -> Option<&RawTokenData<'a>> {
 		self.raw_tokens.get(self.pos)
 	}
 	/// Advances the position to the next token.
-	pub fn next_token(&mut self) -> Option<RawTokenData> {
+	pub fn next_token(&mut self) -> Option<RawTokenData<'a>> {
 		let result = self.peek_next_token().cloned();
 		self.pos += 1;
 		result
@@ -293,8 +332,7 @@ impl IngotMatterTokenParser {
 				if stop_tokens.contains(token) || token == &RawToken::Eos {
 					break;
 				}
-				let s = token.get_as_string();
-				result.push_str(&s);
+				token.push_to(&mut result);
 				self.pos_next();
 			} else {
 				break;
@@ -458,7 +496,9 @@ impl IngotMatterTokenParser {
 			match token {
 				RawToken::Eos => None,
 				RawToken::Quote(q) => Some(self.parse_quoted_block(pos, q)),
-				RawToken::SimpleString(s) => Some(self.parse_simple_string(pos, s, able_key_token)),
+				RawToken::SimpleString(s) => {
+					Some(self.parse_simple_string(pos, s.into_owned(), able_key_token))
+				}
 				RawToken::Comment(mark) => Some(self.parse_comment_part(pos, mark)),
 				RawToken::Sep(_sep) => self.parse_token_node(able_key_token),
 				RawToken::Bracket(bracket) => self.parse_bracket(pos, bracket),
@@ -479,17 +519,8 @@ mod tests {
 
 	use super::*;
 
-	fn tokenize_all(input: &str) -> Vec<RawTokenData> {
-		let mut tokenizer = IngotTokenizer::new(input.chars().collect());
-		let mut tokens: Vec<RawTokenData> = Vec::new();
-		loop {
-			let next_token = tokenizer.next_raw_token();
-			match next_token.1 {
-				RawToken::Eos => break,
-				_ => tokens.push(next_token),
-			}
-		}
-		tokens
+	fn tokenize_all(input: &str) -> Vec<RawTokenData<'_>> {
+		StrTokenizer::new(input).collect()
 	}
 
 	#[test]
@@ -526,4 +557,28 @@ mod tests {
 			}
 		);
 	}
+
+	#[test]
+	fn test_parse_str() {
+		let src = "id: 3\r\nstatus: publish\r\n\r\nタイトル\r\n\r\n本文です。\r\n二行目\r\n\r\n\r\ntags: [a, b]\r\n";
+		let ingot = IngotParser::parse_str(src).unwrap();
+		assert_eq!(ingot.id, 3);
+		assert_eq!(ingot.title, "タイトル");
+		assert_eq!(ingot.content, "本文です。\r\n二行目");
+		assert!(matches!(ingot.tags, RKeyList::Raw(ref tags) if tags.len() == 2));
+	}
+
+	#[test]
+	fn test_split_back_matter() {
+		assert_eq!(IngotParser::split_back_matter(""), ("", ""));
+		assert_eq!(IngotParser::split_back_matter("abc\n\nd"), ("abc\n\nd", ""));
+		assert_eq!(
+			IngotParser::split_back_matter("abc\n\n\nd: e\n"),
+			("abc", "\n\n\nd: e\n")
+		);
+		assert_eq!(
+			IngotParser::split_back_matter("abc\r\n\r\n\r\nd: e"),
+			("abc", "\r\n\r\n\r\nd: e")
+		);
+	}
 }
@@ -0,0 +1,216 @@
+use std::borrow::Cow;
+
+use super::{
+	token::{Bracket, BracketRole, BracketType, CommentMark, Quote, RawToken, RawTokenData, Sep},
+	tokenizer::SYMBOL_CHARS,
+};
+
+#[derive(Debug)]
+/// Tokenizer for the ingot format over a string slice.
+/// Token positions are byte offsets and string tokens borrow the source.
+pub struct StrTokenizer<'a> {
+	src: &'a str,
+	/// Current byte offset of cursor in src.
+	pub pos: usize,
+}
+
+impl<'a> StrTokenizer<'a> {
+	/// constructor
+	pub fn new(src: &'a str) -> StrTokenizer<'a> {
+		StrTokenizer { src, pos: 0 }
+	}
+
+	/// returns next char
+	pub fn next_char(&mut self) -> Option<char> {
+		let result = self.peek_next_char();
+		if let Some(c) = result {
+			self.pos += c.len_utf8();
+		}
+		result
+	}
+
+	/// returns next char without move position
+	pub fn peek_next_char(&self) -> Option<char> {
+		self.src[self.pos..].chars().next()
+	}
+
+	/// returns the char after the next char without move position
+	fn peek_second_char(&self) -> Option<char> {
+		self.src[self.pos..].chars().nth(1)
+	}
+
+	/// moves position over the next char
+	pub fn pos_next(&mut self) {
+		self.next_char();
+	}
+
+	fn slice_from(&self, start: usize) -> Cow<'a, str> {
+		Cow::Borrowed(&self.src[start..self.pos])
+	}
+
+	fn tokenize_new_line(&mut self) -> RawToken<'a> {
+		if let Some('\n') = self.peek_next_char() {
+			self.pos_next();
+		}
+		RawToken::Sep(Sep::NewLine)
+	}
+
+	fn tokenize_whitespaces(&mut self, start: usize) -> RawToken<'a> {
+		while let Some(' ' | '\t') = self.peek_next_char() {
+			self.pos_next();
+		}
+		RawToken::Sep(Sep::WhiteSpaces(self.slice_from(start)))
+	}
+
+	fn tokenize_after_slash(&mut self, start: usize) -> RawToken<'a> {
+		match self.peek_next_char() {
+			Some('/') => {
+				self.pos_next();
+				RawToken::Comment(CommentMark::LineBegin)
+			}
+			Some('*') => {
+				self.pos_next();
+				RawToken::Comment(CommentMark::BlockBegin)
+			}
+			_ => RawToken::SimpleString(self.slice_from(start)),
+		}
+	}
+
+	fn tokenize_after_asterisk(&mut self, start: usize) -> RawToken<'a> {
+		match self.peek_next_char() {
+			Some('/') => {
+				self.pos_next();
+				RawToken::Comment(CommentMark::BlockEnd)
+			}
+			_ => RawToken::SimpleString(self.slice_from(start)),
+		}
+	}
+
+	fn tokenize_string(&mut self, start: usize, first_char: char) -> RawToken<'a> {
+		if first_char == '\\' {
+			self.pos_next();
+		}
+
+		while let Some(c) = self.peek_next_char() {
+			if SYMBOL_CHARS.contains(c) {
+				break;
+			} else if c == '\\' {
+				self.pos_next();
+				if self.next_char().is_none() {
+					break;
+				}
+			} else if c == '*' {
+				if let Some('/') = self.peek_second_char() {
+					break;
+				}
+				self.pos_next();
+			} else {
+				self.pos_next();
+			}
+		}
+		RawToken::SimpleString(self.slice_from(start))
+	}
+	/// Returns the next raw token from src.
+	pub fn next_raw_token(&mut self) -> RawTokenData<'a> {
+		let pos = self.pos;
+		let token = match self.next_char() {
+			Some(c) => match c {
+				'\'' => RawToken::Quote(Quote::Single),
+				'"' => RawToken::Quote(Quote::Double),
+				',' => RawToken::Sep(Sep::Comma),
+				':' => RawToken::Sep(Sep::Colon),
+				'\n' => RawToken::Sep(Sep::NewLine),
+				'\r' => self.tokenize_new_line(),
+				' ' | '\t' => self.tokenize_whitespaces(pos),
+				'[' => RawToken::Bracket(Bracket::new(BracketRole::Start, BracketType::Square)),
+				']' => RawToken::Bracket(Bracket::new(BracketRole::End, BracketType::Square)),
+				'{' => RawToken::Bracket(Bracket::new(BracketRole::Start, BracketType::Curly)),
+				'}' => RawToken::Bracket(Bracket::new(BracketRole::End, BracketType::Curly)),
+				'(' => RawToken::Bracket(Bracket::new(BracketRole::Start, BracketType::Normal)),
+				')' => RawToken::Bracket(Bracket::new(BracketRole::End, BracketType::Normal)),
+				'<' => RawToken::Bracket(Bracket::new(BracketRole::Start, BracketType::Angle)),
+				'>' => RawToken::Bracket(Bracket::new(BracketRole::End, BracketType::Angle)),
+				'/' => self.tokenize_after_slash(pos),
+				'*' => self.tokenize_after_asterisk(pos),
+				_ => self.tokenize_string(pos, c),
+			},
+			None => RawToken::Eos,
+		};
+
+		(pos, token)
+	}
+	/// Returns the rest of src after the current position.
+	pub fn get_rest_all(&self) -> (usize, &'a str) {
+		(self.pos, &self.src[self.pos.min(self.src.len())..])
+	}
+}
+
+impl<'a> Iterator for StrTokenizer<'a> {
+	type Item = RawTokenData<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		match self.next_raw_token() {
+			(_, RawToken::Eos) => None,
+			data => Some(data),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::app::ingot::tokenizer::IngotTokenizer;
+
+	use super::*;
+
+	#[test]
+	fn test_byte_offsets() {
+		let src = "タイトル: 日本語, tag:x";
+		let tokens = StrTokenizer::new(src).collect::<Vec<_>>();
+		for (pos, token) in tokens.iter() {
+			assert!(src[*pos..].starts_with(&token.get_as_string()));
+		}
+		assert_eq!(tokens[0], (0, RawToken::SimpleString("タイトル".into())));
+		assert_eq!(tokens[1], (12, RawToken::Sep(Sep::Colon)));
+		assert_eq!(tokens[3], (14, RawToken::SimpleString("日本語".into())));
+		assert!(matches!(
+			tokens[0].1,
+			RawToken::SimpleString(Cow::Borrowed(_))
+		));
+	}
+
+	#[test]
+	fn test_asterisk_in_string() {
+		let tokens = StrTokenizer::new("a*b c**d*/ e*")
+			.map(|(_, token)| token)
+			.collect::<Vec<_>>();
+		assert_eq!(
+			tokens,
+			[
+				RawToken::SimpleString("a*b".into()),
+				RawToken::Sep(Sep::WhiteSpaces(" ".into())),
+				RawToken::SimpleString("c**d".into()),
+				RawToken::Comment(CommentMark::BlockEnd),
+				RawToken::Sep(Sep::WhiteSpaces(" ".into())),
+				RawToken::SimpleString("e*".into()),
+			]
+		);
+	}
+
+	#[test]
+	fn test_same_tokens_as_char_tokenizer() {
+		// 文字列中の `*` は `IngotTokenizer` が次の文字を読み飛ばすため、ここでは比べない
+		let src = "a: 'c d'\r\n/* x */ [e, f\\,g] // h\n{i: <j>}\t* / end";
+		let by_str = StrTokenizer::new(src)
+			.map(|(_, token)| token)
+			.collect::<Vec<_>>();
+		let mut char_tokenizer = IngotTokenizer::new(src.chars().collect());
+		let mut by_chars = Vec::new();
+		loop {
+			match char_tokenizer.next_raw_token() {
+				(_, RawToken::Eos) => break,
+				(_, token) => by_chars.push(token),
+			}
+		}
+		assert_eq!(by_str, by_chars);
+	}
+}
@@ -1,28 +1,32 @@
-use std::collections::BTreeMap;
+use std::{borrow::Cow, collections::BTreeMap};
 
 use super::{ingot::RKeyList, token_node::TokenNode};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// Tokens represent the separators and whitespace in the input buffer.
-pub enum Sep {
+pub enum Sep<'a> {
 	/// A comma separator.
 	Comma,
 	/// A colon separator.
 	Colon,
 	/// Whitespace characters.
-	WhiteSpaces(String),
+	WhiteSpaces(Cow<'a, str>),
 	/// A newline character.
 	NewLine,
 }
 
-impl Sep {
+impl Sep<'_> {
 	/// Returns the string representation of the separator.
 	pub fn get_as_string(&self) -> String {
+		self.as_str().to_string()
+	}
+	/// Returns the string slice representation of the separator.
+	pub fn as_str(&self) -> &str {
 		match self {
-			Sep::Comma => ",".to_string(),
-			Sep::Colon => ":".to_string(),
-			Sep::WhiteSpaces(s) => s.clone(),
-			Sep::NewLine => "\n".to_string(),
+			Sep::Comma => ",",
+			Sep::Colon => ":",
+			Sep::WhiteSpaces(s) => s,
+			Sep::NewLine => "\n",
 		}
 	}
 }
@@ -128,23 +132,28 @@ pub enum CommentMark {
 impl CommentMark {
 	/// Returns the comment mark as a `String`.
 	pub fn get_as_string(&self) -> String {
+		self.as_str().to_string()
+	}
+	/// Returns the comment mark as a string slice.
+	pub fn as_str(&self) -> &'static str {
 		match self {
-			CommentMark::LineBegin => "//".to_string(),
-			CommentMark::BlockBegin => "/*".to_string(),
-			CommentMark::BlockEnd => "*/".to_string(),
+			CommentMark::LineBegin => "//",
+			CommentMark::BlockBegin => "/*",
+			CommentMark::BlockEnd => "*/",
 		}
 	}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// Represents a raw token.
-pub enum RawToken {
+/// String tokens borrow the input buffer when the tokenizer works over `&str`.
+pub enum RawToken<'a> {
 	/// Represents a quote character (single or double).
 	Quote(Quote),
 	/// Represents a simple string token.
-	SimpleString(String),
+	SimpleString(Cow<'a, str>),
 	/// Represents a separator token (comma, colon, whitespace, or newline).
-	Sep(Sep),
+	Sep(Sep<'a>),
 	/// Represents a bracket token (curly, square, angle, or normal).
 	Bracket(Bracket),
 	/// Represents a comment token.
@@ -153,21 +162,28 @@ pub enum RawToken {
 	Eos,
 }
 
-impl RawToken {
+impl RawToken<'_> {
 	/// Returns `String` that token represents.
 	pub fn get_as_string(&self) -> String {
+		let mut result = String::new();
+		self.push_to(&mut result);
+		result
+	}
+	/// Pushes the string that token represents to the given buffer.
+	pub fn push_to(&self, buf: &mut String) {
 		match self {
-			RawToken::SimpleString(s) => s.clone(),
-			RawToken::Quote(q) => q.get_as_char().to_string(),
-			RawToken::Sep(s) => s.get_as_string(),
-			RawToken::Bracket(bracket) => bracket.get_as_char().to_string(),
-			RawToken::Comment(mark) => mark.get_as_string(),
-			RawToken::Eos => "".to_string(),
+			RawToken::SimpleString(s) => buf.push_str(s),
+			RawToken::Quote(q) => buf.push(q.get_as_char()),
+			RawToken::Sep(s) => buf.push_str(s.as_str()),
+			RawToken::Bracket(bracket) => buf.push(bracket.get_as_char()),
+			RawToken::Comment(mark) => buf.push_str(mark.as_str()),
+			RawToken::Eos => (),
 		}
 	}
 }
 /// Tuple of a raw token with its position in the input stream.
-pub type RawTokenData = (usize, RawToken);
+/// The position is a byte offset for `StrTokenizer` and a char index for `IngotTokenizer`.
+pub type RawTokenData<'a> = (usize, RawToken<'a>);
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// Block token
@@ -10,7 +10,8 @@ pub struct IngotTokenizer {
 	pub pos: usize,
 }
 
-const SYMBOL_CHARS: &str = "{}[]()<>,;: \t\n\r\"'/";
+/// Characters that end a simple string token.
+pub const SYMBOL_CHARS: &str = "{}[]()<>,;: \t\n\r\"'/";
 
 impl IngotTokenizer {
 	/// constructor
@@ -45,7 +46,7 @@ impl IngotTokenizer {
 		SYMBOL_CHARS.contains(c)
 	}
 
-	fn tokenize_new_line(&mut self) -> RawToken {
+	fn tokenize_new_line(&mut self) -> RawToken<'static> {
 		let next = self.peek_next_char();
 		match next {
 			Some('\n') => {
@@ -57,7 +58,7 @@ impl IngotTokenizer {
 		}
 	}
 
-	fn tokenize_whitespaces(&mut self, first_char: char) -> RawToken {
+	fn tokenize_whitespaces(&mut self, first_char: char) -> RawToken<'static> {
 		let mut result = String::from(first_char);
 		loop {
 			let next = self.peek_next_char();
@@ -75,10 +76,10 @@ impl IngotTokenizer {
 			}
 		}
 
-		RawToken::Sep(Sep::WhiteSpaces(result))
+		RawToken::Sep(Sep::WhiteSpaces(result.into()))
 	}
 
-	fn tokenize_after_slash(&mut self) -> RawToken {
+	fn tokenize_after_slash(&mut self) -> RawToken<'static> {
 		let next = self.peek_next_char();
 		match next {
 			Some('/') => {
@@ -89,22 +90,22 @@ impl IngotTokenizer {
 				self.pos_next();
 				RawToken::Comment(CommentMark::BlockBegin)
 			}
-			_ => RawToken::SimpleString('/'.to_string()),
+			_ => RawToken::SimpleString('/'.to_string().into()),
 		}
 	}
 
-	fn tokenize_after_asterisk(&mut self) -> RawToken {
+	fn tokenize_after_asterisk(&mut self) -> RawToken<'static> {
 		let next = self.peek_next_char();
 		match next {
 			Some('/') => {
 				self.pos_next();
 				RawToken::Comment(CommentMark::BlockEnd)
 			}
-			_ => RawToken::SimpleString('*'.to_string()),
+			_ => RawToken::SimpleString('*'.to_string().into()),
 		}
 	}
 
-	fn tokenize_string(&mut self, first_char: char) -> RawToken {
+	fn tokenize_string(&mut self, first_char: char) -> RawToken<'static> {
 		let mut result = String::from(first_char);
 		if first_char == '\\'
 			&& let Some(nc) = self.peek_next_char()
@@ -152,13 +153,13 @@ impl IngotTokenizer {
 				_ => break,
 			}
 		}
-		RawToken::SimpleString(result)
+		RawToken::SimpleString(result.into())
 	}
 	/// Returns the next raw token from chars.
-	pub fn next_raw_token(&mut self) -> RawTokenData {
+	pub fn next_raw_token(&mut self) -> RawTokenData<'static> {
 		let pos = self.pos;
 		let next_char = self.next_char();
-		let token: RawToken = if let Some(c) = next_char {
+		let token: RawToken<'static> = if let Some(c) = next_char {
 			match c {
 				'\'' => RawToken::Quote(Quote::Single),
 				'"' => RawToken::Quote(Quote::Double),
@@ -207,13 +208,13 @@ mod tests {
 		let mut tokenizer = IngotTokenizer::new("aaa:bbb".chars().collect());
 		let (pos, token) = tokenizer.next_raw_token();
 		assert_eq!(pos, 0);
-		assert_eq!(token, RawToken::SimpleString("aaa".to_string()));
+		assert_eq!(token, RawToken::SimpleString("aaa".to_string().into()));
 		let (pos, token) = tokenizer.next_raw_token();
 		assert_eq!(pos, 3);
 		assert_eq!(token, RawToken::Sep(Sep::Colon));
 		let (pos, token) = tokenizer.next_raw_token();
 		assert_eq!(pos, 4);
-		assert_eq!(token, RawToken::SimpleString("bbb".to_string()));
+		assert_eq!(token, RawToken::SimpleString("bbb".to_string().into()));
 	}
 
 	#[test]
@@ -221,13 +222,16 @@ mod tests {
 		let mut tokenizer = IngotTokenizer::new("aaa: {bbb: ccc}".chars().collect());
 		let (pos, token) = tokenizer.next_raw_token();
 		assert_eq!(pos, 0);
-		assert_eq!(token, RawToken::SimpleString("aaa".to_string()));
+		assert_eq!(token, RawToken::SimpleString("aaa".to_string().into()));
 		let (pos, token) = tokenizer.next_raw_token();
 		assert_eq!(pos, 3);
 		assert_eq!(token, RawToken::Sep(Sep::Colon));
 		let (pos, token) = tokenizer.next_raw_token();
 		assert_eq!(pos, 4);
-		assert_eq!(token, RawToken::Sep(Sep::WhiteSpaces(" ".to_string())));
+		assert_eq!(
+			token,
+			RawToken::Sep(Sep::WhiteSpaces(" ".to_string().into()))
+		);
 		let (pos, token) = tokenizer.next_raw_token();
 		assert_eq!(pos, 5);
 		assert_eq!(
@@ -236,16 +240,19 @@ mod tests {
 		);
 		let (pos, token) = tokenizer.next_raw_token();
 		assert_eq!(pos, 6);
-		assert_eq!(token, RawToken::SimpleString("bbb".to_string()));
+		assert_eq!(token, RawToken::SimpleString("bbb".to_string().into()));
 		let (pos, token) = tokenizer.next_raw_token();
 		assert_eq!(pos, 9);
 		assert_eq!(token, RawToken::Sep(Sep::Colon));
 		let (pos, token) = tokenizer.next_raw_token();
 		assert_eq!(pos, 10);
-		assert_eq!(token, RawToken::Sep(Sep::WhiteSpaces(" ".to_string())));
+		assert_eq!(
+			token,
+			RawToken::Sep(Sep::WhiteSpaces(" ".to_string().into()))
+		);
 		let (pos, token) = tokenizer.next_raw_token();
 		assert_eq!(pos, 11);
-		assert_eq!(token, RawToken::SimpleString("ccc".to_string()));
+		assert_eq!(token, RawToken::SimpleString("ccc".to_string().into()));
 		let (pos, token) = tokenizer.next_raw_token();
 		assert_eq!(pos, 14);
 		assert_eq!(
@@ -0,0 +1,139 @@
+use std::fmt::Write;
+
+use jiff::Timestamp;
+
+use super::{
+	Ingot,
+	ingot::{CommentStatus, RKeyList, RKeyRaw, To},
+};
+
+/// Returns the value quoted for the front matter.
+/// Newlines are replaced with spaces since a quoted value ends at the end of the line.
+pub fn quote_matter_value(value: &str) -> String {
+	let value = value.replace("\r\n", " ").replace(['\n', '\r'], " ");
+	if !value.contains('"') {
+		format!("\"{value}\"")
+	} else if !value.contains('\'') {
+		format!("'{value}'")
+	} else {
+		// 両方の引用符を含む値は閉じられなくなるため、二重引用符を置き換える
+		format!("\"{}\"", value.replace('"', "'"))
+	}
+}
+
+fn key_list_value(list: &RKeyList) -> Option<String> {
+	let keys = match list {
+		RKeyList::Raw(raw) => raw
+			.iter()
+			.filter(|r| !r.is_empty())
+			.map(|r| match r {
+				RKeyRaw::Usize(id) => id.to_string(),
+				RKeyRaw::String(s) => quote_matter_value(s),
+			})
+			.collect::<Vec<_>>(),
+		RKeyList::CollatedId(ids) => ids.iter().map(|id| id.to_string()).collect(),
+	};
+	(!keys.is_empty()).then(|| format!("[{}]", keys.join(", ")))
+}
+
+/// Returns the ingot written in the ingot file format, which `IngotParser` reads back.
+/// Default values are omitted from the front matter.
+pub fn ingot_to_string(ingot: &Ingot) -> String {
+	let mut out = String::new();
+	let _ = writeln!(out, "id: {}", ingot.id);
+	let _ = writeln!(
+		out,
+		"status: {}",
+		ingot.status.to_string().to_ascii_lowercase()
+	);
+	if !matches!(ingot.to, To::Post) {
+		let _ = writeln!(out, "to: {}", quote_matter_value(ingot.to.name()));
+	}
+	if !ingot.pname.is_empty() {
+		let _ = writeln!(out, "pname: {}", quote_matter_value(&ingot.pname));
+	}
+	if !ingot.lang.is_empty() {
+		let _ = writeln!(out, "lang: {}", quote_matter_value(&ingot.lang));
+	}
+	if !ingot.translation_key.is_empty() {
+		let _ = writeln!(
+			out,
+			"translation_key: {}",
+			quote_matter_value(&ingot.translation_key)
+		);
+	}
+	if ingot.author != 0 {
+		let _ = writeln!(out, "author: {}", ingot.author);
+	}
+	if matches!(ingot.comment_status, CommentStatus::Open) {
+		let _ = writeln!(out, "comment_status: open");
+	}
+	if ingot.published != Timestamp::default() {
+		let _ = writeln!(out, "published: {}", ingot.published);
+	}
+	if ingot.updated != Timestamp::default() {
+		let _ = writeln!(out, "updated: {}", ingot.updated);
+	}
+	if let Some(tags) = key_list_value(&ingot.tags) {
+		let _ = writeln!(out, "tags: {tags}");
+	}
+	if let Some(categories) = key_list_value(&ingot.categories) {
+		let _ = writeln!(out, "categories: {categories}");
+	}
+	if !ingot.excerpt.trim().is_empty() {
+		let _ = writeln!(out, "excerpt: {}", quote_matter_value(ingot.excerpt.trim()));
+	}
+	out.push('\n');
+	// タイトルは1行でなければならない
+	let title = ingot.title.replace(['\n', '\r'], " ");
+	let _ = writeln!(out, "{}", title.trim());
+	out.push('\n');
+	let content = ingot.content.trim_end();
+	out.push_str(content);
+	out.push('\n');
+	if content.contains("\n\n\n") || content.contains("\r\n\r\n\r\n") {
+		// 本文中の連続した空行が後ろのフロントマターの区切りとして読まれないよう、空の後ろのフロントマターを置く
+		out.push_str("\n\n");
+	}
+	out
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app::ingot::ingot::Status;
+
+	#[test]
+	fn test_ingot_to_string_round_trip() {
+		let mut ingot = Ingot::new(12);
+		ingot.status = Status::Publish;
+		ingot.to = To::Page;
+		ingot.pname = "about/team".to_string();
+		ingot.author = 2;
+		ingot.lang = "en".to_string();
+		ingot.translation_key = "about".to_string();
+		ingot.comment_status = CommentStatus::Open;
+		ingot.published = "2020-01-02T03:04:05Z".parse().unwrap();
+		ingot.tags = RKeyList::from(vec!["rust".to_string(), "static site".to_string()]);
+		ingot.excerpt = "a: \"b\" // c".to_string();
+		ingot.title = "Title: here".to_string();
+		ingot.content = "Body\n\nhttp://example.com/\n\n\n```\na\n\n\nb\n```\n".to_string();
+
+		let parsed = Ingot::read(ingot_to_string(&ingot).as_bytes()).unwrap();
+		assert_eq!(parsed.id, 12);
+		assert!(matches!(parsed.status, Status::Publish));
+		assert!(matches!(parsed.to, To::Page));
+		assert_eq!(parsed.pname, "about/team");
+		assert_eq!(parsed.author, 2);
+		assert_eq!(parsed.lang, "en");
+		assert_eq!(parsed.translation_key, "about");
+		assert!(matches!(parsed.comment_status, CommentStatus::Open));
+		assert_eq!(parsed.published, ingot.published);
+		assert!(
+			matches!(&parsed.tags, RKeyList::Raw(raw) if raw.iter().map(|r| r.to_string()).collect::<Vec<_>>() == ["rust", "static site"])
+		);
+		assert_eq!(parsed.excerpt, ingot.excerpt);
+		assert_eq!(parsed.title, "Title: here");
+		assert_eq!(parsed.content, ingot.content.trim_end());
+	}
+}
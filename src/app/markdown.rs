@@ -0,0 +1,123 @@
+use pulldown_cmark::{Options, Parser, html};
+
+/// Returns the markdown options used by nibi.
+pub fn markdown_options() -> Options {
+	Options::ENABLE_TABLES
+		| Options::ENABLE_FOOTNOTES
+		| Options::ENABLE_STRIKETHROUGH
+		| Options::ENABLE_TASKLISTS
+}
+
+/// Converts the given markdown source into HTML.
+pub fn markdown_to_html(src: &str) -> String {
+	let parser = Parser::new_ext(src, markdown_options());
+	let mut result = String::with_capacity(src.len() * 3 / 2);
+	html::push_html(&mut result, parser);
+	result
+}
+
+/// Escapes the characters which have special meanings in HTML text and attribute values.
+pub fn escape_html(s: &str) -> String {
+	let mut escaped = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&#39;"),
+			_ => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+/// Returns the string with percent-encoded bytes decoded, such as a URL path or a slug.
+/// Invalid UTF-8 sequences are replaced.
+pub fn percent_decode(s: &str) -> String {
+	let bytes = s.as_bytes();
+	let mut out = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		if bytes[i] == b'%'
+			&& let (Some(h), Some(l)) = (
+				bytes.get(i + 1).and_then(|b| (*b as char).to_digit(16)),
+				bytes.get(i + 2).and_then(|b| (*b as char).to_digit(16)),
+			) {
+			out.push((h * 16 + l) as u8);
+			i += 3;
+			continue;
+		}
+		out.push(bytes[i]);
+		i += 1;
+	}
+	String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Converts the given HTML into plain text.
+/// Tags and comments are removed, basic entities are decoded and whitespaces are collapsed.
+pub fn html_to_text(html: &str) -> String {
+	let mut stripped = String::with_capacity(html.len());
+	let mut rest = html;
+	while let Some(pos) = rest.find('<') {
+		stripped.push_str(&rest[..pos]);
+		rest = &rest[pos..];
+		let end = if rest.starts_with("<!--") {
+			rest.find("-->").map(|p| p + 3)
+		} else {
+			rest.find('>').map(|p| p + 1)
+		};
+		match end {
+			Some(end) => {
+				// ブロック要素のタグは空白として扱い、前後の単語がくっつかないようにする
+				if is_block_tag(&rest[..end]) {
+					stripped.push(' ');
+				}
+				rest = &rest[end..];
+			}
+			None => {
+				rest = "";
+			}
+		}
+	}
+	stripped.push_str(rest);
+
+	let decoded = decode_basic_entities(&stripped);
+	decoded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_block_tag(tag: &str) -> bool {
+	let name: String = tag
+		.trim_start_matches('<')
+		.trim_start_matches('/')
+		.chars()
+		.take_while(|c| c.is_ascii_alphanumeric())
+		.collect::<String>()
+		.to_ascii_lowercase();
+	matches!(
+		name.as_str(),
+		"p" | "br"
+			| "hr" | "div"
+			| "li" | "ul"
+			| "ol" | "dl"
+			| "dt" | "dd"
+			| "h1" | "h2"
+			| "h3" | "h4"
+			| "h5" | "h6"
+			| "blockquote"
+			| "pre"
+			| "table"
+			| "tr" | "th"
+			| "td"
+	)
+}
+
+/// Decodes the basic entities which [`escape_html`] and the Markdown renderer produce.
+pub fn decode_basic_entities(s: &str) -> String {
+	s.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&#39;", "'")
+		.replace("&nbsp;", " ")
+		.replace("&amp;", "&")
+}
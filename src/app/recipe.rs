@@ -5,17 +5,20 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-use crate::app::{
-	config::Config,
-	fs::{
-		io::{new_empty_file, open_file_with_read_mode},
-		path::append_ext,
-	},
-	serde::{
-		DeError, DeResult, FileType, StrValOrArray, read_deserialized_value,
-		write_serialized_string_all,
+use crate::{
+	app::{
+		config::Config,
+		fs::io::new_empty_file,
+		ingot::ingot::RKeyRaw,
+		serde::{
+			DeResult, FileType, StrValOrArray, find_path_with_file_type, get_extended_path,
+			read_deserialized_file, write_serialized_string_all,
+		},
+		value::{ValueMap, merge_values},
 	},
+	msg,
 };
+#[derive(Debug, Clone, Serialize, Deserialize)]
 /// Recipe struct. Holds pack list, igata table, and values for building a site.
 pub struct Recipe {
 	/// List of pack names to build a site.
@@ -23,7 +26,15 @@ pub struct Recipe {
 	/// Igata table for template rendering.
 	pub igata_table: BTreeMap<String, String>,
 	/// Values for template rendering.
-	pub values: BTreeMap<String, String>,
+	pub values: ValueMap,
+	/// Navigation menus, keyed by the menu name.
+	pub menus: BTreeMap<String, Vec<MenuEntry>>,
+	/// Settings of the JSON API output.
+	pub api: ApiConf,
+	/// Settings of the client-side search index.
+	pub search: SearchConf,
+	/// Settings of the image processing.
+	pub images: ImagesConf,
 }
 /// Returns the default igata table.
 pub fn default_igata_table() -> BTreeMap<String, String> {
@@ -34,9 +45,9 @@ pub fn default_igata_table() -> BTreeMap<String, String> {
 }
 
 /// Returns the default values for template rendering.
-pub fn default_values(site_name: &str) -> BTreeMap<String, String> {
-	let mut values = BTreeMap::new();
-	values.insert("site_name".to_string(), site_name.to_string());
+pub fn default_values(site_name: &str) -> ValueMap {
+	let mut values = ValueMap::new();
+	values.insert("site_name".to_string(), site_name.into());
 	values
 }
 
@@ -45,29 +56,55 @@ impl Recipe {
 	pub fn new_with_all_fields(
 		pack: Vec<String>,
 		igata_table: BTreeMap<String, String>,
-		values: BTreeMap<String, String>,
+		values: ValueMap,
+		menus: BTreeMap<String, Vec<MenuEntry>>,
+		api: ApiConf,
+		search: SearchConf,
+		images: ImagesConf,
 	) -> Self {
 		Self {
 			pack,
 			igata_table,
 			values,
+			menus,
+			api,
+			search,
+			images,
 		}
 	}
 
 	/// Creates a new `Recipe` with the given config and settings.
 	pub fn new(config: &Config, settings: RecipeSettings) -> Self {
-		let (pack, overrides) = settings.take_fields();
+		let RecipeSettings {
+			pack,
+			overrides,
+			menus,
+			api,
+			search,
+			images,
+		} = settings;
+		let pack = pack.take_inner();
 		let mut igata_table = default_igata_table();
 		igata_table.extend(overrides.igata_table);
 		let mut values = default_values(config.site_name_ref());
-		values.extend(overrides.values);
-		Self::new_with_all_fields(pack, igata_table, values)
+		merge_values(&mut values, overrides.values);
+		Self::new_with_all_fields(pack, igata_table, values, menus, api, search, images)
 	}
 
 	/// Returns the pack names for this recipe.
 	pub fn get_pack_names(&self) -> &[String] {
 		&self.pack
 	}
+	/// Returns the pack names for this recipe, deduplicated. The first occurrence order is preserved.
+	pub fn get_pack_names_dedup_ordered(&self) -> Vec<String> {
+		let mut vec: Vec<String> = Vec::with_capacity(self.pack.len());
+		for name in self.pack.iter() {
+			if !vec.contains(name) {
+				vec.push(name.clone());
+			}
+		}
+		vec
+	}
 	/// Returns the pack names for this recipe, deduplicated. The order is not preserved.
 	pub fn get_pack_names_dedup(&self) -> Vec<String> {
 		let mut vec: Vec<String> = self.pack.clone();
@@ -85,7 +122,7 @@ pub struct Overrides {
 	pub igata_table: BTreeMap<String, String>,
 	#[serde(default)]
 	/// Value overrides.
-	pub values: BTreeMap<String, String>,
+	pub values: ValueMap,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -95,6 +132,184 @@ pub struct RecipeSettings {
 	pub pack: StrValOrArray,
 	/// Overrides for the igata table and values.
 	pub overrides: Overrides,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	/// Navigation menus, keyed by the menu name (e.g. main, footer).
+	pub menus: BTreeMap<String, Vec<MenuEntry>>,
+	#[serde(default, skip_serializing_if = "ApiConf::is_default")]
+	/// Settings of the JSON API output.
+	pub api: ApiConf,
+	#[serde(default, skip_serializing_if = "SearchConf::is_default")]
+	/// Settings of the client-side search index.
+	pub search: SearchConf,
+	#[serde(default, skip_serializing_if = "ImagesConf::is_default")]
+	/// Settings of the image processing.
+	pub images: ImagesConf,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Settings of the JSON API output, written under `api/` in the site directory.
+/// Every endpoint is disabled by default.
+pub struct ApiConf {
+	#[serde(default)]
+	/// Writes `api/ingots.json`, the list of the published ingots.
+	pub ingots: bool,
+	#[serde(default)]
+	/// Writes `api/ingots/<id>.json` for each published ingot, with the content.
+	pub ingot: bool,
+	#[serde(default)]
+	/// Writes `api/tags.json` and `api/tags/<path_name>.json` for each tag.
+	pub tags: bool,
+	#[serde(default)]
+	/// Writes `api/categories.json` and `api/categories/<path_name>.json` for each category.
+	pub categories: bool,
+	#[serde(default)]
+	/// Writes `api/site.json`, the site metadata.
+	pub site: bool,
+}
+
+impl ApiConf {
+	/// Returns `true` if the config is the default.
+	pub fn is_default(&self) -> bool {
+		self == &ApiConf::default()
+	}
+	/// Returns `true` if any endpoint is enabled.
+	pub fn is_enabled(&self) -> bool {
+		self.ingots || self.ingot || self.tags || self.categories || self.site
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Settings of the client-side search index, written to `search.json` in the site directory.
+/// The index is disabled by default.
+pub struct SearchConf {
+	#[serde(default)]
+	/// Writes the search index.
+	pub enabled: bool,
+	#[serde(default = "default_ngram")]
+	/// The length of the character n-grams for the text without spaces between words, such as Japanese.
+	pub ngram: usize,
+}
+
+fn default_ngram() -> usize {
+	2
+}
+
+impl Default for SearchConf {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			ngram: default_ngram(),
+		}
+	}
+}
+
+impl SearchConf {
+	/// Returns `true` if the config is the default.
+	pub fn is_default(&self) -> bool {
+		self == &SearchConf::default()
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Image formats written in addition to the format of the source image.
+pub enum ImageFormat {
+	/// WebP, encoded losslessly.
+	Webp,
+	/// AVIF.
+	Avif,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Settings of the image processing for the images in the resources directory referenced from ingots.
+/// Resized variants are written under `dir` in the site directory. The processing is disabled by default.
+pub struct ImagesConf {
+	#[serde(default)]
+	/// Processes the referenced images.
+	pub enabled: bool,
+	#[serde(default = "default_image_widths")]
+	/// The widths of the resized variants in pixels. Widths larger than the source image are skipped.
+	pub widths: Vec<u32>,
+	#[serde(default = "default_image_formats")]
+	/// The formats of the additional copies, offered with `<source>` elements of `<picture>`.
+	pub formats: Vec<ImageFormat>,
+	#[serde(default = "default_image_quality")]
+	/// The quality of the lossy encodings (JPEG and AVIF), from 1 to 100.
+	pub quality: u8,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	/// The `sizes` attribute of the images. Omitted if empty.
+	pub sizes: String,
+	#[serde(default = "default_image_dir")]
+	/// The output directory of the variants, relative to the site directory.
+	pub dir: String,
+}
+
+fn default_image_widths() -> Vec<u32> {
+	vec![480, 960, 1440]
+}
+
+fn default_image_formats() -> Vec<ImageFormat> {
+	vec![ImageFormat::Avif, ImageFormat::Webp]
+}
+
+fn default_image_quality() -> u8 {
+	80
+}
+
+fn default_image_dir() -> String {
+	"images".to_string()
+}
+
+impl Default for ImagesConf {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			widths: default_image_widths(),
+			formats: default_image_formats(),
+			quality: default_image_quality(),
+			sizes: String::new(),
+			dir: default_image_dir(),
+		}
+	}
+}
+
+impl ImagesConf {
+	/// Returns `true` if the config is the default.
+	pub fn is_default(&self) -> bool {
+		self == &ImagesConf::default()
+	}
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Entry of a navigation menu.
+/// The target is exactly one of an ingot (ID or pname), a category, a tag or an external URL.
+/// Category and tag pages are not built yet, so the entries linking them are reported as errors.
+pub struct MenuEntry {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The title of the entry. Defaults to the title or name of the target.
+	pub title: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The ingot ID or pname to link.
+	pub ingot: Option<RKeyRaw>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The category ID, name or path name to link.
+	pub category: Option<RKeyRaw>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The tag ID, name or path name to link.
+	pub tag: Option<RKeyRaw>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The external URL to link.
+	pub url: Option<String>,
+	#[serde(default, skip_serializing_if = "is_zero_weight")]
+	/// The weight of the entry. Entries with smaller weights come first.
+	pub weight: i64,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	/// The nested entries.
+	pub children: Vec<MenuEntry>,
+}
+
+fn is_zero_weight(weight: &i64) -> bool {
+	*weight == 0
 }
 
 impl RecipeSettings {
@@ -103,6 +318,10 @@ impl RecipeSettings {
 		Self {
 			pack: StrValOrArray(pack),
 			overrides,
+			menus: BTreeMap::new(),
+			api: ApiConf::default(),
+			search: SearchConf::default(),
+			images: ImagesConf::default(),
 		}
 	}
 
@@ -144,44 +363,50 @@ pub fn norm_recipe_name(recipe_name: String) -> String {
 		})
 		.collect()
 }
-/// Creates a new recipe file with the given name in the project directory.
-pub fn create_new_recipe(proj_dir_path: &Path, recipe_name: String) {
+/// Creates a new recipe file with the given name and file type in the project directory.
+pub fn create_new_recipe(proj_dir_path: &Path, recipe_name: String, file_type: FileType) {
 	let recipe_name = norm_recipe_name(recipe_name);
-	println!("begin {recipe_name} creation: {recipe_name}の作成を開始します");
+	println!("{}", msg!(BeginCreation, recipe_name));
 
-	let recipe_path = append_ext(proj_dir_path.join(&recipe_name), "ron");
+	let recipe_base_path = get_recipe_base_path(proj_dir_path, recipe_name);
+	if let Some((path, _)) = find_path_with_file_type(&recipe_base_path) {
+		println!("{}", msg!(RecipeExists, path.display()));
+		return;
+	}
+	let recipe_path = get_extended_path(recipe_base_path, file_type);
 
 	match new_empty_file(&recipe_path) {
 		Ok(file) => {
-			println!(
-				"create new recipe file {0}: 新しいレシピファイルを作成しました {0}",
-				recipe_path.display()
-			);
+			println!("{}", msg!(RecipeCreated, recipe_path.display()));
 			let _ = write_serialized_string_all(
 				file,
 				&RecipeSettings::new(vec!["default".to_string()], Overrides::default()),
-				FileType::Ron,
+				file_type,
 			);
 		}
 		Err(e) => {
-			println!("failed to create new recipe file: レシピファイルの作成に失敗しました - {e}");
+			println!("{}", msg!(RecipeCreateFailed, e));
 		}
 	}
 }
 /// Reads the recipe file for the current project and returns a `Recipe` struct.
+/// The recipe file can be written in any supported file type.
 pub fn read_recipe(config: &Config, proj_dir_path: &Path) -> DeResult<Recipe> {
-	let recipe_path = get_recipe_path(proj_dir_path, config.get_recipe().clone());
-	match open_file_with_read_mode(&recipe_path) {
-		Ok(file) => read_deserialized_value(file, FileType::Ron)
-			.map(|settings: RecipeSettings| Recipe::new(config, settings)),
-		Err(e) => {
-			println!("failed to read recipe file: レシピファイルの読み込みに失敗しました - {e}");
-			Err(DeError::IO(e))
-		}
-	}
+	read_named_recipe(config, proj_dir_path, config.get_recipe().clone())
+}
+
+/// Reads the recipe file with the given name and returns a `Recipe` struct.
+pub fn read_named_recipe(
+	config: &Config,
+	proj_dir_path: &Path,
+	recipe_name: String,
+) -> DeResult<Recipe> {
+	let recipe_base_path = get_recipe_base_path(proj_dir_path, recipe_name);
+	read_deserialized_file(&recipe_base_path).map(|settings| Recipe::new(config, settings))
 }
 
-fn get_recipe_path(proj_dir_path: &Path, recipe_name: String) -> PathBuf {
+/// Returns the base path (without extension) of the recipe file.
+pub fn get_recipe_base_path(proj_dir_path: &Path, recipe_name: String) -> PathBuf {
 	let recipe_name = norm_recipe_name(recipe_name);
-	append_ext(proj_dir_path.join(&recipe_name), "ron")
+	proj_dir_path.join(&recipe_name)
 }
@@ -1,7 +1,15 @@
-use std::{io, path::PathBuf};
+use std::{
+	io,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
 
 use ron::ser::PrettyConfig;
 use serde::{Deserialize, Serialize, ser::SerializeSeq};
+use strum::VariantNames;
+
+use super::fs::io::open_file_with_read_mode;
+use crate::msg;
 
 #[derive(
 	Debug,
@@ -46,6 +54,47 @@ pub fn get_extended_path<T: Into<PathBuf>>(path: T, file_type: FileType) -> Path
 	path
 }
 
+/// Finds the file which has the given base path and a file type extension.
+/// If the base path already has a file type extension, the path is used as is.
+/// Otherwise every extension in `FileType::VARIANTS` is probed in order, and the first existing file is returned.
+pub fn find_path_with_file_type(base_path: &Path) -> Option<(PathBuf, FileType)> {
+	if let Some(file_type) = base_path
+		.extension()
+		.and_then(|ext| FileType::from_str(&ext.to_string_lossy()).ok())
+		&& base_path.is_file()
+	{
+		return Some((base_path.to_path_buf(), file_type));
+	}
+	FileType::VARIANTS.iter().find_map(|ext| {
+		let file_type = FileType::from_str(ext).unwrap(); // VARIANTSから作るので失敗しない
+		let path = get_extended_path(base_path, file_type);
+		if path.is_file() {
+			Some((path, file_type))
+		} else {
+			None
+		}
+	})
+}
+
+/// Reads and deserializes the file found by `find_path_with_file_type`.
+pub fn read_deserialized_file<T: for<'de> serde::de::Deserialize<'de>>(
+	base_path: &Path,
+) -> DeResult<T> {
+	match find_path_with_file_type(base_path) {
+		Some((path, file_type)) => {
+			let file = open_file_with_read_mode(&path).map_err(DeError::IO)?;
+			read_deserialized_value(file, file_type)
+		}
+		None => Err(DeError::IO(io::Error::new(
+			io::ErrorKind::NotFound,
+			msg!(
+				FileNotFound,
+				format!("{}.[{}]", base_path.display(), FileType::VARIANTS.join("|"))
+			),
+		))),
+	}
+}
+
 #[derive(Debug, strum::Display)]
 /// Represents the error that can occur during serialization.
 pub enum SerError {
@@ -113,16 +162,22 @@ pub fn write_serialized_string_all<T: Serialize, W: std::io::Write>(
 /// Represents the error that can occur during deserialization.
 pub enum DeError {
 	/// Represents the RON deserialization error.
+	#[strum(to_string = "{0}")]
 	Ron(ron::de::SpannedError),
 	/// Represents the JSON deserialization error.
+	#[strum(to_string = "{0}")]
 	Json(serde_json::Error),
 	/// Represents the TOML deserialization error.
+	#[strum(to_string = "{0}")]
 	Toml(toml::de::Error),
 	/// Represents the IO error.
+	#[strum(to_string = "{0}")]
 	IO(io::Error),
 	/// Represents the XML deserialization error.
+	#[strum(to_string = "{0}")]
 	Xml(quick_xml::DeError),
 	/// Represents the HCL deserialization error.
+	#[strum(to_string = "{0}")]
 	Hcl(hcl::Error),
 }
 /// Type alias for deserialization results.
@@ -227,8 +282,9 @@ impl<'de> serde::de::Visitor<'de> for StrValOrArrayVisitor {
 mod test {
 	use strum::VariantNames;
 
-	use super::FileType;
-	use std::str::FromStr;
+	use super::{FileType, find_path_with_file_type, read_deserialized_file};
+	use crate::app::testing::TempDir;
+	use std::{collections::BTreeMap, fs, str::FromStr};
 
 	#[test]
 	fn test() {
@@ -293,4 +349,23 @@ mod test {
 	fn test_variants() {
 		assert_eq!(FileType::VARIANTS, ["ron", "toml", "json", "xml", "hcl"]);
 	}
+
+	#[test]
+	fn test_find_path_with_file_type() {
+		let dir = TempDir::new("serde");
+		let base = dir.join("values");
+		assert!(find_path_with_file_type(&base).is_none());
+
+		fs::write(dir.join("values.json"), r#"{"a": "b"}"#).unwrap();
+		fs::write(dir.join("values.toml"), "a = \"c\"").unwrap();
+		// VARIANTSの順に探すので、tomlが先に見つかる
+		let (path, file_type) = find_path_with_file_type(&base).unwrap();
+		assert_eq!(path, dir.join("values.toml"));
+		assert_eq!(file_type, FileType::Toml);
+		let (_, file_type) = find_path_with_file_type(&dir.join("values.json")).unwrap();
+		assert_eq!(file_type, FileType::Json);
+
+		let values: BTreeMap<String, String> = read_deserialized_file(&base).unwrap();
+		assert_eq!(values.get("a").map(|s| s.as_str()), Some("c"));
+	}
 }
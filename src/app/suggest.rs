@@ -0,0 +1,56 @@
+use strsim::osa_distance;
+
+use crate::msg;
+
+/// Returns the candidates which are near-miss names of the given name, nearest first.
+pub fn near_miss_names<'a, I: IntoIterator<Item = &'a str>>(
+	name: &str,
+	candidates: I,
+) -> Vec<String> {
+	let max_distance = (name.chars().count() / 3).max(1);
+	let mut found = candidates
+		.into_iter()
+		.filter(|candidate| *candidate != name)
+		.filter_map(|candidate| {
+			let distance = osa_distance(&name.to_lowercase(), &candidate.to_lowercase());
+			(distance <= max_distance).then(|| (distance, candidate.to_string()))
+		})
+		.collect::<Vec<_>>();
+	found.sort();
+	found.dedup();
+	found.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+/// Returns the suffix for a message that suggests the given names, or an empty string.
+pub fn did_you_mean(suggestions: &[String]) -> String {
+	if suggestions.is_empty() {
+		String::new()
+	} else {
+		msg!(DidYouMean, suggestions.join(", "))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_near_miss_names() {
+		let candidates = ["default", "defualt", "post", "posts", "blog-theme"];
+		assert_eq!(
+			near_miss_names("defalt", candidates),
+			vec!["default".to_string(), "defualt".to_string()]
+		);
+		assert_eq!(
+			near_miss_names("post", candidates),
+			vec!["posts".to_string()]
+		);
+		assert_eq!(
+			near_miss_names("psot", candidates),
+			vec!["post".to_string()]
+		);
+		assert!(near_miss_names("zzz", candidates).is_empty());
+		assert_eq!(did_you_mean(&[]), "");
+		assert_eq!(did_you_mean(&["a".to_string()]), " (did you mean: a?)");
+	}
+}
@@ -4,7 +4,11 @@ use std::{
 	path::{Path, PathBuf},
 };
 
-use super::serde::{DeResult, FileType, read_deserialized_value};
+use super::{
+	i18n::localized_attr,
+	serde::{DeResult, FileType, read_deserialized_file, read_deserialized_value},
+};
+use crate::msg;
 
 #[derive(Debug, Deserialize, Serialize)]
 /// Represents a tag for ingot files classification.
@@ -33,22 +37,34 @@ impl Tag {
 			opt_attr: None,
 		}
 	}
+
+	/// Returns the name of the tag in the given language. `name.<lang>` in the optional attributes is used if exists.
+	pub fn name_in(&self, lang: &str) -> &str {
+		localized_attr(&self.opt_attr, "name", lang).unwrap_or(&self.name)
+	}
+
+	/// Returns the description of the tag in the given language. `description.<lang>` in the optional attributes is used if exists.
+	pub fn description_in(&self, lang: &str) -> &str {
+		localized_attr(&self.opt_attr, "description", lang).unwrap_or(&self.description)
+	}
 }
 
-fn tags_file_path(dir_path: &Path) -> PathBuf {
-	dir_path.join("tags.ron")
+/// Returns the base path (without extension) of the tags list file in the given directory path.
+pub fn tags_file_base_path(dir_path: &Path) -> PathBuf {
+	dir_path.join("tags")
 }
 
-fn read_tags<R: std::io::Read>(reader: R, file_type: FileType) -> DeResult<Vec<Tag>> {
+/// Reads the tags from the given reader.
+pub fn read_tags<R: std::io::Read>(reader: R, file_type: FileType) -> DeResult<Vec<Tag>> {
 	read_deserialized_value(reader, file_type)
 }
 
 /// Reads the tags from the tags list file in the given directory path.
+/// The tags list file can be written in any supported file type.
 pub fn get_tags_from_dir_path(dir_path: &Path) -> Option<Vec<Tag>> {
-	let file_path = tags_file_path(dir_path);
-	println!("read tags from: {}", file_path.display());
-	let file = std::fs::File::open(file_path).ok()?;
-	read_tags(file, FileType::Ron).ok()
+	let file_path = tags_file_base_path(dir_path);
+	println!("{}", msg!(ReadTagsFrom, file_path.display()));
+	read_deserialized_file(&file_path).ok()
 }
 
 /// Returns a map of tag IDs to tags for the given list of tags.
@@ -0,0 +1,95 @@
+use std::{
+	fs::{self, File},
+	ops::Deref,
+	path::{Path, PathBuf},
+};
+
+use crate::app::{
+	config::Config,
+	igata::pack::{PackConfig, PackInfo},
+	serde::{FileType, write_serialized_string_all},
+};
+
+/// A temporary directory for a test. Stale contents are removed on creation,
+/// and the directory is removed on drop, even if the test fails.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+	/// Creates an empty directory named after the test and the process ID.
+	pub fn new(name: &str) -> Self {
+		let path = std::env::temp_dir().join(format!("nibi_{name}_test_{}", std::process::id()));
+		let _ = fs::remove_dir_all(&path);
+		fs::create_dir_all(&path).unwrap();
+		Self(path)
+	}
+}
+
+impl Deref for TempDir {
+	type Target = Path;
+	fn deref(&self) -> &Path {
+		&self.0
+	}
+}
+
+impl AsRef<Path> for TempDir {
+	fn as_ref(&self) -> &Path {
+		&self.0
+	}
+}
+
+impl Drop for TempDir {
+	fn drop(&mut self) {
+		let _ = fs::remove_dir_all(&self.0);
+	}
+}
+
+/// Writes a pack with the name, version and dependencies into the directory.
+/// The pack has the default `pack_config` and a `post` template.
+pub fn write_pack(dir: &Path, name: &str, version: &str, depends: &[(&str, &str)]) {
+	fs::create_dir_all(dir).unwrap();
+	let mut info = PackInfo::new(name);
+	info.version = version.to_string();
+	info.depends = depends
+		.iter()
+		.map(|(n, r)| (n.to_string(), r.to_string()))
+		.collect();
+	let file = File::create(dir.join("pack_info.ron")).unwrap();
+	write_serialized_string_all(file, &info, FileType::Ron).unwrap();
+	let file = File::create(dir.join("pack_config.ron")).unwrap();
+	write_serialized_string_all(file, &PackConfig::default(), FileType::Ron).unwrap();
+	fs::write(dir.join("post.html"), "{{ ingot.title }}").unwrap();
+}
+
+const TEMPLATE: &str = "{{ ingot.title }}\n{{ ingot.content }}";
+
+/// Writes a published ingot into the `zairyo` directory of the project.
+pub fn write_ingot(dir: &Path, file: &str, id: usize, pname: &str, title: &str) {
+	let src = format!("id: {id}\nstatus: publish\npname: {pname}\n\n{title}\n\nbody **{id}**\n");
+	fs::write(dir.join("zairyo").join(file), src).unwrap();
+}
+
+/// Writes a project with the pack `theme`, a recipe using it and 40 posts into the directory,
+/// and returns the config of the project.
+pub fn write_project(dir: &Path) -> Config {
+	let pack_dir = dir.join("igata/theme");
+	write_pack(&pack_dir, "theme", "1.0.0", &[]);
+	for name in ["index", "post", "page", "list", "default"] {
+		fs::write(pack_dir.join(format!("{name}.html")), TEMPLATE).unwrap();
+	}
+	fs::write(
+		dir.join("recipe.ron"),
+		r#"(pack: "theme", overrides: (igata_table: {}, values: {}))"#,
+	)
+	.unwrap();
+	fs::create_dir_all(dir.join("zairyo")).unwrap();
+	for id in 1..=40 {
+		write_ingot(
+			dir,
+			&format!("p{id:02}.ingot"),
+			id,
+			&format!("post{id}"),
+			&format!("Post {id}"),
+		);
+	}
+	Config::new("test".to_string(), "test".to_string())
+}
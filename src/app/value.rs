@@ -0,0 +1,304 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{
+	Deserialize, Serialize,
+	de::{MapAccess, SeqAccess, Visitor},
+};
+
+/// Map of typed values, used for recipe values, pack values and profile values.
+pub type ValueMap = BTreeMap<String, RecipeValue>;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+/// Typed value written in recipes, pack configs and profiles.
+/// Deserializes from any supported file type and is passed to templates as a native value.
+/// XML has no scalar types, so the values written in XML are always strings (or lists and maps of strings),
+/// and they do not satisfy `bool`, `int` or `float` in the schemas of packs.
+pub enum RecipeValue {
+	/// Represents an empty value.
+	Null,
+	/// Represents a boolean value.
+	Bool(bool),
+	/// Represents an integer value.
+	Int(i64),
+	/// Represents a floating point value.
+	Float(f64),
+	/// Represents a string value.
+	String(String),
+	/// Represents a list of values.
+	List(Vec<RecipeValue>),
+	/// Represents a nested map of values.
+	Map(ValueMap),
+}
+
+impl RecipeValue {
+	/// Returns the type name of the value.
+	pub fn type_name(&self) -> &'static str {
+		match self {
+			RecipeValue::Null => "null",
+			RecipeValue::Bool(_) => "bool",
+			RecipeValue::Int(_) => "int",
+			RecipeValue::Float(_) => "float",
+			RecipeValue::String(_) => "string",
+			RecipeValue::List(_) => "list",
+			RecipeValue::Map(_) => "map",
+		}
+	}
+
+	/// Returns the string slice if the value is a string.
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			RecipeValue::String(s) => Some(s),
+			_ => None,
+		}
+	}
+}
+
+impl From<&str> for RecipeValue {
+	fn from(value: &str) -> Self {
+		RecipeValue::String(value.to_string())
+	}
+}
+
+impl From<String> for RecipeValue {
+	fn from(value: String) -> Self {
+		RecipeValue::String(value)
+	}
+}
+
+impl From<bool> for RecipeValue {
+	fn from(value: bool) -> Self {
+		RecipeValue::Bool(value)
+	}
+}
+
+impl From<i64> for RecipeValue {
+	fn from(value: i64) -> Self {
+		RecipeValue::Int(value)
+	}
+}
+
+impl From<f64> for RecipeValue {
+	fn from(value: f64) -> Self {
+		RecipeValue::Float(value)
+	}
+}
+
+impl fmt::Display for RecipeValue {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			RecipeValue::Null => write!(f, "null"),
+			RecipeValue::Bool(b) => write!(f, "{b}"),
+			RecipeValue::Int(i) => write!(f, "{i}"),
+			RecipeValue::Float(x) => write!(f, "{x}"),
+			RecipeValue::String(s) => write!(f, "{s:?}"),
+			RecipeValue::List(list) => {
+				write!(f, "[")?;
+				for (i, v) in list.iter().enumerate() {
+					if i > 0 {
+						write!(f, ", ")?;
+					}
+					write!(f, "{v}")?;
+				}
+				write!(f, "]")
+			}
+			RecipeValue::Map(map) => {
+				write!(f, "{{")?;
+				for (i, (k, v)) in map.iter().enumerate() {
+					if i > 0 {
+						write!(f, ", ")?;
+					}
+					write!(f, "{k:?}: {v}")?;
+				}
+				write!(f, "}}")
+			}
+		}
+	}
+}
+
+/// Merges the values of `over` into `base`.
+/// Nested maps are merged recursively and the other values of `over` replace those of `base`.
+pub fn merge_values(base: &mut ValueMap, over: ValueMap) {
+	for (key, value) in over {
+		match (base.get_mut(&key), value) {
+			(Some(RecipeValue::Map(base_map)), RecipeValue::Map(over_map)) => {
+				merge_values(base_map, over_map);
+			}
+			(_, value) => {
+				base.insert(key, value);
+			}
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for RecipeValue {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		deserializer.deserialize_any(RecipeValueVisitor)
+	}
+}
+
+struct RecipeValueVisitor;
+
+impl<'de> Visitor<'de> for RecipeValueVisitor {
+	type Value = RecipeValue;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		formatter.write_str("bool, number, string, list or map")
+	}
+
+	fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
+		Ok(RecipeValue::Bool(v))
+	}
+
+	fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+		Ok(RecipeValue::Int(v))
+	}
+
+	fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+		Ok(match i64::try_from(v) {
+			Ok(i) => RecipeValue::Int(i),
+			Err(_) => RecipeValue::Float(v as f64),
+		})
+	}
+
+	fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+		Ok(RecipeValue::Float(v))
+	}
+
+	fn visit_char<E>(self, v: char) -> Result<Self::Value, E> {
+		Ok(RecipeValue::String(v.to_string()))
+	}
+
+	fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+		Ok(RecipeValue::String(v.to_owned()))
+	}
+
+	fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+		Ok(RecipeValue::String(v))
+	}
+
+	fn visit_unit<E>(self) -> Result<Self::Value, E> {
+		Ok(RecipeValue::Null)
+	}
+
+	fn visit_none<E>(self) -> Result<Self::Value, E> {
+		Ok(RecipeValue::Null)
+	}
+
+	fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		RecipeValue::deserialize(deserializer)
+	}
+
+	fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		RecipeValue::deserialize(deserializer)
+	}
+
+	fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+	where
+		A: SeqAccess<'de>,
+	{
+		let mut list = Vec::new();
+		while let Some(value) = seq.next_element()? {
+			list.push(value);
+		}
+		Ok(RecipeValue::List(list))
+	}
+
+	fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+	where
+		A: MapAccess<'de>,
+	{
+		let mut map = ValueMap::new();
+		while let Some((key, value)) = access.next_entry::<String, RecipeValue>()? {
+			map.insert(key, value);
+		}
+		// XMLでは要素のテキストが`$text`キーの値になるので、テキストだけの要素は文字列として扱う
+		// 数値や真偽値には変換しない。スキーマがなければ文字列の"10"と区別できないため
+		if map.len() == 1
+			&& let Some(text) = map.remove("$text").or_else(|| map.remove("$value"))
+		{
+			return Ok(text);
+		}
+		Ok(RecipeValue::Map(map))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::app::serde::{FileType, get_deselialized_value};
+
+	use super::*;
+
+	#[test]
+	fn test_deserialize_typed_values() {
+		let ron = r#"{"n": 3, "f": 1.5, "b": true, "s": "x", "l": [1, "a"], "m": {"k": false}}"#;
+		let json = r#"{"n": 3, "f": 1.5, "b": true, "s": "x", "l": [1, "a"], "m": {"k": false}}"#;
+		let toml = "n = 3\nf = 1.5\nb = true\ns = \"x\"\nl = [1, \"a\"]\n[m]\nk = false\n";
+		let mut expected = ValueMap::new();
+		expected.insert("n".into(), RecipeValue::Int(3));
+		expected.insert("f".into(), RecipeValue::Float(1.5));
+		expected.insert("b".into(), RecipeValue::Bool(true));
+		expected.insert("s".into(), "x".into());
+		expected.insert(
+			"l".into(),
+			RecipeValue::List(vec![RecipeValue::Int(1), "a".into()]),
+		);
+		expected.insert(
+			"m".into(),
+			RecipeValue::Map([("k".to_string(), RecipeValue::Bool(false))].into()),
+		);
+		for (src, file_type) in [
+			(ron, FileType::Ron),
+			(json, FileType::Json),
+			(toml, FileType::Toml),
+		] {
+			let values: ValueMap = get_deselialized_value(src, file_type).unwrap();
+			assert_eq!(values, expected, "{file_type}");
+		}
+	}
+
+	#[test]
+	fn test_deserialize_string_values() {
+		let xml = "<values><lang>ja</lang><author>nibi</author></values>";
+		let values: ValueMap = get_deselialized_value(xml, FileType::Xml).unwrap();
+		assert_eq!(values.get("lang"), Some(&"ja".into()));
+		assert_eq!(values.get("author"), Some(&"nibi".into()));
+
+		// XMLの値は数値に見えても文字列のまま
+		let xml = "<values><per_page>10</per_page><draft>true</draft></values>";
+		let values: ValueMap = get_deselialized_value(xml, FileType::Xml).unwrap();
+		assert_eq!(values.get("per_page"), Some(&"10".into()));
+		assert_eq!(values.get("draft"), Some(&"true".into()));
+
+		let ron = r#"{"lang": "ja"}"#;
+		let values: ValueMap = get_deselialized_value(ron, FileType::Ron).unwrap();
+		assert_eq!(values.get("lang"), Some(&"ja".into()));
+	}
+
+	#[test]
+	fn test_merge_values() {
+		let mut base: ValueMap = get_deselialized_value(
+			r#"{"a": 1, "social": {"x": "a", "y": "b"}}"#,
+			FileType::Json,
+		)
+		.unwrap();
+		let over: ValueMap =
+			get_deselialized_value(r#"{"a": "s", "social": {"y": "c"}}"#, FileType::Json).unwrap();
+		merge_values(&mut base, over);
+		let expected: ValueMap = get_deselialized_value(
+			r#"{"a": "s", "social": {"x": "a", "y": "c"}}"#,
+			FileType::Json,
+		)
+		.unwrap();
+		assert_eq!(base, expected);
+	}
+}
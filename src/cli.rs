@@ -1,4 +1,8 @@
-use crate::cmd;
+use crate::{
+	app::i18n::catalog::{CliLang, lang_arg, set_flag_lang},
+	cli::prompt::show_error,
+	cmd, msg,
+};
 use combu::action_result;
 
 /// The prompt module provides prompt-based user input functionality.
@@ -8,5 +12,12 @@ mod stdio;
 
 /// Execute Program
 pub fn run() -> action_result!() {
+	// ヘルプの説明文も切り替わるよう、コマンドを組み立てる前に--langを適用する
+	if let Some(lang) = lang_arg(std::env::args().skip(1)) {
+		match CliLang::from_locale(&lang) {
+			Some(l) => set_flag_lang(Some(l)),
+			None => show_error(msg!(UnknownLang, lang)),
+		}
+	}
 	cmd::treed_cmd().run_with_auto_arg_collect()
 }
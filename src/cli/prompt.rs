@@ -21,6 +21,15 @@ pub fn inquiry_str(message: &str, default: &str) -> String {
 		.unwrap_or(default.into())
 }
 
+/// Prompts the user with an input question which can be left empty, and returns the user's response.
+pub fn inquiry_optional_str(message: &str) -> String {
+	input(message)
+		.required(false)
+		.interact()
+		.ok()
+		.unwrap_or_default()
+}
+
 /// Prompts the user with a selection question and returns the user's response.
 pub fn selector(message: &str, options: &[&str], default: &str) -> String {
 	let mut s = select(message);
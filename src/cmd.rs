@@ -1,16 +1,24 @@
 use combu::{
-	Command, Context, Flag, action_result, checks,
+	Command, Context, Flag, Vector, action_result, checks,
 	command::presets::func::help_tablize_with_alias_dedup, copyright, crate_authors, crate_license,
 	crate_version, done, flags, license, vector,
 };
-use common::sub_help;
+use common::{lang_flag, sub_help};
 
-use crate::nibi_copyright;
+use crate::{msg, nibi_copyright};
 
 /// The build command module.
 pub mod build;
+/// The check command module.
+pub mod check;
 /// The common module.
 mod common;
+/// The export command module.
+pub mod export;
+/// The igata command module.
+pub mod igata;
+/// The import command module.
+pub mod import;
 /// The init commandmodule.
 pub mod init;
 /// The new command module.
@@ -27,16 +35,31 @@ pub fn treed_cmd() -> Command {
 		Some(crate_authors!().to_owned()),
 		"nibi [subcommand] [options]".to_owned(),
 		flags!(),
-		flags!(help, version, license, authors, copyright),
+		common_flags(),
 		vector![],
 		crate_version!().to_owned(),
-		vector![sub_help(), init::cmd(), build::cmd(), new::threed_cmd()],
+		vector![
+			sub_help(),
+			init::cmd(),
+			build::cmd(),
+			check::cmd(),
+			new::threed_cmd(),
+			igata::cmd(),
+			export::cmd(),
+			import::cmd()
+		],
 	)
 }
 
+fn common_flags() -> Vector<Flag> {
+	let mut flags = flags!(help, version, license, authors, copyright);
+	flags.push(lang_flag());
+	flags
+}
+
 fn root_action(cmd: Command, ctx: Context) -> action_result!() {
 	checks!(cmd, ctx, [error, help, version, license]);
-	println!("サブコマンドの指定がないため、ヘルプを表示します");
+	println!("{}", msg!(NoSubcommand));
 	let help = help_tablize_with_alias_dedup(&cmd, &ctx);
 	println!("{help}");
 	done!()
@@ -1,8 +1,13 @@
-use combu::{Command, Context, Flag, Vector, action_result, done, flags, license, vector};
+use combu::{
+	Command, Context, Flag, FlagType, FlagValue, Vector, action_result, done, flags, license, vector,
+};
 
 use crate::{
-	app::{build::build, config::find_config_from_dir_path, fs::path::get_abs_path_from_option},
-	get_config_common, route_common,
+	app::{
+		build::{BuildOptions, build},
+		fs::path::get_abs_path_from_option,
+	},
+	get_config_common, msg, route_common,
 };
 
 use super::common::sub_help;
@@ -15,8 +20,8 @@ pub fn cmd() -> Command {
 		String::default(),
 		String::default(),
 		license![],
-		Some("build nibi project".to_owned()),
-		"nibi build [directory path: default is current]".to_owned(),
+		Some(msg!(BuildCmd)),
+		msg!(BuildUsage),
 		flags(),
 		flags![],
 		vector![],
@@ -25,18 +30,76 @@ pub fn cmd() -> Command {
 	)
 }
 
-/// Returns an empty `Vector` of flags for the `build` subcommand.
+/// Returns the flags for the `build` subcommand.
 pub fn flags() -> Vector<Flag> {
-	vector![]
+	vector![jobs_flag(), recipe_flag(), profile_flag()]
+}
+
+/// Returns the flag for the recipe to build with.
+pub fn recipe_flag() -> Flag {
+	Flag::with_all_field(
+		"recipe".to_owned(),
+		msg!(RecipeFlag),
+		vector!['r'],
+		vector![=>String, "tejyun"],
+		FlagType::String,
+		FlagValue::from(""),
+	)
+}
+
+/// Returns the flag for the build profile.
+pub fn profile_flag() -> Flag {
+	Flag::with_all_field(
+		"profile".to_owned(),
+		msg!(ProfileFlag),
+		vector!['p'],
+		Vector::default(),
+		FlagType::String,
+		FlagValue::from(""),
+	)
+}
+
+fn get_string_flag_from_context(ctx: &Context, flag_name: &str) -> Option<String> {
+	match ctx.get_inputted_local_flag_value_of(flag_name) {
+		Some(FlagValue::String(s)) if !s.trim().is_empty() => Some(s.trim().to_string()),
+		_ => None,
+	}
+}
+
+/// Returns the build options from the context.
+pub fn get_build_options_from_context(ctx: &Context) -> BuildOptions {
+	BuildOptions::new(get_jobs_from_context(ctx))
+		.recipe(get_string_flag_from_context(ctx, "recipe"))
+		.profile(get_string_flag_from_context(ctx, "profile"))
+}
+
+/// Returns the flag for the number of build threads.
+pub fn jobs_flag() -> Flag {
+	Flag::with_all_field(
+		"jobs".to_owned(),
+		msg!(JobsFlag),
+		vector!['j'],
+		vector![=>String, "threads"],
+		FlagType::Int,
+		FlagValue::Int(0),
+	)
+}
+
+/// Returns the number of build threads from the context.
+pub fn get_jobs_from_context(ctx: &Context) -> usize {
+	match ctx.get_inputted_local_flag_value_of("jobs") {
+		Some(FlagValue::Int(jobs)) if jobs > 0 => jobs as usize,
+		_ => 0,
+	}
 }
 
 /// Action function for the `build` subcommand. This executes build site function for the nibi project.
 pub fn build_action(_cmd: Command, ctx: Context) -> action_result!() {
 	let proj_path = get_abs_path_from_option(ctx.args.front());
-	println!("dir_path: {}", proj_path.display());
+	println!("{}", msg!(ProjectDir, proj_path.display()));
 	// 存在しているディレクトリか確認
 	if !proj_path.is_dir() {
-		println!("{} is not directory or does not exist", proj_path.display());
+		println!("{}", msg!(NotDirectory, proj_path.display()));
 		return done!();
 	}
 	let proj_path = proj_path.to_path_buf();
@@ -46,7 +109,7 @@ pub fn build_action(_cmd: Command, ctx: Context) -> action_result!() {
 	// config_pathからプロジェクトパスを修正
 	let proj_path = config_path.parent().unwrap().to_path_buf();
 
-	build(config, &proj_path);
+	build(config, &proj_path, get_build_options_from_context(&ctx));
 
 	done!()
 }
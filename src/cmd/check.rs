@@ -0,0 +1,115 @@
+use std::process::exit;
+
+use combu::{Command, Context, Flag, Vector, action_result, done, flags, license, vector};
+
+use crate::{
+	app::{
+		build::{BuildError, check},
+		fs::path::get_abs_path_from_option,
+	},
+	msg, route_common,
+};
+
+use super::{
+	build::{get_build_options_from_context, jobs_flag, profile_flag, recipe_flag},
+	common::{find_config, sub_help},
+};
+
+/// Returns a `Command` for the `check` subcommand.
+pub fn cmd() -> Command {
+	Command::with_all_field(
+		"check".to_owned(),
+		Some(route_common!(check_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(CheckCmd)),
+		msg!(CheckUsage),
+		flags(),
+		flags![],
+		vector![],
+		String::default(),
+		vector![sub_help()],
+	)
+}
+
+/// Returns the flags for the `check` subcommand.
+pub fn flags() -> Vector<Flag> {
+	vector![jobs_flag(), recipe_flag(), profile_flag()]
+}
+
+/// Action function for the `check` subcommand.
+/// This runs the build pipeline without writing the site, and exits with non-zero status if problems are found.
+pub fn check_action(_cmd: Command, ctx: Context) -> action_result!() {
+	let proj_path = get_abs_path_from_option(ctx.args.front());
+	if !proj_path.is_dir() {
+		println!("{}", msg!(NotDirectory, proj_path.display()));
+		exit(1);
+	}
+
+	let Some((config, config_path)) = find_config(&proj_path) else {
+		println!("{}", msg!(ConfigNotFound));
+		exit(1);
+	};
+	println!("{}", msg!(ConfigPath, config_path.display()));
+	let proj_path = config_path.parent().unwrap().to_path_buf();
+
+	let problems = check(&config, &proj_path, get_build_options_from_context(&ctx));
+	let code = report_problems(&problems);
+	if code != 0 {
+		exit(code);
+	}
+	done!()
+}
+
+/// Prints the problems found by `check` and returns the exit status.
+/// The status is non-zero if any problem is found, so that CI can fail on it.
+pub fn report_problems(problems: &[BuildError]) -> i32 {
+	if problems.is_empty() {
+		println!("{}", msg!(CheckPassed));
+		return 0;
+	}
+	for problem in problems.iter() {
+		println!("{problem}");
+	}
+	println!("{}", msg!(CheckFailed, problems.len()));
+	1
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use super::*;
+	use crate::app::{
+		build::BuildOptions,
+		testing::{TempDir, write_project},
+	};
+
+	#[test]
+	fn test_check() {
+		let dir = TempDir::new("check");
+		let config = write_project(&dir);
+		let problems = check(&config, &dir, BuildOptions::new(2));
+		assert!(problems.is_empty(), "{problems:?}");
+		assert_eq!(report_problems(&problems), 0);
+
+		fs::write(dir.join("zairyo/p00.ingot"), [0xff, 0xfe, 0xfd]).unwrap();
+		fs::write(
+			dir.join("recipe.ron"),
+			r#"(pack: ["theme", "missing"], overrides: (igata_table: {"post": "posts"}, values: {}))"#,
+		)
+		.unwrap();
+		let problems = check(&config, &dir, BuildOptions::new(2));
+		assert!(
+			problems
+				.iter()
+				.any(|p| matches!(p, BuildError::Ingot(path, _) if path.ends_with("p00.ingot")))
+		);
+		assert!(problems.iter().any(|p| matches!(p, BuildError::Pack(_))));
+		assert!(problems.iter().any(
+			|p| matches!(p, BuildError::IgataNotFound(key, name, _) if key == "post" && name == "posts")
+		));
+		assert_eq!(report_problems(&problems), 1);
+	}
+}
@@ -1,16 +1,36 @@
-use std::{env::current_dir, path::PathBuf};
+use std::{
+	env::current_dir,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
 
 use combu::{
-	Command, Context, Flag, FlagType, FlagValue, action::bundle::Bundle, action_result,
+	Command, Context, Flag, FlagType, FlagValue, Vector, action::bundle::Bundle, action_result,
 	command::presets::func::help_tablize_with_alias_dedup, preset_help_command, vector,
 };
 
-use crate::cli;
+use crate::{
+	app::{
+		config::{Config, find_config_from_dir_path},
+		i18n::catalog::{CliLang, set_config_lang},
+		serde::FileType,
+	},
+	cli, msg,
+};
 
 pub fn sub_help() -> Command {
 	preset_help_command!(help_tablize_with_alias_dedup)
 }
 
+/// Finds the config from the directory path, and uses the `cli_lang` setting of the found config for the messages.
+pub fn find_config(dir_path: &Path) -> Option<(Config, PathBuf)> {
+	let found = find_config_from_dir_path(dir_path);
+	if let Some((config, _)) = &found {
+		set_config_lang(CliLang::from_locale(config.get_i18n_conf().get_cli_lang()));
+	}
+	found
+}
+
 pub fn get_flagged_yes_no(bundle: &Bundle) -> Option<bool> {
 	if bundle
 		.get_local_flag_value_of("no")
@@ -30,7 +50,7 @@ pub fn get_flagged_yes_no(bundle: &Bundle) -> Option<bool> {
 }
 
 pub fn overwrite_confirm(yes_no_flag: Option<bool>) -> Option<bool> {
-	get_yes_no(yes_no_flag, "上書きしますか？")
+	get_yes_no(yes_no_flag, &msg!(OverwriteConfirm))
 }
 
 pub fn get_yes_no(yes_no_flag: Option<bool>, message: &str) -> Option<bool> {
@@ -64,7 +84,7 @@ pub fn take_to_bool_option(bundle: &mut Bundle, flag_name: &str) -> Option<bool>
 pub fn project_dir_flag() -> Flag {
 	Flag::with_all_field(
 		"project-dir".to_owned(),
-		"specify project(,or target) directory".to_owned(),
+		msg!(ProjectDirFlag),
 		vector!['d', 'c', 't'],
 		vector!["proj-dir", "pj-d", "target-directory", "target-dir";=>String],
 		FlagType::String,
@@ -72,6 +92,35 @@ pub fn project_dir_flag() -> Flag {
 	)
 }
 
+pub fn file_type_flag() -> Flag {
+	Flag::with_all_field(
+		"file-type".to_owned(),
+		msg!(FileTypeFlag),
+		vector!['f'],
+		vector!["ft", "file_type";=>String],
+		FlagType::String,
+		FlagValue::from("ron"),
+	)
+}
+
+pub fn lang_flag() -> Flag {
+	Flag::with_all_field(
+		"lang".to_owned(),
+		msg!(LangFlag),
+		Vector::default(),
+		vector![=>String, "language"],
+		FlagType::String,
+		FlagValue::from(""),
+	)
+}
+
+pub fn get_file_type_from_context(ctx: &Context) -> Option<FileType> {
+	match ctx.get_inputted_local_flag_value_of("file-type") {
+		Some(FlagValue::String(s)) => FileType::from_str(s.trim()).ok(),
+		_ => Some(FileType::default()),
+	}
+}
+
 pub fn get_proj_dir_from_context(ctx: &Context) -> PathBuf {
 	match ctx.get_inputted_local_flag_value_of("project-dir") {
 		Some(FlagValue::String(s)) => PathBuf::from(s),
@@ -101,13 +150,17 @@ macro_rules! nibi_copyright {
 
 #[macro_export]
 /// Macro for getting a config from a directory path.
+/// The second argument, if given, is evaluated instead of returning when the config is not found.
 macro_rules! get_config_common {
 	($dir: ident) => {
-		match find_config_from_dir_path(&$dir) {
+		$crate::get_config_common!($dir, return done!())
+	};
+	($dir: ident, $not_found: expr) => {
+		match $crate::cmd::common::find_config(&$dir) {
 			Some(c) => c,
 			_ => {
-				println!("config not found, please run `nibi init`");
-				return done!();
+				println!("{}", $crate::msg!(ConfigNotFound));
+				$not_found
 			}
 		}
 	};
@@ -0,0 +1,120 @@
+use std::{process::exit, str::FromStr};
+
+use combu::{
+	Command, Context, Flag, FlagType, FlagValue, Vector, action_result, done, flags, license, vector,
+};
+
+use crate::{
+	app::{
+		export::export,
+		fs::{
+			io::{open_file_with_overwrite_mode, write_str},
+			path::{get_abs_path, get_abs_path_from_option, to_parent_abs_path},
+		},
+		serde::{FileType, get_extended_path},
+	},
+	get_config_common, msg, route_common,
+};
+
+use super::{
+	build::{get_build_options_from_context, profile_flag, recipe_flag},
+	common::sub_help,
+};
+
+/// Returns a `Command` for the `export` subcommand.
+pub fn cmd() -> Command {
+	Command::with_all_field(
+		"export".to_owned(),
+		Some(route_common!(export_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(ExportCmd)),
+		msg!(ExportUsage),
+		flags(),
+		flags![],
+		vector![],
+		String::default(),
+		vector![sub_help()],
+	)
+}
+
+/// Returns the flags for the `export` subcommand.
+pub fn flags() -> Vector<Flag> {
+	vector![format_flag(), output_flag(), recipe_flag(), profile_flag()]
+}
+
+/// Returns the flag for the file type of the export.
+pub fn format_flag() -> Flag {
+	Flag::with_all_field(
+		"format".to_owned(),
+		msg!(FormatFlag),
+		vector!['f'],
+		vector!["file-type", "ft";=>String],
+		FlagType::String,
+		FlagValue::from("json"),
+	)
+}
+
+/// Returns the flag for the output file path of the export.
+pub fn output_flag() -> Flag {
+	Flag::with_all_field(
+		"output".to_owned(),
+		msg!(OutputFlag),
+		vector!['o'],
+		Vector::default(),
+		FlagType::String,
+		FlagValue::from(""),
+	)
+}
+
+/// Action function for the `export` subcommand.
+/// This writes the content model of the project into a file, and exits with non-zero status if it failed.
+pub fn export_action(_cmd: Command, ctx: Context) -> action_result!() {
+	let proj_path = get_abs_path_from_option(ctx.args.front());
+	if !proj_path.is_dir() {
+		println!("{}", msg!(NotDirectory, proj_path.display()));
+		exit(1);
+	}
+	let file_type = match ctx.get_inputted_local_flag_value_of("format") {
+		Some(FlagValue::String(s)) => FileType::from_str(s.trim()).ok(),
+		_ => Some(FileType::Json),
+	};
+	let Some(file_type) = file_type else {
+		println!("{}", msg!(UnknownFileType));
+		exit(1);
+	};
+
+	let (config, config_path) = get_config_common!(proj_path, exit(1));
+	let proj_path = to_parent_abs_path(config_path);
+
+	let (dump, problems) = match export(&config, &proj_path, &get_build_options_from_context(&ctx)) {
+		Ok(result) => result,
+		Err(e) => {
+			println!("{}", msg!(ExportFailed, e));
+			exit(1);
+		}
+	};
+	for problem in problems.iter() {
+		println!("{problem}");
+	}
+	let body = match dump.to_serialized_string(file_type) {
+		Ok(body) => body,
+		Err(e) => {
+			println!("{}", msg!(SerializeAsFailed, file_type, e));
+			exit(1);
+		}
+	};
+
+	let out_path = match ctx.get_inputted_local_flag_value_of("output") {
+		Some(FlagValue::String(s)) if !s.trim().is_empty() => get_abs_path(s.trim()),
+		_ => get_extended_path(proj_path.join("export"), file_type),
+	};
+	if let Err(e) = open_file_with_overwrite_mode(&out_path).and_then(|file| write_str(file, &body))
+	{
+		println!("{}", msg!(WriteFailed, out_path.display(), e));
+		exit(1);
+	}
+	println!("{}", msg!(Exported, dump.ingots.len(), out_path.display()));
+	done!()
+}
@@ -0,0 +1,367 @@
+use std::{path::PathBuf, process::exit};
+
+use combu::{
+	Command, Context, Flag, FlagType, FlagValue, Vector, action_result, alias, done, flags, license,
+	vector,
+};
+
+use crate::{
+	app::{
+		fs::path::{file_name, to_parent_abs_path},
+		igata::{
+			install::{
+				InstallError, Installed, PackSource, install_pack, read_pack_lock, update_pack,
+			},
+			manage::{PackEntry, fork_pack, list_packs, pack_summary, validate_pack},
+			pack::{PackProperties, get_packs_from_names},
+		},
+	},
+	cmd::common::{find_config, get_proj_dir_from_context, project_dir_flag, sub_help},
+	get_config_common, msg, route_common,
+};
+
+/// Returns the [igata] command for the Nibi CLI.
+pub fn cmd() -> Command {
+	Command::with_all_field(
+		"igata".to_owned(),
+		Some(route_common!(not_specified_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(IgataCmd)),
+		"nibi igata [list/info/validate/fork/install/update] [options]".to_owned(),
+		flags![],
+		vector![],
+		alias!["igt", "pack"],
+		String::default(),
+		vector![
+			sub_help(),
+			list_cmd(),
+			info_cmd(),
+			validate_cmd(),
+			fork_cmd(),
+			install_cmd(),
+			update_cmd()
+		],
+	)
+}
+
+/// The action for the [igata] command when no subcommand is specified.
+pub fn not_specified_action(_cmd: Command, _ctx: Context) -> action_result!() {
+	println!("{}", msg!(SpecifySubcommand));
+	println!("{}", msg!(AvailableSubcommands));
+	println!("\t + list");
+	println!("\t + info");
+	println!("\t + validate");
+	println!("\t + fork");
+	println!("\t + install");
+	println!("\t + update");
+	done!()
+}
+
+/// Returns the [igata info] command for the Nibi CLI.
+pub fn info_cmd() -> Command {
+	Command::with_all_field(
+		"info".to_owned(),
+		Some(route_common!(info_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(IgataInfoCmd)),
+		msg!(IgataInfoUsage),
+		vector![project_dir_flag()],
+		vector![],
+		alias!["show"],
+		String::default(),
+		vector![sub_help()],
+	)
+}
+
+/// Finds the pack with the given name in the igata directory of the project.
+/// Prints the problems and returns `None` if the pack could not be resolved.
+fn find_pack(ctx: &Context, pack_name: &str) -> Option<(PackProperties, PathBuf)> {
+	let igata_dir = get_igata_dir_from_context(ctx)?;
+	let pack_name = pack_name.trim().to_string();
+	let (mut packs, errors) = get_packs_from_names(std::slice::from_ref(&pack_name), &igata_dir);
+	for e in errors.iter() {
+		println!("{e}");
+	}
+	packs.remove(&pack_name).map(|pack| (pack, igata_dir))
+}
+
+/// The action for the [igata info] command.
+pub fn info_action(_cmd: Command, ctx: Context) -> action_result!() {
+	let Some(pack_name) = ctx.args.front() else {
+		println!("{}", msg!(SpecifyPackName));
+		return done!();
+	};
+	if let Some((pack, _)) = find_pack(&ctx, pack_name) {
+		println!("{}", pack.describe());
+	}
+	done!()
+}
+
+/// Returns the [igata list] command for the Nibi CLI.
+pub fn list_cmd() -> Command {
+	Command::with_all_field(
+		"list".to_owned(),
+		Some(route_common!(list_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(IgataListCmd)),
+		"nibi igata list".to_owned(),
+		vector![project_dir_flag()],
+		vector![],
+		alias!["ls"],
+		String::default(),
+		vector![sub_help()],
+	)
+}
+
+/// The action for the [igata list] command.
+pub fn list_action(_cmd: Command, ctx: Context) -> action_result!() {
+	let proj_dir = get_proj_dir_from_context(&ctx);
+	let (config, config_path) = get_config_common!(proj_dir);
+	let proj_path = to_parent_abs_path(config_path);
+	let igata_dir = config.get_dir_conf().get_igata_path(&proj_path);
+
+	match list_packs(&igata_dir) {
+		Ok(entries) if entries.is_empty() => {
+			println!("{}", msg!(NoPackFound));
+		}
+		Ok(entries) => {
+			for entry in entries {
+				match entry {
+					PackEntry::Pack(pack) => {
+						let dir_name = file_name(pack.get_directory()).unwrap_or_default();
+						if dir_name == pack.get_pack_name() {
+							println!("{}", pack_summary(pack.get_info()));
+						} else {
+							println!(
+								"{}",
+								msg!(PackInDirectory, pack_summary(pack.get_info()), dir_name)
+							);
+						}
+					}
+					PackEntry::Invalid(dir, e) => {
+						println!("{}", msg!(PackInfoNotFound, dir.display(), e));
+					}
+				}
+			}
+		}
+		Err(e) => {
+			println!("{}", msg!(IgataDirReadFailed, e));
+		}
+	}
+	done!()
+}
+
+/// Returns the [igata validate] command for the Nibi CLI.
+pub fn validate_cmd() -> Command {
+	Command::with_all_field(
+		"validate".to_owned(),
+		Some(route_common!(validate_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(IgataValidateCmd)),
+		msg!(IgataValidateUsage),
+		vector![project_dir_flag()],
+		vector![],
+		alias!["check"],
+		String::default(),
+		vector![sub_help()],
+	)
+}
+
+/// The action for the [igata validate] command.
+/// Exits with non-zero status if problems are found.
+pub fn validate_action(_cmd: Command, ctx: Context) -> action_result!() {
+	let Some(pack_name) = ctx.args.front() else {
+		println!("{}", msg!(SpecifyPackName));
+		exit(1);
+	};
+	let Some((pack, _)) = find_pack(&ctx, pack_name) else {
+		exit(1);
+	};
+	let problems = validate_pack(&pack);
+	if problems.is_empty() {
+		println!("{}", msg!(ValidationPassed));
+		return done!();
+	}
+	for problem in problems.iter() {
+		println!("{problem}");
+	}
+	println!("{}", msg!(ValidationFailed, problems.len()));
+	exit(1);
+}
+
+/// Returns the [igata fork] command for the Nibi CLI.
+pub fn fork_cmd() -> Command {
+	Command::with_all_field(
+		"fork".to_owned(),
+		Some(route_common!(fork_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(IgataForkCmd)),
+		msg!(IgataForkUsage),
+		vector![project_dir_flag()],
+		vector![],
+		alias!["copy", "cp"],
+		String::default(),
+		vector![sub_help()],
+	)
+}
+
+/// The action for the [igata fork] command.
+pub fn fork_action(_cmd: Command, ctx: Context) -> action_result!() {
+	let (Some(pack_name), Some(new_name)) = (ctx.args.front(), ctx.args.get(1)) else {
+		println!("{}", msg!(SpecifyForkNames));
+		return done!();
+	};
+	if let Some((pack, igata_dir)) = find_pack(&ctx, pack_name) {
+		match fork_pack(&pack, &igata_dir, new_name.to_owned()) {
+			Ok(dir) => println!("{}", msg!(PackForked, pack.get_pack_name(), dir.display())),
+			Err(e) => println!("{}", msg!(ForkFailed, e)),
+		}
+	}
+	done!()
+}
+
+/// Returns the [igata install] command for the Nibi CLI.
+pub fn install_cmd() -> Command {
+	Command::with_all_field(
+		"install".to_owned(),
+		Some(route_common!(install_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(IgataInstallCmd)),
+		msg!(IgataInstallUsage),
+		vector![
+			project_dir_flag(),
+			Flag::with_all_field(
+				"force".to_owned(),
+				msg!(InstallForceFlag),
+				vector![],
+				Vector::default(),
+				FlagType::Bool,
+				FlagValue::Bool(false),
+			)
+		],
+		vector![],
+		alias!["add", "i"],
+		String::default(),
+		vector![sub_help()],
+	)
+}
+
+fn get_igata_dir_from_context(ctx: &Context) -> Option<PathBuf> {
+	let proj_dir = get_proj_dir_from_context(ctx);
+	let Some((config, config_path)) = find_config(&proj_dir) else {
+		println!("{}", msg!(ConfigNotFound));
+		return None;
+	};
+	let proj_path = to_parent_abs_path(config_path);
+	Some(config.get_dir_conf().get_igata_path(&proj_path))
+}
+
+fn print_installed(installed: &Installed) {
+	let info = installed.pack.get_info();
+	match &installed.previous {
+		Some(prev) => println!(
+			"{}",
+			msg!(PackUpdated, info.name, prev.version, info.version)
+		),
+		None => println!(
+			"{}",
+			msg!(
+				PackInstalled,
+				info.name,
+				info.version,
+				installed.pack.get_directory().display()
+			)
+		),
+	}
+}
+
+/// The action for the [igata install] command.
+/// Exits with non-zero status if the installation failed.
+pub fn install_action(cmd: Command, ctx: Context) -> action_result!() {
+	let Some(src) = ctx.args.front() else {
+		println!("{}", msg!(SpecifyPackSource));
+		exit(1);
+	};
+	let Some(igata_dir) = get_igata_dir_from_context(&ctx) else {
+		exit(1);
+	};
+	let force = ctx.is_flag_true("force", &cmd);
+	let result = PackSource::detect(src)
+		.ok_or_else(|| InstallError::UnknownSource(src.to_owned()))
+		.and_then(|source| install_pack(source, &igata_dir, force, None));
+	match result {
+		Ok(installed) => print_installed(&installed),
+		Err(e) => {
+			println!("{}", msg!(InstallFailed, e));
+			exit(1);
+		}
+	}
+	done!()
+}
+
+/// Returns the [igata update] command for the Nibi CLI.
+pub fn update_cmd() -> Command {
+	Command::with_all_field(
+		"update".to_owned(),
+		Some(route_common!(update_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(IgataUpdateCmd)),
+		msg!(IgataUpdateUsage),
+		vector![project_dir_flag()],
+		vector![],
+		alias!["up", "upgrade"],
+		String::default(),
+		vector![sub_help()],
+	)
+}
+
+/// The action for the [igata update] command.
+/// Exits with non-zero status if some packs failed to update.
+pub fn update_action(_cmd: Command, ctx: Context) -> action_result!() {
+	let Some(igata_dir) = get_igata_dir_from_context(&ctx) else {
+		exit(1);
+	};
+	let names = if ctx.args.is_empty() {
+		match read_pack_lock(&igata_dir) {
+			Ok(lock) => lock.packs.into_keys().collect::<Vec<_>>(),
+			Err(e) => {
+				println!("{}", msg!(LockReadFailed, e));
+				exit(1);
+			}
+		}
+	} else {
+		ctx.args.iter().map(|s| s.trim().to_string()).collect()
+	};
+	if names.is_empty() {
+		println!("{}", msg!(NoInstalledPack));
+		return done!();
+	}
+	let mut failed = 0;
+	for name in names.iter() {
+		match update_pack(name, &igata_dir) {
+			Ok(installed) => print_installed(&installed),
+			Err(e) => {
+				failed += 1;
+				println!("{}", msg!(UpdateFailed, name, e));
+			}
+		}
+	}
+	if failed > 0 {
+		exit(1);
+	}
+	done!()
+}
@@ -0,0 +1,184 @@
+use std::{path::Path, process::exit};
+
+use combu::{
+	Command, Context, Flag, FlagType, FlagValue, Vector, action_result, alias, done, flags, license,
+	vector,
+};
+
+use crate::{
+	app::{
+		fs::path::{get_abs_path_from_option, to_parent_abs_path},
+		import::{
+			ImportDest, ImportError, ImportReport, hugo::import_hugo, jekyll::import_jekyll,
+			wordpress::import_wordpress,
+		},
+		serde::FileType,
+	},
+	cmd::common::{
+		file_type_flag, find_config, get_file_type_from_context, get_proj_dir_from_context,
+		project_dir_flag, sub_help,
+	},
+	msg, route_common,
+};
+
+/// Returns the [import] command for the Nibi CLI.
+pub fn cmd() -> Command {
+	Command::with_all_field(
+		"import".to_owned(),
+		Some(route_common!(not_specified_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(ImportCmd)),
+		"nibi import [wordpress/hugo/jekyll] [options]".to_owned(),
+		flags![],
+		vector![],
+		alias!["imp"],
+		String::default(),
+		vector![sub_help(), wordpress_cmd(), hugo_cmd(), jekyll_cmd()],
+	)
+}
+
+/// The action for the [import] command when no subcommand is specified.
+pub fn not_specified_action(_cmd: Command, _ctx: Context) -> action_result!() {
+	println!("{}", msg!(SpecifySubcommand));
+	println!("{}", msg!(AvailableSubcommands));
+	println!("\t + wordpress");
+	println!("\t + hugo");
+	println!("\t + jekyll");
+	done!()
+}
+
+fn force_flag() -> Flag {
+	Flag::with_all_field(
+		"force".to_owned(),
+		msg!(ImportForceFlag),
+		vector![],
+		Vector::default(),
+		FlagType::Bool,
+		FlagValue::Bool(false),
+	)
+}
+
+/// Returns the [import wordpress] command for the Nibi CLI.
+pub fn wordpress_cmd() -> Command {
+	Command::with_all_field(
+		"wordpress".to_owned(),
+		Some(route_common!(wordpress_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(ImportWordpressCmd)),
+		msg!(ImportWordpressUsage),
+		vector![project_dir_flag(), file_type_flag(), force_flag()],
+		vector![],
+		alias!["wp", "wxr"],
+		String::default(),
+		vector![sub_help()],
+	)
+}
+
+/// Prints the result of the import.
+pub fn print_import_report(report: &ImportReport) {
+	for path in report.files.iter() {
+		println!("{}", msg!(ImportWrote, path.display()));
+	}
+	if !report.resources.is_empty() {
+		println!("{}", msg!(ImportCopiedResources, report.resources.len()));
+	}
+	for skipped in report.skipped.iter() {
+		println!("{}", msg!(ImportSkipped, skipped));
+	}
+	for (path, reason) in report.rejected.iter() {
+		println!("{}", msg!(ImportRejected, path.display(), reason));
+	}
+	println!(
+		"{}",
+		msg!(ImportSummary, report.ingots.len(), report.rejected.len())
+	);
+}
+
+/// Returns the [import hugo] command for the Nibi CLI.
+pub fn hugo_cmd() -> Command {
+	Command::with_all_field(
+		"hugo".to_owned(),
+		Some(route_common!(hugo_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(ImportHugoCmd)),
+		msg!(ImportHugoUsage),
+		vector![project_dir_flag(), file_type_flag(), force_flag()],
+		vector![],
+		alias![],
+		String::default(),
+		vector![sub_help()],
+	)
+}
+
+/// Returns the [import jekyll] command for the Nibi CLI.
+pub fn jekyll_cmd() -> Command {
+	Command::with_all_field(
+		"jekyll".to_owned(),
+		Some(route_common!(jekyll_action)),
+		String::default(),
+		String::default(),
+		license![],
+		Some(msg!(ImportJekyllCmd)),
+		msg!(ImportJekyllUsage),
+		vector![project_dir_flag(), file_type_flag(), force_flag()],
+		vector![],
+		alias![],
+		String::default(),
+		vector![sub_help()],
+	)
+}
+
+type ImportFn = fn(&Path, &ImportDest, FileType, bool) -> Result<ImportReport, ImportError>;
+
+/// Runs the import from the source given as the first argument into the project.
+/// Exits with non-zero status if the import failed.
+fn run_import(cmd: &Command, ctx: &Context, import: ImportFn) {
+	let Some(src) = ctx.args.front() else {
+		println!("{}", msg!(SpecifyImportSource));
+		exit(1);
+	};
+	let src = get_abs_path_from_option(Some(src));
+	let Some(file_type) = get_file_type_from_context(ctx) else {
+		println!("{}", msg!(UnknownFileType));
+		exit(1);
+	};
+	let proj_dir = get_proj_dir_from_context(ctx);
+	let Some((config, config_path)) = find_config(&proj_dir) else {
+		println!("{}", msg!(ConfigNotFound));
+		exit(1);
+	};
+	let proj_path = to_parent_abs_path(config_path);
+	let dest = ImportDest::from_config(&config, &proj_path);
+
+	match import(&src, &dest, file_type, ctx.is_flag_true("force", cmd)) {
+		Ok(report) => print_import_report(&report),
+		Err(e) => {
+			println!("{}", msg!(ImportFailed, e));
+			exit(1);
+		}
+	}
+}
+
+/// The action for the [import wordpress] command.
+pub fn wordpress_action(cmd: Command, ctx: Context) -> action_result!() {
+	run_import(&cmd, &ctx, import_wordpress);
+	done!()
+}
+
+/// The action for the [import hugo] command.
+pub fn hugo_action(cmd: Command, ctx: Context) -> action_result!() {
+	run_import(&cmd, &ctx, import_hugo);
+	done!()
+}
+
+/// The action for the [import jekyll] command.
+pub fn jekyll_action(cmd: Command, ctx: Context) -> action_result!() {
+	run_import(&cmd, &ctx, import_jekyll);
+	done!()
+}
@@ -9,15 +9,19 @@ use std::{fs, io::ErrorKind::AlreadyExists};
 use strum::VariantNames;
 
 use crate::app::config::default_config_file_type;
-use crate::app::fs::path::{file_name, get_abs_path_from_option, get_dir_path_string};
+use crate::app::fs::path::{
+	file_name, get_abs_path, get_abs_path_from_option, get_dir_path_string,
+};
+use crate::app::import::{ImportDest, ingots::import_ingots_dir};
 use crate::app::serde::FileType;
-use crate::cli::prompt::{Spinner, inquiry_str, selector, show_error};
+use crate::cli::prompt::{Spinner, inquiry_optional_str, inquiry_str, selector, show_error};
 use crate::cmd::common::{get_yes_no, get_yes_no_with_default};
-use crate::route_common;
+use crate::cmd::import::print_import_report;
 use crate::{
 	app::config::{self, Config},
 	cmd::common::overwrite_confirm,
 };
+use crate::{msg, route_common};
 
 use super::common::{get_flagged_yes_no, sub_help, take_to_bool_option, take_to_string_option};
 
@@ -29,8 +33,8 @@ pub fn cmd() -> Command {
 		String::default(),
 		String::default(),
 		license![],
-		Some("init nibi project".to_owned()),
-		"nibi init [directory path: default is current]".to_owned(),
+		Some(msg!(InitCmd)),
+		msg!(InitUsage),
 		flags(),
 		flags![],
 		vector![],
@@ -45,7 +49,7 @@ pub fn flags() -> Vector<Flag> {
 		no_flag!(),
 		Flag::with_all_field(
 			"import-ingots-dir-path".to_owned(),
-			"import ingots path: 取り込みたいingotsを収納したディレクトリのパス".to_owned(),
+			msg!(ImportIngotsDirFlag),
 			vector!['i'],
 			Vector::default(),
 			FlagType::String,
@@ -53,7 +57,7 @@ pub fn flags() -> Vector<Flag> {
 		),
 		Flag::with_all_field(
 			"skip-create-prompt".to_owned(),
-			"skip prompt for project information: 初期化時のプロンプトをスキップ".to_owned(),
+			msg!(SkipCreatePromptFlag),
 			vector![],
 			vector![=>String, "skip_create_prompt", "skip-prompt", "skip_prompt"],
 			FlagType::Bool,
@@ -61,7 +65,7 @@ pub fn flags() -> Vector<Flag> {
 		),
 		Flag::with_all_field(
 			"force".to_owned(),
-			"force init dir: ファイルが存在しても強制的に初期化".to_owned(),
+			msg!(InitForceFlag),
 			vector!['f'],
 			Vector::default(),
 			FlagType::Bool,
@@ -69,7 +73,7 @@ pub fn flags() -> Vector<Flag> {
 		),
 		Flag::with_all_field(
 			"project-name".to_owned(),
-			"project name: プロジェクト名".to_owned(),
+			msg!(ProjectName),
 			vector!['p'],
 			vector![=>String, "pn", "p-name", "name", "project_name", "p_name"],
 			FlagType::String,
@@ -77,7 +81,7 @@ pub fn flags() -> Vector<Flag> {
 		),
 		Flag::with_all_field(
 			"site-name".to_owned(),
-			"site name: サイト名".to_owned(),
+			msg!(SiteName),
 			vector!['s'],
 			vector![=>String,"sn","s-name", "s_name", "site_name"],
 			FlagType::String,
@@ -85,7 +89,7 @@ pub fn flags() -> Vector<Flag> {
 		),
 		Flag::with_all_field(
 			"config-file-type".to_owned(),
-			"file type of config: コンフィグファイルの形式".to_owned(),
+			msg!(ConfigFileType),
 			vector!['t'],
 			vector![=>String, "cft", "config-ft", "config_ft", "config_file_type"],
 			FlagType::String,
@@ -93,8 +97,7 @@ pub fn flags() -> Vector<Flag> {
 		),
 		Flag::with_all_field(
 			"vcs".to_owned(),
-			"will under management of vcs: 初期化後vcs(git)の初期化処理を実行してvcsの管理下に置く"
-				.to_owned(),
+			msg!(VcsFlag),
 			vector!['v'],
 			vector![=>String, "git"],
 			FlagType::Bool,
@@ -156,10 +159,8 @@ impl InitConfig {
 		self.vcs.unwrap_or(true)
 	}
 }
-//const INIT_EARLY_EXIT_MESSAGE: &str = "初期化処理を中断し、プログラムを終了します。";
-
 fn get_early_exit_message() -> String {
-	"初期化処理を中断し、プログラムを終了します。".to_string()
+	msg!(InitEarlyExit)
 }
 
 fn init(mut init_config: InitConfig) {
@@ -170,7 +171,7 @@ fn init(mut init_config: InitConfig) {
 	}
 
 	// init先フォルダの状態確認となければ作成
-	let spinner = Spinner::start("プロジェクトフォルダを作成中...");
+	let spinner = Spinner::start(&msg!(CreatingProjectDir));
 	match create_root_dir(&dir_path, init_config.get_force_yes_no()) {
 		Ok(msg) => spinner.end(msg),
 		Err(msg) => {
@@ -182,7 +183,7 @@ fn init(mut init_config: InitConfig) {
 
 	let config = get_config_from_init_config(&init_config);
 
-	let spinner = Spinner::start("コンフィグファイルを作成中...");
+	let spinner = Spinner::start(&msg!(CreatingConfigFile));
 	match create_config_file(
 		&dir_path,
 		&config,
@@ -197,7 +198,7 @@ fn init(mut init_config: InitConfig) {
 		}
 	};
 
-	let spinner = Spinner::start("サブフォルダ群を作成中...");
+	let spinner = Spinner::start(&msg!(CreatingSrcDirs));
 	match create_src_dirs(&config, &dir_path) {
 		Ok(msg) => spinner.end(msg),
 		Err(msg) => {
@@ -207,17 +208,36 @@ fn init(mut init_config: InitConfig) {
 		}
 	}
 
+	if let Some(ingots_dir_path) = init_config
+		.ingots_dir_path
+		.as_ref()
+		.filter(|p| !p.trim().is_empty())
+	{
+		import_ingots(
+			&config,
+			&dir_path,
+			&get_abs_path(ingots_dir_path.trim()),
+			init_config.force,
+		);
+	}
+
 	if init_config.vcs_init() {
 		init_vcs(&dir_path);
 	}
 }
 
+fn import_ingots(config: &Config, dir_path: &Path, ingots_dir_path: &Path, force: bool) {
+	let dest = ImportDest::from_config(config, dir_path);
+	println!("{}", msg!(ImportIngotsFrom, ingots_dir_path.display()));
+	match import_ingots_dir(ingots_dir_path, &dest, force) {
+		Ok(report) => print_import_report(&report),
+		Err(e) => show_error(msg!(ImportIngotsFailed, e)),
+	}
+}
+
 fn create_root_dir(dir_path: &Path, yes_no: Option<bool>) -> Result<String, String> {
 	match fs::create_dir(dir_path) {
-		Ok(_) => Ok(format!(
-			"プロジェクトフォルダ: {}を作成しました。",
-			dir_path.display()
-		)),
+		Ok(_) => Ok(msg!(ProjectDirCreated, dir_path.display())),
 		Err(mut err) => {
 			if err.kind() == AlreadyExists {
 				// プロジェクトフォルダが存在する場合
@@ -225,23 +245,11 @@ fn create_root_dir(dir_path: &Path, yes_no: Option<bool>) -> Result<String, Stri
 					Ok(mut i) => {
 						return if i.next().is_none() {
 							// フォルダが空の場合
-							Ok(format!(
-								"空のプロジェクトフォルダ{}が既に存在します",
-								dir_path.display()
-							))
+							Ok(msg!(EmptyProjectDirExists, dir_path.display()))
 						} else {
-							match get_yes_no(
-								yes_no,
-								"指定されたディレクトリは空ではありません\nこのまま指定されたディレクトリを使用して初期化しますか？",
-							) {
-								Some(true) => Ok(format!(
-									"空のプロジェクトフォルダ{}を使用して初期化します",
-									dir_path.display()
-								)),
-								_ => Err(format!(
-									"{}が既に存在するため、初期化プロセスを中断しました。",
-									dir_path.display()
-								)),
+							match get_yes_no(yes_no, &msg!(NotEmptyDirConfirm)) {
+								Some(true) => Ok(msg!(InitWithExistingDir, dir_path.display())),
+								_ => Err(msg!(ProjectDirExists, dir_path.display())),
 							}
 						};
 					}
@@ -251,39 +259,34 @@ fn create_root_dir(dir_path: &Path, yes_no: Option<bool>) -> Result<String, Stri
 				// 他のエラーなら再作成してみてエラーハンドリング
 				match fs::create_dir_all(dir_path) {
 					Ok(_) => {
-						return Ok(format!(
-							"プロジェクトフォルダ: {}を作成しました。",
-							dir_path.display()
-						));
+						return Ok(msg!(ProjectDirCreated, dir_path.display()));
 					}
 					Err(e) => err = e,
 				}
 			}
-			Err(format!("指定されたパスでエラーが発生しました:{err}"))
+			Err(msg!(PathError, err))
 		}
 	}
 }
 
 fn prompt_init_config(init_config: &mut InitConfig) {
 	// init_configをプロンプトで補足する
-	println!(
-		"input your project information for initialization.: 初期化のためのプロジェクト情報を入力してください。"
-	);
+	println!("{}", msg!(InputProjectInfo));
 	if init_config.project_name.is_none() {
 		let dir_path = file_name(&init_config.dir_path).unwrap_or_default();
-		let project_name = inquiry_str("project name", &dir_path);
+		let project_name = inquiry_str(&msg!(ProjectName), &dir_path);
 		init_config.project_name = Some(project_name);
 	};
 
 	if init_config.site_name.is_none() {
 		let pn = init_config.project_name.as_ref().unwrap();
-		let sn = inquiry_str("site name", pn);
+		let sn = inquiry_str(&msg!(SiteName), pn);
 		init_config.site_name = Some(sn);
 	}
 
 	if init_config.config_file_type.is_none() {
 		let config_file_type = FileType::from_str(&selector(
-			"config file type",
+			&msg!(ConfigFileType),
 			FileType::VARIANTS,
 			&default_config_file_type().to_string(),
 		))
@@ -291,10 +294,15 @@ fn prompt_init_config(init_config: &mut InitConfig) {
 		init_config.config_file_type = Some(config_file_type);
 	}
 
+	if init_config.ingots_dir_path.is_none() {
+		let ingots_dir_path = inquiry_optional_str(&msg!(IngotsDirToImport));
+		init_config.ingots_dir_path = Some(ingots_dir_path);
+	}
+
 	if init_config.vcs.is_none() {
 		init_config.vcs = Some(get_yes_no_with_default(
 			init_config.yes_no,
-			"初期化プロセスの最後にgit init を行いますか？",
+			&msg!(GitInitConfirm),
 			true,
 		));
 	}
@@ -320,24 +328,19 @@ fn create_config_file(
 ) -> Result<String, String> {
 	let config_path = config::get_config_path(dir_path, file_type.to_string().as_str());
 	match config::create_config_file(&config_path, config, file_type) {
-		Ok(_) => Ok(format!(
-			"configファイルを作成しました: {}",
-			config_path.display()
-		)),
+		Ok(_) => Ok(msg!(ConfigFileCreated, config_path.display())),
 		Err(err) => {
 			if err.kind() == AlreadyExists {
 				let yes_no = overwrite_confirm(yes_no);
 				match yes_no {
 					Some(true) => match config::reset_config_file(&config_path, config) {
-						Err(err) => Err(format!("コンフィグの初期化処理に失敗しました: {err}")),
-						_ => Ok("コンフィグファイルを上書きしました".to_string()),
+						Err(err) => Err(msg!(ConfigResetFailed, err)),
+						_ => Ok(msg!(ConfigFileOverwritten)),
 					},
-					_ => Err("上書きしません。".to_owned()),
+					_ => Err(msg!(NotOverwritten)),
 				}
 			} else {
-				Err(format!(
-					"コンフィグファイルの作成中にエラーが発生しました: {err}"
-				))
+				Err(msg!(ConfigFileCreateError, err))
 			}
 		}
 	}
@@ -353,26 +356,22 @@ fn create_src_dirs(config: &Config, root_dir: &Path) -> Result<String, String> {
 					msg.push('\n');
 				}
 				if e.0.kind() == AlreadyExists {
-					msg.push_str(&format!("ディレクトリ {} は既に存在します", e.1.display()));
+					msg.push_str(&msg!(DirExists, e.1.display()));
 				} else {
-					msg.push_str(&format!(
-						"ディレクトリ {} の作成中にエラーが発生しました: {}",
-						e.1.display(),
-						e.0
-					));
+					msg.push_str(&msg!(DirCreateError, e.1.display(), e.0));
 					r = false;
 				}
 			}
 			if r { Ok(msg) } else { Err(msg) }
 		}
-		_ => Ok("ソースフォルダ群を作成しました".to_string()),
+		_ => Ok(msg!(SrcDirsCreated)),
 	}
 }
 
 fn init_vcs(dir_path: &Path) {
 	let dir_path_str = get_dir_path_string(dir_path);
 	let cmd = vec!["git", "init", &dir_path_str];
-	println!("init vcs by: {}", cmd.join(" "));
+	println!("{}", msg!(InitVcs, cmd.join(" ")));
 	let result = exes(cmd);
 	println!("{result}");
 }
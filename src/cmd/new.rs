@@ -1,12 +1,12 @@
 use combu::{Command, Context, Flag, action_result, alias, done, flags, license, vector};
 
 use crate::{
-	app::{
-		config::find_config_from_dir_path, fs::path::to_parent_abs_path,
-		igata::pack::create_new_pack, recipe::create_new_recipe,
+	app::{fs::path::to_parent_abs_path, igata::pack::create_new_pack, recipe::create_new_recipe},
+	cmd::common::{
+		file_type_flag, get_file_type_from_context, get_proj_dir_from_context, project_dir_flag,
+		sub_help,
 	},
-	cmd::common::{get_proj_dir_from_context, project_dir_flag, sub_help},
-	get_config_common, route_common,
+	get_config_common, msg, route_common,
 };
 
 /// Returns the [new] command for the Nibi CLI.
@@ -17,7 +17,7 @@ pub fn threed_cmd() -> Command {
 		String::default(),
 		String::default(),
 		license![],
-		Some("create new [site(project)/ingot(post)/igata(template)/igata_set(theme)]".to_owned()),
+		Some(msg!(NewCmd)),
 		"nibi new [site(project)/ingot(post)/igata(template)/igata_set(theme)]".to_owned(),
 		flags![],
 		vector![],
@@ -35,8 +35,8 @@ pub fn new_igt_pack_cmd() -> Command {
 		String::default(),
 		String::default(),
 		license![],
-		Some("create new igata_pack (, or theme).".to_owned()),
-		"nibi new igata_pack [igata set's name]".to_owned(),
+		Some(msg!(NewIgataPackCmd)),
+		msg!(NewIgataPackUsage),
 		vector![project_dir_flag()],
 		vector![],
 		new_igt_pack_alias().into(),
@@ -47,8 +47,8 @@ pub fn new_igt_pack_cmd() -> Command {
 
 /// The action for the [new] command when no target is specified.
 pub fn not_specified_target_action(_cmd: Command, _ctx: Context) -> action_result!() {
-	println!("specify new target: 新しく作成するものを指定してください。");
-	println!("now available target: ");
+	println!("{}", msg!(SpecifyNewTarget));
+	println!("{}", msg!(AvailableTargets));
 	println!("\t + igata_pack ({})", new_igt_pack_alias().join("/"));
 	done!()
 }
@@ -86,7 +86,7 @@ pub fn new_igt_pack_action(_cmd: Command, ctx: Context) -> action_result!() {
 			igata_pack_name.trim().to_string(),
 		);
 	} else {
-		println!("specify theme name: テーマ名を指定してください。");
+		println!("{}", msg!(SpecifyThemeName));
 	}
 	done!()
 }
@@ -99,9 +99,9 @@ pub fn new_recipe_cmd() -> Command {
 		String::default(),
 		String::default(),
 		license![],
-		Some("create new recipe".to_owned()),
-		"nibi new recipe [recipe name]".to_owned(),
-		vector![project_dir_flag()],
+		Some(msg!(NewRecipeCmd)),
+		msg!(NewRecipeUsage),
+		vector![project_dir_flag(), file_type_flag()],
 		vector![],
 		alias!["tejyun"],
 		String::default(),
@@ -112,12 +112,16 @@ pub fn new_recipe_cmd() -> Command {
 /// The action for the [new recipe] command.
 pub fn new_recipe_action(_cmd: Command, ctx: Context) -> action_result!() {
 	if let Some(recipe_name) = ctx.args.front() {
+		let Some(file_type) = get_file_type_from_context(&ctx) else {
+			println!("{}", msg!(UnknownFileType));
+			return done!();
+		};
 		let proj_dir = get_proj_dir_from_context(&ctx);
 		let (_config, config_path) = get_config_common!(proj_dir);
 
 		let proj_path = to_parent_abs_path(config_path);
 
-		create_new_recipe(&proj_path, recipe_name.to_owned());
+		create_new_recipe(&proj_path, recipe_name.to_owned(), file_type);
 	}
 	done!()
 }